@@ -0,0 +1,218 @@
+// src/bin/batch.rs
+//
+// Headless evaluation: run every algorithm over a directory of scenario
+// JSON files (the `fixtures/scenarios/` format) and write one CSV row per
+// scenario and algorithm. Each run is a journey, not a single search: the
+// rover walks its plan, detects the scenario's optional `dobs` the way the
+// app's default sensor does, and replans when one converts.
+//
+//     cargo run --release --bin batch
+//     cargo run --release --bin batch -- my_maps/ --out results.csv
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use scout::pathfinding::movement::densify;
+use scout::pathfinding::{build_planner, Connectivity, Coord, PlannerOptions, ALGORITHMS};
+
+const OUT_FLAG: &str = "--out";
+const DEFAULT_DIR: &str = "fixtures/scenarios";
+
+/// Euclidean detection radius, matching the app's default sensor (sensor.rs).
+const DETECTION_RADIUS: f64 = 2.0;
+
+/// A scenario file. Fields the runner doesn't use, such as `expected`, are ignored.
+#[derive(Deserialize)]
+struct Scenario {
+    name: String,
+    width: usize,
+    height: usize,
+    obstacles: Vec<Coord>,
+    start: Coord,
+    goal: Coord,
+    #[serde(default)]
+    allow_diagonals: bool,
+    /// Dynamic obstacles the rover only learns about once in sensor range.
+    #[serde(default)]
+    dobs: Vec<Coord>,
+}
+
+/// Outcome of one journey.
+struct Run {
+    path_cost: f64,        // Length of the walked trail; diagonal steps count √2
+    nodes_expanded: usize, // Summed over the first plan and every replan
+    reroutes: usize,
+    success: bool,
+}
+
+fn step_length(from: Coord, to: Coord) -> f64 {
+    let dx = from.0.abs_diff(to.0) as f64;
+    let dy = from.1.abs_diff(to.1) as f64;
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Walk from start to goal with `algorithm`, replanning whenever a DOB comes
+/// into range. Gives up when no plan is left or the walk runs far longer
+/// than any sane route.
+fn run_journey(scenario: &Scenario, algorithm: &str) -> Run {
+    let (width, height) = (scenario.width, scenario.height);
+    let mut grid = vec![vec![false; height]; width];
+    for &(x, y) in &scenario.obstacles {
+        if x < width && y < height {
+            grid[x][y] = true;
+        }
+    }
+    let connectivity = Connectivity::for_algorithm(algorithm, scenario.allow_diagonals);
+    let options = PlannerOptions::new(connectivity);
+    let mut planner = build_planner(algorithm, grid, scenario.start, scenario.goal, &options);
+
+    let mut run = Run {
+        path_cost: 0.0,
+        nodes_expanded: 0,
+        reroutes: 0,
+        success: false,
+    };
+    let mut amber: Vec<Coord> = scenario.dobs.clone();
+    let mut position = scenario.start;
+    let mut plan: Vec<Coord> = Vec::new();
+    let max_steps = width * height * 4;
+
+    for _ in 0..=max_steps {
+        if position == scenario.goal {
+            run.success = true;
+            break;
+        }
+
+        let detected: Vec<Coord> = amber
+            .iter()
+            .copied()
+            .filter(|&dob| step_length(position, dob) <= DETECTION_RADIUS)
+            .collect();
+        amber.retain(|dob| !detected.contains(dob));
+        for &dob in &detected {
+            planner.update_obstacle(dob, true);
+        }
+
+        if plan.is_empty() || !detected.is_empty() {
+            if !plan.is_empty() {
+                run.reroutes += 1;
+            }
+            let path = planner.compute_path(position, scenario.goal);
+            run.nodes_expanded += planner.nodes_expanded();
+            plan = match path {
+                Some(waypoints) => densify(&waypoints),
+                None => break,
+            };
+            if plan.len() < 2 {
+                break;
+            }
+        }
+
+        let next = plan[1];
+        run.path_cost += step_length(position, next);
+        position = next;
+        plan.remove(0);
+    }
+    run
+}
+
+/// `value` as a CSV field, quoted when it holds a comma, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Every `.json` file in `dir`, sorted so the rows come out in a stable order.
+fn scenario_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+fn load_scenario(path: &Path) -> Result<Scenario, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let scenario: Scenario =
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let (width, height) = (scenario.width, scenario.height);
+    for (what, (x, y)) in [("start", scenario.start), ("goal", scenario.goal)] {
+        if x >= width || y >= height {
+            return Err(format!(
+                "{}: {} ({}, {}) is outside the {}x{} grid",
+                path.display(),
+                what,
+                x,
+                y,
+                width,
+                height
+            ));
+        }
+    }
+    Ok(scenario)
+}
+
+fn run() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut dir = PathBuf::from(DEFAULT_DIR);
+    let mut out: Option<PathBuf> = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == OUT_FLAG {
+            out = Some(args.next().ok_or("--out needs a file name")?.into());
+        } else {
+            dir = arg.into();
+        }
+    }
+
+    let mut csv = String::from("scenario,algorithm,path_cost,nodes_expanded,reroutes,success\n");
+    let files = scenario_files(&dir)?;
+    for path in &files {
+        let scenario = load_scenario(path)?;
+        for algorithm in ALGORITHMS {
+            let run = run_journey(&scenario, algorithm);
+            let path_cost = if run.success {
+                format!("{:.4}", run.path_cost)
+            } else {
+                String::new()
+            };
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                csv_field(&scenario.name),
+                csv_field(algorithm),
+                path_cost,
+                run.nodes_expanded,
+                run.reroutes,
+                run.success
+            ));
+        }
+    }
+
+    match out {
+        Some(out) => {
+            fs::write(&out, csv).map_err(|e| format!("{}: {}", out.display(), e))?;
+            eprintln!(
+                "{} scenarios x {} algorithms -> {}",
+                files.len(),
+                ALGORITHMS.len(),
+                out.display()
+            );
+        }
+        None => print!("{}", csv),
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("batch: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,118 @@
+// src/bin/bench.rs
+//
+// Headless benchmark: every algorithm preset over a matrix of grid sizes and
+// obstacle densities, printed as a table of expansions and solve time.
+// Planners come from the library's `build_planner` rather than `Rover`, so
+// the timing covers the search alone.
+//
+//     cargo run --release --bin bench
+//     cargo run --release --bin bench -- --runs 10 --seed 7
+
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use scout::pathfinding::movement::densify;
+use scout::pathfinding::{
+    build_planner, parse_algorithm, Coord, PlannerOptions, ALGORITHM_PRESETS,
+};
+
+/// Square grid sizes to run, in cells per side.
+const SIZES: [usize; 3] = [50, 100, 200];
+
+/// Fraction of cells blocked at random (start and goal always stay free).
+const DENSITIES: [f64; 3] = [0.0, 0.2, 0.35];
+
+const RUNS_FLAG: &str = "--runs";
+const SEED_FLAG: &str = "--seed";
+const DEFAULT_RUNS: usize = 3;
+const DEFAULT_SEED: u64 = 42;
+
+/// Value following `flag` on the command line, if it parses.
+fn flag_value<T: std::str::FromStr>(args: &[String], flag: &str) -> Option<T> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    args.get(index + 1)?.parse().ok()
+}
+
+/// `grid[x][y]` columns with roughly `density` of the cells blocked.
+fn random_grid(size: usize, density: f64, rng: &mut StdRng) -> Vec<Vec<bool>> {
+    let mut grid = vec![vec![false; size]; size];
+    for column in &mut grid {
+        for cell in column.iter_mut() {
+            *cell = rng.gen_bool(density);
+        }
+    }
+    grid[0][0] = false;
+    grid[size - 1][size - 1] = false;
+    grid
+}
+
+/// Sum of step lengths; diagonal steps count √2.
+fn path_cost(path: &[Coord]) -> f64 {
+    path.windows(2)
+        .map(|w| {
+            let dx = w[0].0.abs_diff(w[1].0) as f64;
+            let dy = w[0].1.abs_diff(w[1].1) as f64;
+            (dx * dx + dy * dy).sqrt()
+        })
+        .sum()
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let runs = flag_value(&args, RUNS_FLAG).unwrap_or(DEFAULT_RUNS).max(1);
+    let seed = flag_value(&args, SEED_FLAG).unwrap_or(DEFAULT_SEED);
+
+    println!(
+        "{:>9} {:>8}  {:<25} {:>10} {:>10} {:>11}",
+        "grid", "density", "algorithm", "cost", "expanded", "mean ms"
+    );
+    for size in SIZES {
+        for density in DENSITIES {
+            // Same map for every algorithm in the row group
+            let mut rng = StdRng::seed_from_u64(seed ^ (size as u64) ^ density.to_bits());
+            let grid = random_grid(size, density, &mut rng);
+            let (start, goal) = ((0, 0), (size - 1, size - 1));
+
+            for preset in ALGORITHM_PRESETS {
+                let Some((algorithm, Some(connectivity))) = parse_algorithm(preset) else {
+                    continue;
+                };
+                let options = PlannerOptions::new(connectivity);
+                let mut total_ms = 0.0;
+                let mut outcome = None;
+                for _ in 0..runs {
+                    // A fresh planner each run, so caches never carry over
+                    let mut planner = build_planner(algorithm, grid.clone(), start, goal, &options);
+                    let started = Instant::now();
+                    let path = planner.compute_path(start, goal);
+                    total_ms += started.elapsed().as_secs_f64() * 1000.0;
+                    outcome = Some((
+                        path.map(|waypoints| path_cost(&densify(&waypoints))),
+                        planner.nodes_expanded(),
+                        planner.budget_exceeded(),
+                    ));
+                }
+
+                let Some((cost, expanded, budget_exceeded)) = outcome else {
+                    continue;
+                };
+                let cost = match cost {
+                    Some(cost) => format!("{:.2}", cost),
+                    None if budget_exceeded => "budget".to_string(),
+                    None => "no path".to_string(),
+                };
+                println!(
+                    "{:>9} {:>7.0}%  {:<25} {:>10} {:>10} {:>11.3}",
+                    format!("{}x{}", size, size),
+                    density * 100.0,
+                    preset,
+                    cost,
+                    expanded,
+                    total_ms / runs as f64
+                );
+            }
+        }
+    }
+}
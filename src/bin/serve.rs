@@ -1,10 +1,14 @@
 // src/bin/serve.rs
 
-use std::fs::File;
+use std::fs::{File, Metadata};
 use std::io::Read;
 use std::path::Path;
+use std::time::UNIX_EPOCH;
 use tiny_http::{Header, Request, Response, Server, StatusCode};
 
+/// Pass `--no-cache` to skip ETags and force a full refetch on every reload.
+const NO_CACHE_FLAG: &str = "--no-cache";
+
 fn mime_from_path(path: &str) -> &'static str {
     if path.ends_with(".html") {
         "text/html; charset=utf-8"
@@ -27,7 +31,76 @@ fn mime_from_path(path: &str) -> &'static str {
     }
 }
 
-fn handle_request(request: Request) {
+/// Weak validator from size and modification time; cheap and changes on every rebuild.
+fn etag_for(metadata: &Metadata) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos());
+    format!("\"{:x}-{:x}\"", metadata.len(), modified)
+}
+
+/// Whether the request's `If-None-Match` already lists `etag`.
+fn etag_matches(request: &Request, etag: &str) -> bool {
+    request
+        .headers()
+        .iter()
+        .filter(|h| h.field.equiv("If-None-Match"))
+        .any(|h| {
+            h.value
+                .as_str()
+                .split(',')
+                .any(|tag| tag.trim() == etag || tag.trim() == "*")
+        })
+}
+
+/// What a `Range` header asks for, given a body of `len` bytes.
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    /// No usable range (absent, malformed or multi-range): send the whole body.
+    Full,
+    /// Inclusive `start..=end` slice, answered with 206.
+    Partial(usize, usize),
+    /// Starts past the end of the body, answered with 416.
+    Unsatisfiable,
+}
+
+/// Parse a single `bytes=start-end`, `bytes=start-` or `bytes=-suffix` range.
+fn parse_range(header: Option<&str>, len: usize) -> ByteRange {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = match (start.parse::<usize>(), end.parse::<usize>()) {
+        (Ok(start), Ok(end)) if start <= end => (start, end.min(len.saturating_sub(1))),
+        (Ok(start), Err(_)) if end.is_empty() => (start, len.saturating_sub(1)),
+        (Err(_), Ok(suffix)) if start.is_empty() && suffix > 0 => {
+            (len.saturating_sub(suffix), len.saturating_sub(1))
+        }
+        _ => return ByteRange::Full,
+    };
+    if start >= len {
+        ByteRange::Unsatisfiable
+    } else {
+        ByteRange::Partial(start, end)
+    }
+}
+
+fn header_value<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str())
+}
+
+fn handle_request(request: Request, no_cache: bool) {
     let url = request.url();
 
     let path = if url == "/" {
@@ -38,6 +111,21 @@ fn handle_request(request: Request) {
 
     println!("Request for: {}", path);
 
+    let etag = if no_cache {
+        None
+    } else {
+        std::fs::metadata(&path).ok().map(|m| etag_for(&m))
+    };
+
+    if let Some(etag) = etag.as_deref().filter(|etag| etag_matches(&request, etag)) {
+        let mut resp = Response::from_data(Vec::new()).with_status_code(StatusCode(304));
+        resp.add_header(Header::from_bytes("ETag", etag).expect("failed to create ETag header"));
+        if let Err(e) = request.respond(resp) {
+            eprintln!("Failed to send response: {}", e);
+        }
+        return;
+    }
+
     let response = if Path::new(&path).exists() {
         match File::open(&path) {
             Ok(mut file) => {
@@ -47,7 +135,36 @@ fn handle_request(request: Request) {
                     Response::from_data(Vec::new()).with_status_code(StatusCode(500))
                 } else {
                     let mime = mime_from_path(&path);
-                    let mut resp = Response::from_data(buf);
+                    let total = buf.len();
+                    let mut resp = match parse_range(header_value(&request, "Range"), total) {
+                        ByteRange::Full => Response::from_data(buf),
+                        ByteRange::Partial(start, end) => {
+                            let mut resp = Response::from_data(buf[start..=end].to_vec())
+                                .with_status_code(StatusCode(206));
+                            resp.add_header(
+                                Header::from_bytes(
+                                    "Content-Range",
+                                    format!("bytes {}-{}/{}", start, end, total),
+                                )
+                                .expect("failed to create Content-Range header"),
+                            );
+                            resp
+                        }
+                        ByteRange::Unsatisfiable => {
+                            let mut resp =
+                                Response::from_data(Vec::new()).with_status_code(StatusCode(416));
+                            resp.add_header(
+                                Header::from_bytes("Content-Range", format!("bytes */{}", total))
+                                    .expect("failed to create Content-Range header"),
+                            );
+                            resp
+                        }
+                    };
+
+                    resp.add_header(
+                        Header::from_bytes("Accept-Ranges", "bytes")
+                            .expect("failed to create Accept-Ranges header"),
+                    );
 
                     resp.add_header(
                         Header::from_bytes("Content-Type", mime)
@@ -59,8 +176,19 @@ fn handle_request(request: Request) {
                             .expect("failed to create CORS header"),
                     );
 
+                    // With an ETag the browser keeps its copy but revalidates every load
+                    let cache_control = match &etag {
+                        Some(etag) => {
+                            resp.add_header(
+                                Header::from_bytes("ETag", etag.as_str())
+                                    .expect("failed to create ETag header"),
+                            );
+                            "no-cache"
+                        }
+                        None => "no-cache, no-store, must-revalidate",
+                    };
                     resp.add_header(
-                        Header::from_bytes("Cache-Control", "no-cache, no-store, must-revalidate")
+                        Header::from_bytes("Cache-Control", cache_control)
                             .expect("failed to create Cache-Control header"),
                     );
 
@@ -83,12 +211,119 @@ fn handle_request(request: Request) {
 }
 
 fn main() {
+    let no_cache = std::env::args().any(|arg| arg == NO_CACHE_FLAG);
+
     let server = Server::http("127.0.0.1:8000").unwrap();
     println!("🚀 Serving Scout Pathfinder on http://127.0.0.1:8000");
     println!("📁 Serving files from current directory");
+    if no_cache {
+        println!("🚫 Caching disabled ({})", NO_CACHE_FLAG);
+    }
     println!("Press Ctrl+C to stop the server\n");
 
     for request in server.incoming_requests() {
-        handle_request(request);
+        handle_request(request, no_cache);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_closed_range_is_partial() {
+        assert_eq!(
+            parse_range(Some("bytes=0-9"), 100),
+            ByteRange::Partial(0, 9)
+        );
+        // An end past the body is clamped to its last byte
+        assert_eq!(
+            parse_range(Some("bytes=90-200"), 100),
+            ByteRange::Partial(90, 99)
+        );
+    }
+
+    #[test]
+    fn an_open_ended_range_runs_to_the_last_byte() {
+        assert_eq!(
+            parse_range(Some("bytes=40-"), 100),
+            ByteRange::Partial(40, 99)
+        );
+    }
+
+    #[test]
+    fn a_suffix_range_is_the_last_bytes() {
+        assert_eq!(
+            parse_range(Some("bytes=-10"), 100),
+            ByteRange::Partial(90, 99)
+        );
+        assert_eq!(
+            parse_range(Some("bytes=-500"), 100),
+            ByteRange::Partial(0, 99)
+        );
+    }
+
+    #[test]
+    fn multi_and_malformed_ranges_send_the_whole_body() {
+        assert_eq!(parse_range(Some("bytes=0-9,20-29"), 100), ByteRange::Full);
+        assert_eq!(parse_range(Some("bytes=9-0"), 100), ByteRange::Full);
+        assert_eq!(parse_range(Some("items=0-9"), 100), ByteRange::Full);
+        assert_eq!(parse_range(None, 100), ByteRange::Full);
+    }
+
+    #[test]
+    fn a_start_past_the_end_is_unsatisfiable() {
+        assert_eq!(
+            parse_range(Some("bytes=100-"), 100),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(
+            parse_range(Some("bytes=150-160"), 100),
+            ByteRange::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn a_range_request_serves_the_slice_of_a_real_file() {
+        use std::io::Write;
+        use std::net::TcpStream;
+
+        // Tests run from the crate root, where the server finds index.html too
+        let file = std::fs::read("index.html").expect("index.html should exist");
+        let server = Server::http("127.0.0.1:0").expect("failed to bind test server");
+        let addr = server.server_addr();
+        let handler = std::thread::spawn(move || {
+            let request = server.recv().expect("failed to receive request");
+            handle_request(request, false);
+        });
+
+        let mut stream = TcpStream::connect(addr).expect("failed to connect");
+        stream
+            .write_all(
+                b"GET /index.html HTTP/1.1\r\nHost: localhost\r\nRange: bytes=0-9\r\nConnection: close\r\n\r\n",
+            )
+            .expect("failed to send request");
+        let mut reply = Vec::new();
+        stream
+            .read_to_end(&mut reply)
+            .expect("failed to read reply");
+        handler.join().expect("handler panicked");
+
+        let split = reply
+            .windows(4)
+            .position(|w| w == b"\r\n\r\n")
+            .expect("reply should have a header block");
+        let head = String::from_utf8_lossy(&reply[..split]).to_string();
+        let body = &reply[split + 4..];
+
+        assert!(head.starts_with("HTTP/1.1 206"), "status line: {}", head);
+        let content_range = format!("content-range: bytes 0-9/{}", file.len());
+        assert!(
+            head.lines()
+                .any(|line| line.to_ascii_lowercase() == content_range),
+            "headers: {}",
+            head
+        );
+        assert_eq!(body, &file[..10]);
     }
 }
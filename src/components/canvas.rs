@@ -1,10 +1,44 @@
 // src/components/canvas.rs
 
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::palette::{rgba, ColorScheme, Palette};
+use crate::pathfinding::movement::thin_wall;
 use crate::pathfinding::Coord;
+use crate::rover::step_delay_ms;
+use crate::sensor::{
+    Direction, DistanceMetric, FieldOfView, Sensor, CONE_HALF_ANGLE, DEFAULT_OCCUPANCY_THRESHOLD,
+};
 use wasm_bindgen::JsCast;
-use web_sys::{window, HtmlBodyElement, HtmlCanvasElement, MouseEvent};
+use web_sys::{AddEventListenerOptions, HtmlCanvasElement, MouseEvent, PointerEvent, WheelEvent};
 use yew::prelude::*;
 
+/// How long the goal-reached flash and confetti play.
+pub const CELEBRATION_MS: u32 = 3000;
+
+/// Render interval while animating (20 fps).
+pub const DEFAULT_FRAME_MS: u32 = 50;
+
+/// The detection ring pulses on its own, slower timer.
+const PULSE_MS: u32 = 100;
+
+/// Longer side of the zoomed-in overview, in CSS pixels.
+const MINIMAP_PX: f64 = 160.0;
+
+/// Cell size bounds when fitting the grid to its container, in CSS pixels.
+const MIN_CELL_PX: f64 = 10.0;
+const MAX_CELL_PX: f64 = 25.0;
+
+/// Room left around the grid inside its container when fitting.
+const FIT_MARGIN_PX: f64 = 40.0;
+
+/// A click this close to a cell border (as a fraction of a cell) lands on
+/// the border, toggling a thin wall, instead of on the cell.
+const EDGE_HIT_FRACTION: f64 = 0.2;
+
+const CONFETTI_COLORS: [&str; 5] = ["#f59e0b", "#10b981", "#3b82f6", "#ec4899", "#8b5cf6"];
+
 #[derive(Clone, PartialEq)]
 pub enum DragMode {
     None,
@@ -13,17 +47,197 @@ pub enum DragMode {
     MovingGoal,
 }
 
+/// What occupies a cell, for the hover label.
+#[derive(Clone, Copy, PartialEq)]
+enum CellKind {
+    Free,
+    Static,
+    Amber,
+    Blue,
+    Start,
+    Goal,
+}
+
+impl CellKind {
+    fn label(self) -> &'static str {
+        match self {
+            CellKind::Free => "free",
+            CellKind::Static => "static obstacle",
+            CellKind::Amber => "amber DOB",
+            CellKind::Blue => "converted DOB",
+            CellKind::Start => "start",
+            CellKind::Goal => "goal",
+        }
+    }
+}
+
+/// Smoothly slides the drawn rover between cells instead of teleporting.
+/// Positions are in cell units; `progress` runs 0.0 → 1.0 per step.
+#[derive(Clone, Copy)]
+struct RoverMotion {
+    from: (f64, f64),
+    to: Coord,
+    progress: f64,
+    duration_ms: f64,
+}
+
+impl RoverMotion {
+    fn at(pos: Coord) -> Self {
+        Self {
+            from: (pos.0 as f64, pos.1 as f64),
+            to: pos,
+            progress: 1.0,
+            duration_ms: 1.0,
+        }
+    }
+
+    fn current(&self) -> (f64, f64) {
+        let t = self.progress;
+        (
+            self.from.0 + (self.to.0 as f64 - self.from.0) * t,
+            self.from.1 + (self.to.1 as f64 - self.from.1) * t,
+        )
+    }
+
+    /// Start sliding towards `pos`. Anything other than a single step
+    /// (restart, replay seek, dragging the start) snaps straight there.
+    fn retarget(&mut self, pos: Coord, duration_ms: f64, fresh_journey: bool) {
+        if pos == self.to {
+            return;
+        }
+        let is_step = self.to.0.abs_diff(pos.0) <= 1 && self.to.1.abs_diff(pos.1) <= 1;
+        if is_step && !fresh_journey {
+            *self = Self {
+                from: self.current(),
+                to: pos,
+                progress: 0.0,
+                duration_ms,
+            };
+        } else {
+            *self = Self::at(pos);
+        }
+    }
+
+    fn advance(&mut self, elapsed_ms: f64) {
+        self.progress = (self.progress + elapsed_ms / self.duration_ms).min(1.0);
+    }
+}
+
+/// Zoom and pan of the drawing, in canvas pixels: screen = world * zoom + offset.
+/// At zoom 1 the whole grid is visible; the offset is clamped so the grid
+/// always covers the canvas.
+#[derive(Clone, Copy)]
+struct Viewport {
+    zoom: f64,
+    offset: (f64, f64),
+}
+
+impl Viewport {
+    const MAX_ZOOM: f64 = 4.0;
+
+    /// Whether part of the grid is off screen, so the overview is worth showing.
+    fn is_zoomed(self) -> bool {
+        self.zoom > 1.0
+    }
+
+    fn identity() -> Self {
+        Self {
+            zoom: 1.0,
+            offset: (0.0, 0.0),
+        }
+    }
+
+    fn to_world(self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            (x - self.offset.0) / self.zoom,
+            (y - self.offset.1) / self.zoom,
+        )
+    }
+
+    fn clamp(&mut self, w_px: f64, h_px: f64) {
+        self.offset.0 = self.offset.0.clamp(w_px * (1.0 - self.zoom), 0.0);
+        self.offset.1 = self.offset.1.clamp(h_px * (1.0 - self.zoom), 0.0);
+    }
+
+    /// Zoom by `factor`, keeping the point under the cursor fixed.
+    fn zoom_at(&mut self, factor: f64, (px, py): (f64, f64), w_px: f64, h_px: f64) {
+        let zoom = (self.zoom * factor).clamp(1.0, Self::MAX_ZOOM);
+        let scale = zoom / self.zoom;
+        self.offset = (
+            px - (px - self.offset.0) * scale,
+            py - (py - self.offset.1) * scale,
+        );
+        self.zoom = zoom;
+        self.clamp(w_px, h_px);
+    }
+
+    /// Pan so the world point `(wx, wy)` sits in the middle of the canvas.
+    fn center_on(&mut self, (wx, wy): (f64, f64), w_px: f64, h_px: f64) {
+        self.offset = (w_px / 2.0 - wx * self.zoom, h_px / 2.0 - wy * self.zoom);
+        self.clamp(w_px, h_px);
+    }
+}
+
 #[derive(Properties, PartialEq)]
 pub struct CanvasProps {
     pub width: usize,
     pub height: usize,
     pub rover_state: crate::rover::RoverState,
-    pub visual_start: Coord,      
+    pub visual_start: Coord,
     pub traveled_path: Vec<Coord>, // Turquoise path
     pub amber_dobs: Vec<Coord>,    // Amber DOBs for display
+    pub sensor: Sensor,
+    #[prop_or_default]
+    pub heading: Direction, // Aims the detection area when the sensor is a forward cone
+    pub cost_field: Option<HashMap<Coord, f64>>, // Overlay; None hides it
+    #[prop_or_default]
+    pub dead_end_cells: HashSet<Coord>, // Free cells walled off from the goal (red)
+    #[prop_or_default]
+    pub occupancy: HashMap<Coord, f64>, // Uncertain cells, shaded by how likely each is occupied
+    #[prop_or(DEFAULT_OCCUPANCY_THRESHOLD)]
+    pub occupancy_threshold: f64, // Uncertain cells at or above it are outlined as blocked
+    pub celebration_started: Option<f64>,        // Date::now() when the goal was reached
+    pub is_dark: bool,
+    #[prop_or_default]
+    pub color_scheme: ColorScheme,
+    /// Redraw every `frame_ms` while true; otherwise only when something changes.
+    pub animating: bool,
+    #[prop_or(DEFAULT_FRAME_MS)]
+    pub frame_ms: u32,
+    #[prop_or_default]
+    pub reroutes: Vec<(Coord, usize)>, // Reroute spots and the DOBs that triggered each
+    #[prop_or_default]
+    pub reroute_indices: Vec<usize>, // Traveled-path indices where a new plan took over
+    #[prop_or_default]
+    pub preview_path: Vec<Coord>, // "What if" plan with the hovered cell blocked (faint)
+    #[prop_or_default]
+    pub optimal_path: Vec<Coord>, // Full-knowledge route, dashed over the traveled one at journey end
+    #[prop_or_default]
+    pub plan_stale: bool, // The map changed since the plan was found; drawn dimmed and dashed
+    #[prop_or_default]
+    pub on_hover: Callback<Option<Coord>>, // Cell under the pointer changed
+    #[prop_or_default]
+    pub goals: Vec<(Coord, bool)>, // Every goal and whether it was reached; empty = just rover_state.goal
+    #[prop_or_default]
+    pub on_goal_toggle: Callback<Coord>, // Shift+click adds or removes an extra goal
+    #[prop_or_default]
+    pub selected_cell: Option<Coord>, // Outlined while its terrain cost is edited
+    #[prop_or_default]
+    pub on_cell_select: Callback<Coord>, // Ctrl/Cmd+click picks a cell to edit
+    #[prop_or_default]
+    pub edit_thin_walls: bool, // Clicks near a border go to `on_thin_wall_toggle`
+    #[prop_or_default]
+    pub on_thin_wall_toggle: Callback<(Coord, Coord)>, // The two cells either side of the clicked border
+    #[prop_or_default]
+    pub capture_frames: bool, // Hand each finished redraw to `on_frame` as a PNG data URL
+    #[prop_or_default]
+    pub on_frame: Callback<String>,
+    #[prop_or_default]
+    pub panel_minimized: bool, // The container width jumps when it flips, so the grid refits
     pub on_mouse_down: Callback<Coord>,
     pub on_mouse_move: Callback<Coord>,
     pub on_mouse_up: Callback<()>,
+    pub on_erase: Callback<Coord>,
     pub on_start_drag: Callback<Coord>,
     pub on_goal_drag: Callback<Coord>,
 }
@@ -31,10 +245,26 @@ pub struct CanvasProps {
 #[function_component(Canvas)]
 pub fn canvas(props: &CanvasProps) -> Html {
     let canvas_ref = use_node_ref();
+    let minimap_ref = use_node_ref();
     let drag_mode = use_state(|| DragMode::None);
-    let animation_frame = use_state(|| 0i32);
+    let hovered_cell = use_state(|| None::<Coord>); // Read-only; independent of dragging
+    let active_pointer = use_mut_ref(|| None::<i32>);
+    let motion = use_mut_ref(|| RoverMotion::at(props.rover_state.pos));
+    motion.borrow_mut().retarget(
+        props.rover_state.pos,
+        step_delay_ms(props.rover_state.speed) as f64,
+        props.traveled_path.len() <= 1,
+    );
+    let pulse_phase = use_mut_ref(|| 0.0f64);
+    // Read live by the render loop and pointer handlers, so changes need no re-render
+    let viewport = use_mut_ref(Viewport::identity);
+    // Latest render closure, for one-off redraws (zoom) when no loop is running
+    let redraw = use_mut_ref(|| None::<Rc<dyn Fn()>>);
+    let follow_rover = use_state(|| false);
 
     let cell_size = use_state(|| 20.0f64);
+    // Bumped by the Fit button to force a refit against the current container
+    let fit_request = use_state(|| 0u32);
 
     {
         let canvas_ref = canvas_ref.clone();
@@ -42,21 +272,20 @@ pub fn canvas(props: &CanvasProps) -> Html {
         let width = props.width;
         let height = props.height;
 
-        use_effect_with((), move |_| {
+        let deps = (width, height, props.panel_minimized, *fit_request);
+        use_effect_with(deps, move |_| {
             let update_size = {
                 let canvas_ref = canvas_ref.clone();
                 let cell_size = cell_size.clone();
                 move || {
                     if let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() {
                         if let Some(parent) = canvas.parent_element() {
-                            let parent_width = parent.client_width() as f64 - 40.0;
-                            let parent_height = parent.client_height() as f64 - 40.0;
-
-                            let cell_w = parent_width / width as f64;
-                            let cell_h = parent_height / height as f64;
-                            let new_cell_size = cell_w.min(cell_h).min(25.0).max(10.0);
-
-                            cell_size.set(new_cell_size);
+                            cell_size.set(fit_cell_size(
+                                parent.client_width() as f64,
+                                parent.client_height() as f64,
+                                width,
+                                height,
+                            ));
                         }
                     }
                 }
@@ -85,11 +314,62 @@ pub fn canvas(props: &CanvasProps) -> Html {
         });
     }
 
+    // Wheel zoom needs a non-passive listener so the page doesn't scroll too
     {
-        let animation_frame = animation_frame.clone();
+        let canvas_ref = canvas_ref.clone();
+        let viewport = viewport.clone();
+        let redraw = redraw.clone();
         use_effect_with((), move |_| {
-            let interval = gloo_timers::callback::Interval::new(50, move || {
-                animation_frame.set((*animation_frame + 1) % 360);
+            let canvas = canvas_ref.cast::<HtmlCanvasElement>();
+            let closure = wasm_bindgen::closure::Closure::wrap(Box::new(move |e: WheelEvent| {
+                let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() else {
+                    return;
+                };
+                e.prevent_default();
+                let rect = canvas.get_bounding_client_rect();
+                let cursor = (
+                    e.client_x() as f64 - rect.left(),
+                    e.client_y() as f64 - rect.top(),
+                );
+                let factor = if e.delta_y() < 0.0 { 1.1 } else { 1.0 / 1.1 };
+                viewport
+                    .borrow_mut()
+                    .zoom_at(factor, cursor, rect.width(), rect.height());
+                if let Some(redraw) = redraw.borrow().as_ref() {
+                    redraw();
+                }
+            })
+                as Box<dyn Fn(WheelEvent)>);
+
+            if let Some(canvas) = &canvas {
+                let options = AddEventListenerOptions::new();
+                options.set_passive(false);
+                let _ = canvas.add_event_listener_with_callback_and_add_event_listener_options(
+                    "wheel",
+                    closure.as_ref().unchecked_ref(),
+                    &options,
+                );
+            }
+
+            move || {
+                if let Some(canvas) = canvas {
+                    let _ = canvas.remove_event_listener_with_callback(
+                        "wheel",
+                        closure.as_ref().unchecked_ref(),
+                    );
+                }
+                drop(closure);
+            }
+        });
+    }
+
+    {
+        let pulse_phase = pulse_phase.clone();
+        use_effect_with(props.animating, move |&animating| {
+            let interval = animating.then(|| {
+                gloo_timers::callback::Interval::new(PULSE_MS, move || {
+                    *pulse_phase.borrow_mut() += 0.04;
+                })
             });
 
             move || drop(interval)
@@ -98,14 +378,45 @@ pub fn canvas(props: &CanvasProps) -> Html {
 
     {
         let canvas_ref = canvas_ref.clone();
+        // The render closure takes `canvas_ref` itself
+        let capture_ref = canvas_ref.clone();
+        let minimap_ref = minimap_ref.clone();
         let rover_state = props.rover_state.clone();
         let visual_start = props.visual_start;
         let traveled_path = props.traveled_path.clone();
         let amber_dobs = props.amber_dobs.clone();
+        let sensor = props.sensor;
+        let heading = props.heading;
+        let cost_field = props.cost_field.clone();
+        let dead_end_cells = props.dead_end_cells.clone();
+        let occupancy = props.occupancy.clone();
+        let occupancy_threshold = props.occupancy_threshold;
+        let preview_path = props.preview_path.clone();
+        let plan_stale = props.plan_stale;
+        let optimal_path = props.optimal_path.clone();
+        let selected_cell = props.selected_cell;
+        let goals = if props.goals.is_empty() {
+            vec![(rover_state.goal, false)]
+        } else {
+            props.goals.clone()
+        };
+        let reroutes = props.reroutes.clone();
+        let reroute_indices = props.reroute_indices.clone();
+        let celebration_started = props.celebration_started;
+        let is_dark = props.is_dark;
+        let color_scheme = props.color_scheme;
+        let animating = props.animating;
+        let frame_ms = props.frame_ms.max(1);
+        let capture_frames = props.capture_frames;
+        let on_frame = props.on_frame.clone();
         let width = props.width;
         let height = props.height;
         let cell_size_val = *cell_size;
-        let animation_frame = animation_frame.clone();
+        let pulse_phase = pulse_phase.clone();
+        let motion = motion.clone();
+        let viewport = viewport.clone();
+        let redraw = redraw.clone();
+        let follow = *follow_rover;
 
         use_effect_with(
             (
@@ -113,18 +424,43 @@ pub fn canvas(props: &CanvasProps) -> Html {
                 cell_size_val,
                 traveled_path.clone(),
                 amber_dobs.clone(),
+                (sensor, heading),
+                cost_field.clone(),
+                (
+                    dead_end_cells.clone(),
+                    preview_path.clone(),
+                    goals.clone(),
+                    selected_cell,
+                    reroute_indices.clone(),
+                    plan_stale,
+                    optimal_path.clone(),
+                    (occupancy.clone(), occupancy_threshold),
+                ),
+                reroutes.clone(),
+                celebration_started,
+                follow,
+                (is_dark, color_scheme, animating, frame_ms, capture_frames),
             ),
             move |_| {
+                // No loop to finish the slide, or a frame must show the rover on its cell
+                if !animating || capture_frames {
+                    motion.borrow_mut().advance(f64::INFINITY);
+                }
+
+                let loop_motion = motion.clone();
+                let palette = Palette::new(color_scheme, is_dark);
                 let render = move || {
-                    let frame = *animation_frame;
+                    let phase = *pulse_phase.borrow();
 
                     if let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() {
                         let cell_size = cell_size_val;
 
                         let w_px = (width as f64) * cell_size;
                         let h_px = (height as f64) * cell_size;
-                        canvas.set_width(w_px as u32);
-                        canvas.set_height(h_px as u32);
+                        // Backing store in device pixels; drawing stays in CSS pixels
+                        let dpr = device_pixel_ratio();
+                        canvas.set_width((w_px * dpr).round() as u32);
+                        canvas.set_height((h_px * dpr).round() as u32);
 
                         let context = canvas
                             .get_context("2d")
@@ -133,17 +469,35 @@ pub fn canvas(props: &CanvasProps) -> Html {
                             .dyn_into::<web_sys::CanvasRenderingContext2d>()
                             .unwrap();
 
-                        let doc = window().unwrap().document().unwrap();
-                        let body = doc.body().unwrap();
-                        let body_element = body.dyn_into::<HtmlBodyElement>().unwrap();
-                        let is_dark = body_element.class_list().contains("dark");
-
-                        let bg_color = if is_dark { "#0a0a0a" } else { "#fafafa" };
-                        context.set_fill_style_str(bg_color);
+                        let _ = context.set_transform(dpr, 0.0, 0.0, dpr, 0.0, 0.0);
+                        context.set_fill_style_str(palette.background);
                         context.fill_rect(0.0, 0.0, w_px, h_px);
 
-                        let grid_color = if is_dark { "#1f1f1f" } else { "#e5e7eb" };
-                        context.set_stroke_style_str(grid_color);
+                        // Everything below is drawn in grid space under the zoom/pan
+                        let view = {
+                            let mut view = viewport.borrow_mut();
+                            if follow {
+                                let (fx, fy) = motion.borrow().current();
+                                view.center_on(
+                                    ((fx + 0.5) * cell_size, (fy + 0.5) * cell_size),
+                                    w_px,
+                                    h_px,
+                                );
+                            } else {
+                                view.clamp(w_px, h_px);
+                            }
+                            *view
+                        };
+                        let _ = context.set_transform(
+                            view.zoom * dpr,
+                            0.0,
+                            0.0,
+                            view.zoom * dpr,
+                            view.offset.0 * dpr,
+                            view.offset.1 * dpr,
+                        );
+
+                        context.set_stroke_style_str(palette.grid_line);
                         context.set_line_width(0.5);
 
                         for i in 0..=width {
@@ -162,8 +516,89 @@ pub fn canvas(props: &CanvasProps) -> Html {
                             context.stroke();
                         }
 
-                        let obstacle_color = if is_dark { "#3f3f46" } else { "#52525b" };
-                        context.set_fill_style_str(obstacle_color);
+                        // Weighted terrain: the costlier the cell, the darker the tint
+                        for (&(tx, ty), &cost) in &rover_state.terrain_costs {
+                            if tx < width && ty < height {
+                                context.set_fill_style_str(&rgba(
+                                    palette.terrain,
+                                    (0.12 + 0.08 * (cost - 1.0)).min(0.6),
+                                ));
+                                context.fill_rect(
+                                    (tx as f64) * cell_size,
+                                    (ty as f64) * cell_size,
+                                    cell_size,
+                                    cell_size,
+                                );
+                            }
+                        }
+
+                        // Cost field: near cells bright, far cells dim, unreached uncolored
+                        if let Some(field) = &cost_field {
+                            let max_cost = field.values().cloned().fold(0.0f64, f64::max);
+                            for (&(cx, cy), &cost) in field {
+                                if cx < width && cy < height {
+                                    let closeness = if max_cost > 0.0 {
+                                        1.0 - cost / max_cost
+                                    } else {
+                                        1.0
+                                    };
+                                    context.set_fill_style_str(&rgba(
+                                        palette.cost_field,
+                                        0.08 + 0.5 * closeness,
+                                    ));
+                                    context.fill_rect(
+                                        (cx as f64) * cell_size,
+                                        (cy as f64) * cell_size,
+                                        cell_size,
+                                        cell_size,
+                                    );
+                                }
+                            }
+                        }
+
+                        context.set_fill_style_str(&rgba(palette.dead_end, 0.25));
+                        for &(dx, dy) in &dead_end_cells {
+                            if dx < width && dy < height {
+                                context.fill_rect(
+                                    (dx as f64) * cell_size,
+                                    (dy as f64) * cell_size,
+                                    cell_size,
+                                    cell_size,
+                                );
+                            }
+                        }
+
+                        // Uncertain cells: the likelier occupied, the more solid; the
+                        // ones planned around get an outline
+                        context.save();
+                        context.set_fill_style_str(palette.obstacle);
+                        context.set_stroke_style_str(palette.obstacle);
+                        context.set_line_width(1.5);
+                        for (&(ox, oy), &p) in &occupancy {
+                            if ox < width && oy < height {
+                                let x = (ox as f64) * cell_size;
+                                let y = (oy as f64) * cell_size;
+                                context.set_global_alpha(p);
+                                context.fill_rect(
+                                    x + 1.0,
+                                    y + 1.0,
+                                    cell_size - 2.0,
+                                    cell_size - 2.0,
+                                );
+                                if p >= occupancy_threshold {
+                                    context.set_global_alpha(1.0);
+                                    context.stroke_rect(
+                                        x + 1.5,
+                                        y + 1.5,
+                                        cell_size - 3.0,
+                                        cell_size - 3.0,
+                                    );
+                                }
+                            }
+                        }
+                        context.restore();
+
+                        context.set_fill_style_str(palette.obstacle);
                         for &(ox, oy) in &rover_state.obstacles {
                             if ox < width && oy < height {
                                 let x = (ox as f64) * cell_size;
@@ -177,8 +612,7 @@ pub fn canvas(props: &CanvasProps) -> Html {
                             }
                         }
 
-                        let amber_dob_color = if is_dark { "#d97706" } else { "#f59e0b" };
-                        context.set_fill_style_str(amber_dob_color);
+                        context.set_fill_style_str(palette.amber_dob);
                         for &(ox, oy) in &amber_dobs {
                             if ox < width && oy < height {
                                 let x = (ox as f64) * cell_size;
@@ -192,8 +626,7 @@ pub fn canvas(props: &CanvasProps) -> Html {
                             }
                         }
 
-                        let converted_obstacle_color = if is_dark { "#2563eb" } else { "#3b82f6" };
-                        context.set_fill_style_str(converted_obstacle_color);
+                        context.set_fill_style_str(palette.converted_dob);
                         for &(ox, oy) in &rover_state.converted_obstacles {
                             if ox < width && oy < height {
                                 let x = (ox as f64) * cell_size;
@@ -207,26 +640,70 @@ pub fn canvas(props: &CanvasProps) -> Html {
                             }
                         }
 
+                        // Thin walls run along the border between their two cells
+                        context.set_stroke_style_str(palette.obstacle);
+                        context.set_line_width((cell_size * 0.18).max(2.0));
+                        context.set_line_cap("round");
+                        for &((ax, ay), (bx, by)) in &rover_state.thin_walls {
+                            if bx >= width || by >= height {
+                                continue;
+                            }
+                            let (from, to) = if ax != bx {
+                                let x = (bx as f64) * cell_size;
+                                (
+                                    (x, (ay as f64) * cell_size),
+                                    (x, ((ay + 1) as f64) * cell_size),
+                                )
+                            } else {
+                                let y = (by as f64) * cell_size;
+                                (
+                                    ((ax as f64) * cell_size, y),
+                                    (((ax + 1) as f64) * cell_size, y),
+                                )
+                            };
+                            context.begin_path();
+                            context.move_to(from.0, from.1);
+                            context.line_to(to.0, to.1);
+                            context.stroke();
+                        }
+
                         if !traveled_path.is_empty() {
-                            context.set_stroke_style_str("#14b8a6");
                             context.set_line_width(3.0);
                             context.set_line_cap("round");
                             context.set_line_join("round");
-                            context.begin_path();
 
-                            for (i, &(x, y)) in traveled_path.iter().enumerate() {
-                                let px = (x as f64) * cell_size + (cell_size / 2.0);
-                                let py = (y as f64) * cell_size + (cell_size / 2.0);
+                            // One shade per plan; neighbouring segments share the reroute cell
+                            let last = traveled_path.len() - 1;
+                            let mut bounds = vec![0];
+                            bounds.extend(
+                                reroute_indices
+                                    .iter()
+                                    .copied()
+                                    .filter(|&i| i > 0 && i < last),
+                            );
+                            bounds.push(last);
+                            bounds.dedup();
+                            for (segment, span) in bounds.windows(2).enumerate() {
+                                context.set_stroke_style_str(
+                                    palette.traveled[segment % palette.traveled.len()],
+                                );
+                                context.begin_path();
+                                for (i, &(x, y)) in
+                                    traveled_path[span[0]..=span[1]].iter().enumerate()
+                                {
+                                    let px = (x as f64) * cell_size + (cell_size / 2.0);
+                                    let py = (y as f64) * cell_size + (cell_size / 2.0);
 
-                                if i == 0 {
-                                    context.move_to(px, py);
-                                } else {
-                                    context.line_to(px, py);
+                                    if i == 0 {
+                                        context.move_to(px, py);
+                                    } else {
+                                        context.line_to(px, py);
+                                    }
                                 }
+                                context.stroke();
                             }
-                            context.stroke();
 
-                            context.set_fill_style_str("#0d9488");
+                            context.set_fill_style_str(palette.traveled_dot);
                             for &(x, y) in traveled_path.iter().skip(1) {
                                 let px = (x as f64) * cell_size + (cell_size / 2.0);
                                 let py = (y as f64) * cell_size + (cell_size / 2.0);
@@ -239,8 +716,35 @@ pub fn canvas(props: &CanvasProps) -> Html {
                             }
                         }
 
+                        // Reroute markers: a diamond labelled with the DOBs that forced it
+                        for &((x, y), dobs) in &reroutes {
+                            if x < width && y < height {
+                                let px = (x as f64) * cell_size + (cell_size / 2.0);
+                                let py = (y as f64) * cell_size + (cell_size / 2.0);
+                                let r = cell_size * 0.45;
+                                context.set_fill_style_str(palette.reroute);
+                                context.begin_path();
+                                context.move_to(px, py - r);
+                                context.line_to(px + r, py);
+                                context.line_to(px, py + r);
+                                context.line_to(px - r, py);
+                                context.close_path();
+                                context.fill();
+                                context.set_fill_style_str(palette.reroute_text);
+                                context.set_font("bold 10px -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif");
+                                context.set_text_align("center");
+                                let _ = context.fill_text(&dobs.to_string(), px, py + 3.5);
+                            }
+                        }
+
                         if !rover_state.path.is_empty() && rover_state.path.len() > 1 {
-                            context.set_stroke_style_str("#a855f7");
+                            context.save();
+                            if plan_stale {
+                                context.set_global_alpha(0.35);
+                                let dash = js_sys::Array::of2(&6.0.into(), &6.0.into());
+                                let _ = context.set_line_dash(&dash);
+                            }
+                            context.set_stroke_style_str(palette.planned);
                             context.set_line_width(3.0);
                             context.set_line_cap("round");
                             context.set_line_join("round");
@@ -259,7 +763,7 @@ pub fn canvas(props: &CanvasProps) -> Html {
                             }
                             context.stroke();
 
-                            context.set_fill_style_str("#9333ea");
+                            context.set_fill_style_str(palette.planned_dot);
                             if rover_state.path.len() > 2 {
                                 for &(x, y) in
                                     rover_state.path[1..rover_state.path.len() - 1].iter()
@@ -274,6 +778,62 @@ pub fn canvas(props: &CanvasProps) -> Html {
                                     context.fill();
                                 }
                             }
+                            context.restore();
+                        }
+
+                        if optimal_path.len() > 1 {
+                            context.save();
+                            context.set_stroke_style_str(palette.optimal);
+                            context.set_line_width(2.5);
+                            context.set_line_cap("round");
+                            context.set_line_join("round");
+                            let dash = js_sys::Array::of2(&8.0.into(), &5.0.into());
+                            let _ = context.set_line_dash(&dash);
+                            context.begin_path();
+                            for (i, &(x, y)) in optimal_path.iter().enumerate() {
+                                let px = (x as f64) * cell_size + (cell_size / 2.0);
+                                let py = (y as f64) * cell_size + (cell_size / 2.0);
+                                if i == 0 {
+                                    context.move_to(px, py);
+                                } else {
+                                    context.line_to(px, py);
+                                }
+                            }
+                            context.stroke();
+                            context.restore();
+                        }
+
+                        if preview_path.len() > 1 {
+                            context.save();
+                            context.set_stroke_style_str(&rgba(palette.preview, 0.45));
+                            context.set_line_width(2.0);
+                            let dash = js_sys::Array::of2(&4.0.into(), &4.0.into());
+                            let _ = context.set_line_dash(&dash);
+                            context.begin_path();
+                            for (i, &(x, y)) in preview_path.iter().enumerate() {
+                                let px = (x as f64) * cell_size + (cell_size / 2.0);
+                                let py = (y as f64) * cell_size + (cell_size / 2.0);
+                                if i == 0 {
+                                    context.move_to(px, py);
+                                } else {
+                                    context.line_to(px, py);
+                                }
+                            }
+                            context.stroke();
+                            context.restore();
+                        }
+
+                        if let Some((sx, sy)) =
+                            selected_cell.filter(|&(x, y)| x < width && y < height)
+                        {
+                            context.set_stroke_style_str(palette.selected);
+                            context.set_line_width(2.0);
+                            context.stroke_rect(
+                                (sx as f64) * cell_size + 1.0,
+                                (sy as f64) * cell_size + 1.0,
+                                cell_size - 2.0,
+                                cell_size - 2.0,
+                            );
                         }
 
                         let (start_x, start_y) = visual_start;
@@ -281,11 +841,11 @@ pub fn canvas(props: &CanvasProps) -> Html {
                             let x = (start_x as f64) * cell_size;
                             let y = (start_y as f64) * cell_size;
 
-                            context.set_fill_style_str("#16a34a");
+                            context.set_fill_style_str(palette.start);
                             context.fill_rect(x + 2.0, y + 2.0, cell_size - 4.0, cell_size - 4.0);
 
                             // Text
-                            context.set_fill_style_str("#FFFFFF");
+                            context.set_fill_style_str(palette.endpoint_text);
                             context.set_font("bold 11px -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif");
                             context.set_text_align("center");
                             context
@@ -293,58 +853,96 @@ pub fn canvas(props: &CanvasProps) -> Html {
                                 .unwrap();
                         }
 
-                        let (goal_x, goal_y) = rover_state.goal;
-                        if goal_x < width && goal_y < height {
+                        for &((goal_x, goal_y), reached) in &goals {
+                            if goal_x >= width || goal_y >= height {
+                                continue;
+                            }
                             let x = (goal_x as f64) * cell_size;
                             let y = (goal_y as f64) * cell_size;
 
-                            context.set_fill_style_str("#dc2626");
+                            context.save();
+                            if reached {
+                                context.set_global_alpha(0.35);
+                            }
+                            context.set_fill_style_str(palette.goal);
                             context.fill_rect(x + 2.0, y + 2.0, cell_size - 4.0, cell_size - 4.0);
 
                             // Text
-                            context.set_fill_style_str("#FFFFFF");
+                            context.set_fill_style_str(palette.endpoint_text);
                             context.set_font("bold 11px -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif");
                             context.set_text_align("center");
                             context
                                 .fill_text("G", x + cell_size / 2.0, y + cell_size / 2.0 + 4.0)
                                 .unwrap();
+                            context.restore();
+                        }
+
+                        // The journey ends on the last goal, wherever the rover stopped
+                        let (end_x, end_y) = rover_state.pos;
+                        if let Some(started) =
+                            celebration_started.filter(|_| end_x < width && end_y < height)
+                        {
+                            let progress = (js_sys::Date::now() - started) / CELEBRATION_MS as f64;
+                            if (0.0..1.0).contains(&progress) {
+                                draw_celebration(
+                                    &context,
+                                    (end_x as f64) * cell_size + cell_size / 2.0,
+                                    (end_y as f64) * cell_size + cell_size / 2.0,
+                                    cell_size,
+                                    progress,
+                                );
+                            }
                         }
 
                         let (rx, ry) = rover_state.pos;
                         if rx < width && ry < height {
-                            let cx = (rx as f64) * cell_size + (cell_size / 2.0);
-                            let cy = (ry as f64) * cell_size + (cell_size / 2.0);
+                            // Body and detection ring follow the interpolated position
+                            let (fx, fy) = motion.borrow().current();
+                            let cx = fx * cell_size + (cell_size / 2.0);
+                            let cy = fy * cell_size + (cell_size / 2.0);
 
-                            let time = (frame as f64) * 0.02;
-                            let pulse = (time.sin() * 0.3 + 0.7).max(0.1);
+                            let pulse = (phase.sin() * 0.3 + 0.7).max(0.1);
 
                             context.save();
 
-                            context.set_shadow_color("rgba(251, 146, 60, 0.5)");
+                            context.set_shadow_color(&rgba(palette.sensor, 0.5));
                             context.set_shadow_blur(15.0);
 
-                            context.set_stroke_style_str(&format!(
-                                "rgba(251, 146, 60, {})",
-                                pulse * 0.8
-                            ));
+                            let ring_radius = sensor.range * cell_size;
+                            // A forward cone is the usual rings clipped to a wedge,
+                            // wide enough to reach a square area's corners
+                            let cone_radius = ring_radius * std::f64::consts::SQRT_2 + cell_size;
+                            let cone = (sensor.field_of_view == FieldOfView::ForwardCone)
+                                .then(|| heading.angle());
+                            if let Some(angle) = cone {
+                                trace_cone(&context, angle, cx, cy, cone_radius);
+                                context.clip();
+                            }
+
+                            context.set_stroke_style_str(&rgba(palette.sensor, pulse * 0.8));
                             context.set_line_width(3.0);
-                            context.begin_path();
-                            context
-                                .arc(cx, cy, 2.0 * cell_size, 0.0, std::f64::consts::PI * 2.0)
-                                .unwrap();
+                            trace_detection_shape(&context, sensor.metric, cx, cy, ring_radius);
                             context.stroke();
 
-                            context.set_stroke_style_str(&format!(
-                                "rgba(251, 146, 60, {})",
-                                pulse * 0.5
-                            ));
+                            context.set_stroke_style_str(&rgba(palette.sensor, pulse * 0.5));
                             context.set_line_width(2.0);
-                            context.begin_path();
-                            context
-                                .arc(cx, cy, 1.5 * cell_size, 0.0, std::f64::consts::PI * 2.0)
-                                .unwrap();
+                            trace_detection_shape(
+                                &context,
+                                sensor.metric,
+                                cx,
+                                cy,
+                                ring_radius * 0.75,
+                            );
                             context.stroke();
 
+                            // The wedge's sides, cut off where the outer ring ends
+                            if let Some(angle) = cone {
+                                trace_detection_shape(&context, sensor.metric, cx, cy, ring_radius);
+                                context.clip();
+                                trace_cone(&context, angle, cx, cy, cone_radius);
+                                context.stroke();
+                            }
+
                             context.restore();
 
                             context.set_fill_style_str("#8b7355");
@@ -367,14 +965,70 @@ pub fn canvas(props: &CanvasProps) -> Html {
                                 .arc(cx - 2.0, cy - 2.0, 2.0, 0.0, std::f64::consts::PI * 2.0)
                                 .unwrap();
                             context.fill();
+
+                            // The one cell the rover will step to next cycle
+                            if let Some(&(nx, ny)) = rover_state.path.get(1) {
+                                if nx < width && ny < height {
+                                    draw_next_step(
+                                        &context,
+                                        (cx, cy),
+                                        (
+                                            nx as f64 * cell_size + cell_size / 2.0,
+                                            ny as f64 * cell_size + cell_size / 2.0,
+                                        ),
+                                        cell_size,
+                                        palette.next_step,
+                                    );
+                                }
+                            }
+                        }
+
+                        if let Some(minimap) = minimap_ref.cast::<HtmlCanvasElement>() {
+                            minimap.set_hidden(!view.is_zoomed());
+                            if view.is_zoomed() {
+                                let blocked = [
+                                    (&rover_state.obstacles, palette.obstacle),
+                                    (&rover_state.converted_obstacles, palette.converted_dob),
+                                ];
+                                draw_minimap(
+                                    &minimap,
+                                    &palette,
+                                    (width, height),
+                                    &blocked,
+                                    &amber_dobs,
+                                    &goals,
+                                    motion.borrow().current(),
+                                    (
+                                        view.to_world((0.0, 0.0)),
+                                        view.to_world((w_px, h_px)),
+                                        cell_size,
+                                    ),
+                                );
+                            }
                         }
                     }
                 };
+                let render: Rc<dyn Fn()> = Rc::new(render);
 
                 render();
+                *redraw.borrow_mut() = Some(render.clone());
+
+                // The draw above is synchronous, so the canvas now holds this state in full
+                if capture_frames {
+                    if let Some(url) = capture_ref
+                        .cast::<HtmlCanvasElement>()
+                        .and_then(|canvas| canvas.to_data_url_with_type("image/png").ok())
+                    {
+                        on_frame.emit(url);
+                    }
+                }
 
-                let render_loop = gloo_timers::callback::Interval::new(50, move || {
-                    render();
+                // Idle or paused: the next prop change triggers the next draw
+                let render_loop = animating.then(|| {
+                    gloo_timers::callback::Interval::new(frame_ms, move || {
+                        loop_motion.borrow_mut().advance(frame_ms as f64);
+                        render();
+                    })
                 });
 
                 move || drop(render_loop)
@@ -388,32 +1042,119 @@ pub fn canvas(props: &CanvasProps) -> Html {
     let rover_state = props.rover_state.clone();
     let visual_start = props.visual_start;
 
-    let onmousedown = {
+    let on_toggle_follow = {
+        let follow_rover = follow_rover.clone();
+        Callback::from(move |_: MouseEvent| follow_rover.set(!*follow_rover))
+    };
+
+    // Show the whole grid again, re-measured against the container as it is now
+    let on_fit = {
+        let viewport = viewport.clone();
+        let follow_rover = follow_rover.clone();
+        let redraw = redraw.clone();
+        let fit_request = fit_request.clone();
+        Callback::from(move |_: MouseEvent| {
+            *viewport.borrow_mut() = Viewport::identity();
+            follow_rover.set(false);
+            fit_request.set(fit_request.wrapping_add(1));
+            if let Some(redraw) = redraw.borrow().as_ref() {
+                redraw();
+            }
+        })
+    };
+
+    // Clicking the overview centres the main view there and stops following
+    let on_minimap_click = {
+        let viewport = viewport.clone();
+        let follow_rover = follow_rover.clone();
+        let redraw = redraw.clone();
+        Callback::from(move |e: MouseEvent| {
+            let scale = minimap_scale(width, height);
+            let cell = (e.offset_x() as f64 / scale, e.offset_y() as f64 / scale);
+            let (w_px, h_px) = (width as f64 * cell_size_val, height as f64 * cell_size_val);
+            viewport.borrow_mut().center_on(
+                (cell.0 * cell_size_val, cell.1 * cell_size_val),
+                w_px,
+                h_px,
+            );
+            follow_rover.set(false);
+            if let Some(redraw) = redraw.borrow().as_ref() {
+                redraw();
+            }
+        })
+    };
+
+    // Only the first active pointer drives a drag; extra touches are ignored
+    let is_active_pointer = {
+        let active_pointer = active_pointer.clone();
+        move |e: &PointerEvent| *active_pointer.borrow() == Some(e.pointer_id())
+    };
+
+    let onpointerdown = {
         let canvas_ref = canvas_ref.clone();
+        let viewport = viewport.clone();
         let drag_mode = drag_mode.clone();
+        let active_pointer = active_pointer.clone();
         let on_mouse_down = props.on_mouse_down.clone();
+        let on_erase = props.on_erase.clone();
         let on_start_drag = props.on_start_drag.clone();
         let on_goal_drag = props.on_goal_drag.clone();
+        let on_goal_toggle = props.on_goal_toggle.clone();
+        let on_cell_select = props.on_cell_select.clone();
+        let on_thin_wall_toggle = props.on_thin_wall_toggle.clone();
+        let edit_thin_walls = props.edit_thin_walls;
         let goal_pos = rover_state.goal;
 
-        Callback::from(move |e: MouseEvent| {
+        Callback::from(move |e: PointerEvent| {
             e.prevent_default();
 
-            if let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() {
-                let rect = canvas.get_bounding_client_rect();
-                let x = e.client_x() as f64 - rect.left();
-                let y = e.client_y() as f64 - rect.top();
-
-                let cell_x = (x / cell_size_val).floor() as usize;
-                let cell_y = (y / cell_size_val).floor() as usize;
+            if active_pointer.borrow().is_some() || !e.is_primary() {
+                return;
+            }
 
-                if cell_x < width && cell_y < height {
-                    if (cell_x, cell_y) == visual_start {
+            if let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() {
+                if let Some((cell_x, cell_y)) = event_cell(
+                    &canvas,
+                    &e,
+                    *viewport.borrow(),
+                    cell_size_val,
+                    width,
+                    height,
+                ) {
+                    *active_pointer.borrow_mut() = Some(e.pointer_id());
+                    // Keep receiving moves for this pointer even if it leaves the canvas
+                    let _ = canvas.set_pointer_capture(e.pointer_id());
+
+                    // Right button or Alt+click erases instead of toggling
+                    let is_erase = e.button() == 2 || e.alt_key();
+
+                    if is_erase {
+                        drag_mode.set(DragMode::PlacingObstacles);
+                        on_erase.emit((cell_x, cell_y));
+                    } else if e.shift_key() {
+                        on_goal_toggle.emit((cell_x, cell_y));
+                    } else if e.ctrl_key() || e.meta_key() {
+                        on_cell_select.emit((cell_x, cell_y));
+                    } else if (cell_x, cell_y) == visual_start {
                         drag_mode.set(DragMode::MovingStart);
                         on_start_drag.emit((cell_x, cell_y));
                     } else if (cell_x, cell_y) == goal_pos {
                         drag_mode.set(DragMode::MovingGoal);
                         on_goal_drag.emit((cell_x, cell_y));
+                    } else if let Some(edge) = edit_thin_walls
+                        .then(|| {
+                            event_edge(
+                                &canvas,
+                                &e,
+                                *viewport.borrow(),
+                                cell_size_val,
+                                width,
+                                height,
+                            )
+                        })
+                        .flatten()
+                    {
+                        on_thin_wall_toggle.emit(edge);
                     } else {
                         drag_mode.set(DragMode::PlacingObstacles);
                         on_mouse_down.emit((cell_x, cell_y));
@@ -423,64 +1164,427 @@ pub fn canvas(props: &CanvasProps) -> Html {
         })
     };
 
-    let onmousemove = {
+    let onpointermove = {
         let canvas_ref = canvas_ref.clone();
+        let viewport = viewport.clone();
         let drag_mode = drag_mode.clone();
+        let hovered_cell = hovered_cell.clone();
+        let is_active_pointer = is_active_pointer.clone();
+        let on_hover = props.on_hover.clone();
         let on_mouse_move = props.on_mouse_move.clone();
         let on_start_drag = props.on_start_drag.clone();
         let on_goal_drag = props.on_goal_drag.clone();
 
-        Callback::from(move |e: MouseEvent| {
-            if *drag_mode == DragMode::None {
+        Callback::from(move |e: PointerEvent| {
+            let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() else {
                 return;
+            };
+            let cell = event_cell(
+                &canvas,
+                &e,
+                *viewport.borrow(),
+                cell_size_val,
+                width,
+                height,
+            );
+            if e.is_primary() && *hovered_cell != cell {
+                hovered_cell.set(cell);
+                on_hover.emit(cell);
             }
 
-            if let Some(canvas) = canvas_ref.cast::<HtmlCanvasElement>() {
-                let rect = canvas.get_bounding_client_rect();
-                let x = e.client_x() as f64 - rect.left();
-                let y = e.client_y() as f64 - rect.top();
-
-                let cell_x = (x / cell_size_val).floor() as usize;
-                let cell_y = (y / cell_size_val).floor() as usize;
-
-                if cell_x < width && cell_y < height {
-                    match *drag_mode {
-                        DragMode::PlacingObstacles => on_mouse_move.emit((cell_x, cell_y)),
-                        DragMode::MovingStart => on_start_drag.emit((cell_x, cell_y)),
-                        DragMode::MovingGoal => on_goal_drag.emit((cell_x, cell_y)),
-                        DragMode::None => {}
-                    }
+            if *drag_mode == DragMode::None || !is_active_pointer(&e) {
+                return;
+            }
+
+            if let Some(cell) = cell {
+                match *drag_mode {
+                    DragMode::PlacingObstacles => on_mouse_move.emit(cell),
+                    DragMode::MovingStart => on_start_drag.emit(cell),
+                    DragMode::MovingGoal => on_goal_drag.emit(cell),
+                    DragMode::None => {}
                 }
             }
         })
     };
 
-    let onmouseup = {
+    // Shared by pointerup, pointercancel and pointerleave (after clearing the hover)
+    let onpointerend = {
         let drag_mode = drag_mode.clone();
+        let active_pointer = active_pointer.clone();
         let on_mouse_up = props.on_mouse_up.clone();
-        Callback::from(move |_: MouseEvent| {
+        Callback::from(move |e: PointerEvent| {
+            if !is_active_pointer(&e) {
+                return;
+            }
+            *active_pointer.borrow_mut() = None;
             drag_mode.set(DragMode::None);
             on_mouse_up.emit(());
         })
     };
 
-    let onmouseleave = {
-        let drag_mode = drag_mode.clone();
-        let on_mouse_up = props.on_mouse_up.clone();
-        Callback::from(move |_: MouseEvent| {
-            drag_mode.set(DragMode::None);
-            on_mouse_up.emit(());
+    let onpointerleave = {
+        let hovered_cell = hovered_cell.clone();
+        let onpointerend = onpointerend.clone();
+        let on_hover = props.on_hover.clone();
+        Callback::from(move |e: PointerEvent| {
+            hovered_cell.set(None);
+            on_hover.emit(None);
+            onpointerend.emit(e);
         })
     };
 
+    let hover_label = (*hovered_cell).map(|cell| {
+        let kind = if cell == visual_start {
+            CellKind::Start
+        } else if cell == rover_state.goal || props.goals.iter().any(|&(goal, _)| goal == cell) {
+            CellKind::Goal
+        } else if props.amber_dobs.contains(&cell) {
+            CellKind::Amber
+        } else if rover_state.converted_obstacles.contains(&cell) {
+            CellKind::Blue
+        } else if rover_state.obstacles.contains(&cell) {
+            CellKind::Static
+        } else {
+            CellKind::Free
+        };
+        match props.occupancy.get(&cell) {
+            Some(p) => format!("({}, {}) · {} · p {:.2}", cell.0, cell.1, kind.label(), p),
+            None => format!("({}, {}) · {}", cell.0, cell.1, kind.label()),
+        }
+    });
+
     html! {
-        <canvas
-            ref={canvas_ref}
-            onmousedown={onmousedown}
-            onmousemove={onmousemove}
-            onmouseup={onmouseup}
-            onmouseleave={onmouseleave}
-            style="display: block; border-radius: 12px; box-shadow: 0 4px 6px -1px rgba(0, 0, 0, 0.1), 0 2px 4px -1px rgba(0, 0, 0, 0.06); cursor: crosshair;"
-        />
+        <>
+            <canvas
+                ref={canvas_ref}
+                onpointerdown={onpointerdown}
+                onpointermove={onpointermove}
+                onpointerup={onpointerend.clone()}
+                onpointercancel={onpointerend.clone()}
+                onpointerleave={onpointerleave}
+                oncontextmenu={Callback::from(|e: MouseEvent| e.prevent_default())}
+                style={format!(
+                    "width: {}px; height: {}px; {}",
+                    width as f64 * cell_size_val,
+                    height as f64 * cell_size_val,
+                    "display: block; border-radius: 12px; box-shadow: 0 4px 6px -1px rgba(0, 0, 0, 0.1), 0 2px 4px -1px rgba(0, 0, 0, 0.06); cursor: crosshair; touch-action: none;"
+                )}
+            />
+            <div class="canvas-view-controls">
+                <button
+                    class={classes!("view-btn", (*follow_rover).then_some("active"))}
+                    onclick={on_toggle_follow}
+                    title="Keep the rover centered (scroll to zoom in)"
+                >
+                    { "🎯 Follow" }
+                </button>
+                <button class="view-btn" onclick={on_fit} title="Show the whole grid, sized to fit">
+                    { "⤢ Fit" }
+                </button>
+            </div>
+            <canvas
+                ref={minimap_ref}
+                class="canvas-minimap"
+                hidden=true
+                onclick={on_minimap_click}
+                title="Whole grid; click to move the view there"
+                style={format!(
+                    "width: {}px; height: {}px;",
+                    width as f64 * minimap_scale(width, height),
+                    height as f64 * minimap_scale(width, height)
+                )}
+            />
+            {if let Some(label) = hover_label {
+                html! { <div class="canvas-hover-label">{ label }</div> }
+            } else {
+                html! {}
+            }}
+        </>
     }
 }
+
+/// CSS pixels per cell in the overview, so its longer side is `MINIMAP_PX`.
+fn minimap_scale(width: usize, height: usize) -> f64 {
+    MINIMAP_PX / width.max(height).max(1) as f64
+}
+
+/// The whole grid in miniature: walls, DOBs, goals and the rover, with the
+/// part the main view shows outlined. `visible` is the view's top-left and
+/// bottom-right corners in world pixels, plus the cell size they're in.
+#[allow(clippy::too_many_arguments)]
+fn draw_minimap(
+    canvas: &HtmlCanvasElement,
+    palette: &Palette,
+    (width, height): (usize, usize),
+    blocked: &[(&HashSet<Coord>, &str)],
+    amber_dobs: &[Coord],
+    goals: &[(Coord, bool)],
+    (rover_x, rover_y): (f64, f64),
+    ((left, top), (right, bottom), cell_size): ((f64, f64), (f64, f64), f64),
+) {
+    let scale = minimap_scale(width, height);
+    let (w_px, h_px) = (width as f64 * scale, height as f64 * scale);
+    let dpr = device_pixel_ratio();
+    canvas.set_width((w_px * dpr).round() as u32);
+    canvas.set_height((h_px * dpr).round() as u32);
+    let Some(context) = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|c| c.dyn_into::<web_sys::CanvasRenderingContext2d>().ok())
+    else {
+        return;
+    };
+    let _ = context.set_transform(dpr, 0.0, 0.0, dpr, 0.0, 0.0);
+    context.set_fill_style_str(palette.background);
+    context.fill_rect(0.0, 0.0, w_px, h_px);
+
+    // Cells never shrink below a pixel, or a big grid's walls would vanish
+    let dot = scale.max(1.0);
+    let fill_cells = |cells: &mut dyn Iterator<Item = Coord>, color: &str| {
+        context.set_fill_style_str(color);
+        for (x, y) in cells {
+            context.fill_rect(x as f64 * scale, y as f64 * scale, dot, dot);
+        }
+    };
+    for &(cells, color) in blocked {
+        fill_cells(&mut cells.iter().copied(), color);
+    }
+    fill_cells(&mut amber_dobs.iter().copied(), palette.amber_dob);
+
+    // Goals and the rover get a few pixels whatever the scale, so they stay findable
+    let marker = scale.max(4.0);
+    context.set_fill_style_str(palette.goal);
+    for &((gx, gy), _) in goals {
+        context.fill_rect(
+            (gx as f64 + 0.5) * scale - marker / 2.0,
+            (gy as f64 + 0.5) * scale - marker / 2.0,
+            marker,
+            marker,
+        );
+    }
+    context.set_fill_style_str("#8b7355");
+    context.begin_path();
+    let _ = context.arc(
+        (rover_x + 0.5) * scale,
+        (rover_y + 0.5) * scale,
+        marker / 2.0 + 1.0,
+        0.0,
+        std::f64::consts::PI * 2.0,
+    );
+    context.fill();
+
+    let to_mini = scale / cell_size;
+    context.set_stroke_style_str(palette.selected);
+    context.set_line_width(1.5);
+    context.stroke_rect(
+        left * to_mini,
+        top * to_mini,
+        (right - left) * to_mini,
+        (bottom - top) * to_mini,
+    );
+}
+
+/// `window.devicePixelRatio`, or 1.0 when it is missing, zero or not a number.
+fn device_pixel_ratio() -> f64 {
+    web_sys::window()
+        .map(|w| w.device_pixel_ratio())
+        .filter(|ratio| ratio.is_finite() && *ratio > 0.0)
+        .unwrap_or(1.0)
+}
+
+/// Outline the next cell and point an arrow at it from the rover's edge.
+fn draw_next_step(
+    context: &web_sys::CanvasRenderingContext2d,
+    (fx, fy): (f64, f64),
+    (tx, ty): (f64, f64),
+    cell_size: f64,
+    color: &str,
+) {
+    context.set_stroke_style_str(color);
+    context.set_line_width(2.0);
+    context.stroke_rect(
+        tx - cell_size / 2.0 + 1.5,
+        ty - cell_size / 2.0 + 1.5,
+        cell_size - 3.0,
+        cell_size - 3.0,
+    );
+
+    let (dx, dy) = (tx - fx, ty - fy);
+    let length = dx.hypot(dy);
+    if length < 1.0 {
+        return; // Already there mid-slide
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    let (sx, sy) = (fx + ux * 10.0, fy + uy * 10.0); // Leave the rover body clear
+    let head = (cell_size * 0.3).max(4.0);
+    let (bx, by) = (tx - ux * head, ty - uy * head);
+
+    context.set_fill_style_str(color);
+    if length > 10.0 + head {
+        context.set_line_cap("round");
+        context.begin_path();
+        context.move_to(sx, sy);
+        context.line_to(bx, by);
+        context.stroke();
+    }
+
+    context.begin_path();
+    context.move_to(tx, ty);
+    context.line_to(bx - uy * head * 0.6, by + ux * head * 0.6);
+    context.line_to(bx + uy * head * 0.6, by - ux * head * 0.6);
+    context.close_path();
+    context.fill();
+}
+
+/// Goal-reached flash plus a ring of confetti flying outward; `progress`
+/// runs 0.0 → 1.0 over `CELEBRATION_MS`.
+fn draw_celebration(
+    context: &web_sys::CanvasRenderingContext2d,
+    cx: f64,
+    cy: f64,
+    cell_size: f64,
+    progress: f64,
+) {
+    let fade = 1.0 - progress;
+
+    // Pulsing gold halo on the goal cell
+    let flash = fade * (0.5 + 0.5 * (progress * 24.0).sin().abs());
+    context.set_fill_style_str(&format!("rgba(250, 204, 21, {:.3})", 0.6 * flash));
+    context.fill_rect(
+        cx - cell_size / 2.0,
+        cy - cell_size / 2.0,
+        cell_size,
+        cell_size,
+    );
+
+    let pieces = 24;
+    let spread = cell_size * (0.5 + 3.0 * progress);
+    context.save();
+    context.set_global_alpha(fade);
+    for i in 0..pieces {
+        let angle = i as f64 * std::f64::consts::TAU / pieces as f64 + progress * 2.0;
+        // Alternate pieces fly a little further so the ring looks scattered
+        let distance = spread * if i % 2 == 0 { 1.0 } else { 0.75 };
+        let px = cx + angle.cos() * distance;
+        let py = cy + angle.sin() * distance + progress * progress * cell_size;
+        context.set_fill_style_str(CONFETTI_COLORS[i % CONFETTI_COLORS.len()]);
+        context.fill_rect(px - 2.0, py - 2.0, 4.0, 4.0);
+    }
+    context.restore();
+}
+
+/// Begin a path outlining the detection area of `metric` around (cx, cy).
+fn trace_detection_shape(
+    context: &web_sys::CanvasRenderingContext2d,
+    metric: DistanceMetric,
+    cx: f64,
+    cy: f64,
+    radius: f64,
+) {
+    context.begin_path();
+    match metric {
+        DistanceMetric::Euclidean => {
+            context
+                .arc(cx, cy, radius, 0.0, std::f64::consts::PI * 2.0)
+                .unwrap();
+        }
+        DistanceMetric::Chebyshev => {
+            context.rect(cx - radius, cy - radius, radius * 2.0, radius * 2.0);
+        }
+        DistanceMetric::Manhattan => {
+            context.move_to(cx, cy - radius);
+            context.line_to(cx + radius, cy);
+            context.line_to(cx, cy + radius);
+            context.line_to(cx - radius, cy);
+            context.close_path();
+        }
+    }
+}
+
+/// Begin a path outlining the forward cone: a wedge of `radius` centred on `angle`.
+fn trace_cone(
+    context: &web_sys::CanvasRenderingContext2d,
+    angle: f64,
+    cx: f64,
+    cy: f64,
+    radius: f64,
+) {
+    context.begin_path();
+    context.move_to(cx, cy);
+    context
+        .arc(
+            cx,
+            cy,
+            radius,
+            angle - CONE_HALF_ANGLE,
+            angle + CONE_HALF_ANGLE,
+        )
+        .unwrap();
+    context.close_path();
+}
+
+/// Map a pointer position to the grid cell under it (through the current
+/// zoom and pan), if inside the grid.
+fn event_cell(
+    canvas: &HtmlCanvasElement,
+    e: &PointerEvent,
+    viewport: Viewport,
+    cell_size: f64,
+    width: usize,
+    height: usize,
+) -> Option<Coord> {
+    let (x, y) = event_world(canvas, e, viewport);
+    if x < 0.0 || y < 0.0 {
+        return None;
+    }
+
+    let cell_x = (x / cell_size).floor() as usize;
+    let cell_y = (y / cell_size).floor() as usize;
+    (cell_x < width && cell_y < height).then_some((cell_x, cell_y))
+}
+
+/// The cell border under the pointer, as the `thin_wall` pair either side
+/// of it: the nearest border of the cell clicked, if it lies within
+/// `EDGE_HIT_FRACTION` and another cell of the grid is on its far side.
+fn event_edge(
+    canvas: &HtmlCanvasElement,
+    e: &PointerEvent,
+    viewport: Viewport,
+    cell_size: f64,
+    width: usize,
+    height: usize,
+) -> Option<(Coord, Coord)> {
+    let cell @ (cx, cy) = event_cell(canvas, e, viewport, cell_size, width, height)?;
+    let (x, y) = event_world(canvas, e, viewport);
+    let (fx, fy) = (x / cell_size - cx as f64, y / cell_size - cy as f64);
+    [
+        (fx, cx.checked_sub(1).map(|nx| (nx, cy))),
+        (1.0 - fx, Some((cx + 1, cy))),
+        (fy, cy.checked_sub(1).map(|ny| (cx, ny))),
+        (1.0 - fy, Some((cx, cy + 1))),
+    ]
+    .into_iter()
+    .filter_map(|(distance, neighbor)| Some((distance, neighbor?)))
+    .filter(|&(_, (nx, ny))| nx < width && ny < height)
+    .min_by(|a, b| a.0.total_cmp(&b.0))
+    .filter(|&(distance, _)| distance < EDGE_HIT_FRACTION)
+    .map(|(_, neighbor)| thin_wall(cell, neighbor))
+}
+
+/// Cell size that fits a `width` x `height` grid into a container of
+/// `container_w` x `container_h` CSS pixels, within the cell size bounds.
+fn fit_cell_size(container_w: f64, container_h: f64, width: usize, height: usize) -> f64 {
+    let cell_w = (container_w - FIT_MARGIN_PX) / width as f64;
+    let cell_h = (container_h - FIT_MARGIN_PX) / height as f64;
+    cell_w.min(cell_h).clamp(MIN_CELL_PX, MAX_CELL_PX)
+}
+
+/// Pointer position in grid space, through the current zoom and pan.
+fn event_world(canvas: &HtmlCanvasElement, e: &PointerEvent, viewport: Viewport) -> (f64, f64) {
+    let rect = canvas.get_bounding_client_rect();
+    viewport.to_world((
+        e.client_x() as f64 - rect.left(),
+        e.client_y() as f64 - rect.top(),
+    ))
+}
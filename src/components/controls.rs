@@ -1,34 +1,493 @@
 // src/components/controls.rs
 
+use std::collections::HashSet;
+use std::rc::Rc;
+
 use wasm_bindgen::JsCast;
-use web_sys::{HtmlInputElement, HtmlSelectElement};
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
 use yew::prelude::*;
 
+use crate::frames::MAX_FRAMES;
+use crate::palette::ColorScheme;
+use crate::pathfinding::{preset_name, Connectivity, Coord, ALGORITHM_PRESETS};
+use crate::rover::MIN_TERRAIN_COST;
+use crate::sensor::{ConversionPolicy, DistanceMetric, FieldOfView, Sensor, SENSOR_RANGES};
+use crate::serialize::parse_ascii_map;
+use crate::url_params::{Scenario, SIZE_RANGE};
+
 #[derive(Properties, PartialEq)]
 pub struct ControlsProps {
     pub on_compute: Callback<()>,
+    pub on_cancel_compute: Callback<()>,
     pub on_start_journey: Callback<()>,
     pub on_pause: Callback<()>,
+    pub on_step_back: Callback<()>,
+    pub can_step_back: bool, // The rover has moved off the journey start
     pub on_reset: Callback<()>,
     pub on_restart: Callback<()>,
+    pub on_clear_dobs: Callback<()>,
     pub on_algo_change: Callback<String>,
     pub on_speed_change: Callback<u32>,
     pub current_algorithm: String,
     pub current_speed: u32,
     pub is_computing: bool,
     pub is_animating: bool,
+    pub is_paused: bool,
     pub path_computed: bool,
     pub on_toggle_panel: Callback<()>,
     pub is_panel_minimized: bool,
+    pub replay_len: usize,
+    pub replay_index: Option<usize>,
+    pub on_replay_seek: Callback<Option<usize>>,
+    pub sensor: Sensor,
+    pub on_sensor_change: Callback<Sensor>,
+    pub conversion_policy: ConversionPolicy,
+    pub on_conversion_policy_change: Callback<ConversionPolicy>,
+    pub wander: bool,
+    pub wander_seed: u64,
+    pub on_toggle_wander: Callback<()>,
+    pub on_wander_seed_change: Callback<u64>,
+    pub show_cost_field: bool,
+    pub on_toggle_cost_field: Callback<()>,
+    pub what_if: bool,
+    pub on_toggle_what_if: Callback<()>,
+    pub capture_frames: bool,
+    pub on_toggle_capture_frames: Callback<()>,
+    pub captured_frames: usize, // Frames held so far; capture stops at MAX_FRAMES
+    pub on_download_frames: Callback<()>,
+    pub allow_diagonals: bool,
+    pub diagonal_cost: f64,
+    pub on_diagonal_cost_change: Callback<f64>,
+    pub turn_penalty: f64,
+    pub on_turn_penalty_change: Callback<f64>,
+    pub sensor_limited: bool,
+    pub on_toggle_sensor_limited: Callback<()>,
+    pub no_revisit: bool,
+    pub on_toggle_no_revisit: Callback<()>,
+    pub start: Coord,
+    pub goal: Coord,
+    pub grid_size: (usize, usize),
+    pub blocked_cells: Rc<HashSet<Coord>>, // Obstacles and DOBs; endpoints can't go here
+    pub on_start_drag: Callback<Coord>,
+    pub on_goal_drag: Callback<Coord>,
+    pub brush_size: usize,
+    pub on_brush_change: Callback<usize>,
+    pub frame_rate: u32,
+    pub on_frame_rate_change: Callback<u32>,
+    pub color_scheme: ColorScheme,
+    pub on_color_scheme_change: Callback<ColorScheme>,
+    pub maze_seed: Option<u64>, // Seed of the maze on screen, if unedited
+    pub share_query: Option<String>, // "?start=..&seed=.." link that rebuilds it
+    pub on_generate_maze: Callback<Option<u64>>, // None = pick a fresh seed
+    pub selected_cell: Option<Coord>, // Ctrl+clicked cell whose terrain cost is being edited
+    pub selected_cost: f64,
+    pub on_terrain_cost_change: Callback<(Coord, f64)>,
+    pub selected_occupancy: f64, // Belief that the selected cell is occupied; 0.0 = certain ground
+    pub on_occupancy_change: Callback<(Coord, f64)>,
+    pub on_deselect_cell: Callback<()>,
+    pub occupancy_threshold: f64,
+    pub on_occupancy_threshold_change: Callback<f64>,
+    pub on_import_map: Callback<Scenario>,
+}
+
+/// Square brush widths offered for painting obstacles.
+const BRUSH_SIZES: [usize; 3] = [1, 3, 5];
+
+/// Canvas redraw rates offered while the rover moves, in frames per second.
+const FRAME_RATES: [u32; 4] = [10, 20, 30, 60];
+
+#[derive(Properties, PartialEq)]
+struct CoordEntryProps {
+    label: &'static str,
+    value: Coord,
+    other_endpoint: Coord,
+    grid_size: (usize, usize),
+    blocked_cells: Rc<HashSet<Coord>>,
+    on_place: Callback<Coord>,
+    disabled: bool,
+}
+
+/// Check a typed cell before moving an endpoint there.
+fn validate_placement(
+    x: &str,
+    y: &str,
+    (width, height): (usize, usize),
+    blocked_cells: &HashSet<Coord>,
+    other_endpoint: Coord,
+) -> Result<Coord, String> {
+    let (Ok(x), Ok(y)) = (x.trim().parse::<usize>(), y.trim().parse::<usize>()) else {
+        return Err("Enter whole numbers for X and Y".to_string());
+    };
+    if x >= width || y >= height {
+        return Err(format!(
+            "({}, {}) is off the grid (0-{}, 0-{})",
+            x,
+            y,
+            width - 1,
+            height - 1
+        ));
+    }
+    if blocked_cells.contains(&(x, y)) {
+        return Err(format!("({}, {}) is an obstacle", x, y));
+    }
+    if (x, y) == other_endpoint {
+        return Err(format!("({}, {}) is taken by the other endpoint", x, y));
+    }
+    Ok((x, y))
+}
+
+/// X/Y inputs for placing start or goal exactly; bad cells are reported inline.
+#[function_component(CoordEntry)]
+fn coord_entry(props: &CoordEntryProps) -> Html {
+    let x_text = use_state(|| props.value.0.to_string());
+    let y_text = use_state(|| props.value.1.to_string());
+    let error = use_state(|| None::<String>);
+
+    // Follow the endpoint when it's dragged on the canvas
+    {
+        let x_text = x_text.clone();
+        let y_text = y_text.clone();
+        let error = error.clone();
+        use_effect_with(props.value, move |&(x, y)| {
+            x_text.set(x.to_string());
+            y_text.set(y.to_string());
+            error.set(None);
+        });
+    }
+
+    let on_input = |text: UseStateHandle<String>| {
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                text.set(input.value());
+            }
+        })
+    };
+
+    let on_set = {
+        let x_text = x_text.clone();
+        let y_text = y_text.clone();
+        let error = error.clone();
+        let grid_size = props.grid_size;
+        let blocked_cells = props.blocked_cells.clone();
+        let other_endpoint = props.other_endpoint;
+        let on_place = props.on_place.clone();
+        Callback::from(move |_: MouseEvent| {
+            match validate_placement(&x_text, &y_text, grid_size, &blocked_cells, other_endpoint) {
+                Ok(cell) => {
+                    error.set(None);
+                    on_place.emit(cell);
+                }
+                Err(message) => error.set(Some(message)),
+            }
+        })
+    };
+
+    html! {
+        <div class="coord-entry">
+            <div class="coord-row">
+                <span class="control-label">{ props.label }</span>
+                <input
+                    type="number"
+                    class="coord-input"
+                    min="0"
+                    max={(props.grid_size.0 - 1).to_string()}
+                    value={(*x_text).clone()}
+                    oninput={on_input(x_text.clone())}
+                    disabled={props.disabled}
+                    aria-label={format!("{} X", props.label)}
+                    aria-invalid={error.is_some().to_string()}
+                />
+                <input
+                    type="number"
+                    class="coord-input"
+                    min="0"
+                    max={(props.grid_size.1 - 1).to_string()}
+                    value={(*y_text).clone()}
+                    oninput={on_input(y_text.clone())}
+                    disabled={props.disabled}
+                    aria-label={format!("{} Y", props.label)}
+                    aria-invalid={error.is_some().to_string()}
+                />
+                <button
+                    class="btn btn-secondary coord-set-btn"
+                    onclick={on_set}
+                    disabled={props.disabled}
+                    aria-label={format!("Move {} to these coordinates", props.label.to_lowercase())}
+                >
+                    { "Set" }
+                </button>
+            </div>
+            {if let Some(message) = (*error).clone() {
+                html! { <div class="coord-error" role="alert">{ message }</div> }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct TerrainCostEntryProps {
+    cell: Coord,
+    cost: f64,
+    on_set: Callback<(Coord, f64)>,
+    on_close: Callback<()>,
+    disabled: bool,
+}
+
+/// Check a typed terrain cost; plain ground is 1.0 and nothing may be cheaper.
+fn parse_terrain_cost(text: &str) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        Ok(cost) if cost.is_finite() && cost >= MIN_TERRAIN_COST => Ok(cost),
+        Ok(_) => Err(format!("Cost must be at least {:.1}", MIN_TERRAIN_COST)),
+        Err(_) => Err("Enter a number".to_string()),
+    }
+}
+
+/// Exact cost for one cell, for fine-tuning weighted scenarios; 1.0 clears it.
+#[function_component(TerrainCostEntry)]
+fn terrain_cost_entry(props: &TerrainCostEntryProps) -> Html {
+    let text = use_state(|| props.cost.to_string());
+    let error = use_state(|| None::<String>);
+
+    // Show the selected cell's cost whenever the selection or its cost changes
+    {
+        let text = text.clone();
+        let error = error.clone();
+        use_effect_with((props.cell, props.cost), move |&(_, cost)| {
+            text.set(cost.to_string());
+            error.set(None);
+        });
+    }
+
+    let on_input = {
+        let text = text.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                text.set(input.value());
+            }
+        })
+    };
+
+    let on_set = {
+        let text = text.clone();
+        let error = error.clone();
+        let cell = props.cell;
+        let on_set = props.on_set.clone();
+        Callback::from(move |_: MouseEvent| match parse_terrain_cost(&text) {
+            Ok(cost) => {
+                error.set(None);
+                on_set.emit((cell, cost));
+            }
+            Err(message) => error.set(Some(message)),
+        })
+    };
+
+    let on_close = props.on_close.clone();
+    let (x, y) = props.cell;
+
+    html! {
+        <div class="coord-entry">
+            <div class="coord-row" title="Cost of entering this cell (A* and Dijkstra); 1.0 is plain ground">
+                <label class="control-label" for="terrain-cost-input">{ format!("Cost ({}, {})", x, y) }</label>
+                <input
+                    id="terrain-cost-input"
+                    type="number"
+                    class="coord-input"
+                    min={MIN_TERRAIN_COST.to_string()}
+                    step="0.5"
+                    value={(*text).clone()}
+                    oninput={on_input}
+                    disabled={props.disabled}
+                    aria-invalid={error.is_some().to_string()}
+                />
+                <button class="btn btn-secondary coord-set-btn" onclick={on_set} disabled={props.disabled}>
+                    { "Set" }
+                </button>
+                <button
+                    class="btn btn-secondary coord-set-btn"
+                    onclick={Callback::from(move |_| on_close.emit(()))}
+                    aria-label="Deselect cell"
+                >
+                    { "✕" }
+                </button>
+            </div>
+            {if let Some(message) = (*error).clone() {
+                html! { <div class="coord-error" role="alert">{ message }</div> }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct OccupancyEntryProps {
+    cell: Coord,
+    probability: f64,
+    on_set: Callback<(Coord, f64)>,
+    disabled: bool,
+}
+
+/// Check a typed occupancy probability.
+fn parse_occupancy(text: &str) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+        Ok(_) => Err("Probability must be between 0 and 1".to_string()),
+        Err(_) => Err("Enter a number".to_string()),
+    }
+}
+
+/// How likely one cell is to be occupied, for uncertain maps; 0 clears it.
+#[function_component(OccupancyEntry)]
+fn occupancy_entry(props: &OccupancyEntryProps) -> Html {
+    let text = use_state(|| props.probability.to_string());
+    let error = use_state(|| None::<String>);
+
+    // Show the selected cell's belief whenever the selection or its belief changes
+    {
+        let text = text.clone();
+        let error = error.clone();
+        use_effect_with((props.cell, props.probability), move |&(_, probability)| {
+            text.set(probability.to_string());
+            error.set(None);
+        });
+    }
+
+    let on_input = {
+        let text = text.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                text.set(input.value());
+            }
+        })
+    };
+
+    let on_set = {
+        let text = text.clone();
+        let error = error.clone();
+        let cell = props.cell;
+        let on_set = props.on_set.clone();
+        Callback::from(move |_: MouseEvent| match parse_occupancy(&text) {
+            Ok(probability) => {
+                error.set(None);
+                on_set.emit((cell, probability));
+            }
+            Err(message) => error.set(Some(message)),
+        })
+    };
+
+    html! {
+        <div class="coord-entry">
+            <div class="coord-row" title="Chance this cell is occupied. The rover plans around it at or above the threshold, and its sensor raises or lowers the chance on every look">
+                <label class="control-label" for="occupancy-input">{ "Occupied p" }</label>
+                <input
+                    id="occupancy-input"
+                    type="number"
+                    class="coord-input"
+                    min="0"
+                    max="1"
+                    step="0.05"
+                    value={(*text).clone()}
+                    oninput={on_input}
+                    disabled={props.disabled}
+                    aria-invalid={error.is_some().to_string()}
+                />
+                <button class="btn btn-secondary coord-set-btn" onclick={on_set} disabled={props.disabled}>
+                    { "Set" }
+                </button>
+            </div>
+            {if let Some(message) = (*error).clone() {
+                html! { <div class="coord-error" role="alert">{ message }</div> }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct MapImportProps {
+    on_import: Callback<Scenario>,
+    disabled: bool,
+}
+
+/// Paste an ASCII map (`#` wall, `.` free, `S`/`G`); problems are reported inline.
+#[function_component(MapImport)]
+fn map_import(props: &MapImportProps) -> Html {
+    let text = use_state(String::new);
+    let error = use_state(|| None::<String>);
+
+    let on_input = {
+        let text = text.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(area) = e.target_dyn_into::<HtmlTextAreaElement>() {
+                text.set(area.value());
+            }
+        })
+    };
+
+    let on_import = {
+        let text = text.clone();
+        let error = error.clone();
+        let on_import = props.on_import.clone();
+        Callback::from(move |_: MouseEvent| match parse_ascii_map(&text) {
+            Ok(scenario)
+                if !SIZE_RANGE.contains(&scenario.width)
+                    || !SIZE_RANGE.contains(&scenario.height) =>
+            {
+                error.set(Some(format!(
+                    "The map is {}x{}; each side must be {}-{} cells",
+                    scenario.width,
+                    scenario.height,
+                    SIZE_RANGE.start(),
+                    SIZE_RANGE.end()
+                )));
+            }
+            Ok(scenario) => {
+                error.set(None);
+                on_import.emit(scenario);
+            }
+            Err(e) => error.set(Some(e.to_string())),
+        })
+    };
+
+    html! {
+        <div class="coord-entry">
+            <label class="control-label" for="ascii-map-input">{ "Import ASCII Map" }</label>
+            <textarea
+                id="ascii-map-input"
+                class="ascii-map-input"
+                rows="4"
+                spellcheck="false"
+                placeholder={"#####\n#S..#\n#.#G#\n#####"}
+                value={(*text).clone()}
+                oninput={on_input}
+                disabled={props.disabled}
+                aria-invalid={error.is_some().to_string()}
+            />
+            <button class="btn btn-secondary" onclick={on_import} disabled={props.disabled}>
+                { "Import" }
+            </button>
+            {if let Some(message) = (*error).clone() {
+                html! { <div class="coord-error" role="alert">{ message }</div> }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
 }
 
 #[function_component(Controls)]
 pub fn controls(props: &ControlsProps) -> Html {
     let on_compute = props.on_compute.clone();
+    let on_cancel_compute = props.on_cancel_compute.clone();
     let on_start_journey = props.on_start_journey.clone();
     let on_pause = props.on_pause.clone();
+    let on_step_back = props.on_step_back.clone();
     let on_reset = props.on_reset.clone();
     let on_restart = props.on_restart.clone();
+    let on_clear_dobs = props.on_clear_dobs.clone();
     let on_algo_change = props.on_algo_change.clone();
     let on_speed_change = props.on_speed_change.clone();
     let on_toggle_panel = props.on_toggle_panel.clone();
@@ -36,8 +495,88 @@ pub fn controls(props: &ControlsProps) -> Html {
     let current_algorithm = props.current_algorithm.clone();
     let is_computing = props.is_computing;
     let is_animating = props.is_animating;
+    let is_paused = props.is_paused;
     let path_computed = props.path_computed;
     let is_panel_minimized = props.is_panel_minimized;
+    let replay_len = props.replay_len;
+    let replay_index = props.replay_index;
+    let on_replay_seek = props.on_replay_seek.clone();
+    let sensor = props.sensor;
+    let on_sensor_change = props.on_sensor_change.clone();
+    let show_cost_field = props.show_cost_field;
+    let on_toggle_cost_field = props.on_toggle_cost_field.clone();
+    let what_if = props.what_if;
+    let on_toggle_what_if = props.on_toggle_what_if.clone();
+    let current_preset = preset_name(
+        &current_algorithm,
+        Connectivity::for_algorithm(&current_algorithm, props.allow_diagonals),
+    );
+    let diagonal_cost = props.diagonal_cost;
+    let on_diagonal_cost_change = props.on_diagonal_cost_change.clone();
+    let turn_penalty = props.turn_penalty;
+    let on_turn_penalty_change = props.on_turn_penalty_change.clone();
+    let occupancy_threshold = props.occupancy_threshold;
+    let on_occupancy_threshold_change = props.on_occupancy_threshold_change.clone();
+    let sensor_limited = props.sensor_limited;
+    let on_toggle_sensor_limited = props.on_toggle_sensor_limited.clone();
+    let no_revisit = props.no_revisit;
+    let on_toggle_no_revisit = props.on_toggle_no_revisit.clone();
+    let on_toggle_wander = props.on_toggle_wander.clone();
+    let on_toggle_capture_frames = props.on_toggle_capture_frames.clone();
+    let on_download_frames = props.on_download_frames.clone();
+    let (start, goal, grid_size) = (props.start, props.goal, props.grid_size);
+    let blocked_cells = props.blocked_cells.clone();
+    let on_start_drag = props.on_start_drag.clone();
+    let on_goal_drag = props.on_goal_drag.clone();
+    let brush_size = props.brush_size;
+    let on_brush_change = props.on_brush_change.clone();
+    let frame_rate = props.frame_rate;
+    let color_scheme = props.color_scheme;
+    let on_color_scheme_change = props.on_color_scheme_change.clone();
+    let on_frame_rate_change = props.on_frame_rate_change.clone();
+    let has_diagonal_cost = props.allow_diagonals && current_algorithm != "Theta*";
+    let has_turn_penalty = matches!(current_algorithm.as_str(), "A*" | "Dijkstra" | "D*-Lite");
+    let has_cost_field = current_algorithm != "Theta*";
+    let maze_seed = props.maze_seed;
+    let share_query = props.share_query.clone();
+    let seed_text = use_state(String::new);
+
+    let on_input_seed = {
+        let seed_text = seed_text.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                seed_text.set(input.value());
+            }
+        })
+    };
+
+    let on_generate_maze = {
+        let seed_text = seed_text.clone();
+        let on_generate_maze = props.on_generate_maze.clone();
+        // A blank or unreadable seed means "surprise me"
+        Callback::from(move |_: MouseEvent| {
+            on_generate_maze.emit(seed_text.trim().parse::<u64>().ok());
+        })
+    };
+
+    // Focus rings only after Tab, so clicking doesn't leave outlines behind
+    let keyboard_focus = use_state(|| false);
+    let on_panel_keydown = {
+        let keyboard_focus = keyboard_focus.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            if e.key() == "Tab" && !*keyboard_focus {
+                keyboard_focus.set(true);
+            }
+        })
+    };
+    let on_panel_pointerdown = {
+        let keyboard_focus = keyboard_focus.clone();
+        Callback::from(move |_: PointerEvent| {
+            if *keyboard_focus {
+                keyboard_focus.set(false);
+            }
+        })
+    };
 
     let on_change_algo = Callback::from(move |e: Event| {
         let select = e
@@ -49,6 +588,105 @@ pub fn controls(props: &ControlsProps) -> Html {
         on_algo_change.emit(alg_str);
     });
 
+    let on_change_metric = {
+        let on_sensor_change = on_sensor_change.clone();
+        Callback::from(move |e: Event| {
+            let select = e
+                .target()
+                .unwrap()
+                .dyn_into::<HtmlSelectElement>()
+                .expect("should be a select element");
+            if let Some(metric) = DistanceMetric::from_label(&select.value()) {
+                on_sensor_change.emit(Sensor { metric, ..sensor });
+            }
+        })
+    };
+
+    let on_change_field_of_view = {
+        let on_sensor_change = on_sensor_change.clone();
+        Callback::from(move |e: Event| {
+            let select = e
+                .target()
+                .unwrap()
+                .dyn_into::<HtmlSelectElement>()
+                .expect("should be a select element");
+            if let Some(field_of_view) = FieldOfView::from_label(&select.value()) {
+                on_sensor_change.emit(Sensor {
+                    field_of_view,
+                    ..sensor
+                });
+            }
+        })
+    };
+
+    let on_change_policy = {
+        let on_conversion_policy_change = props.on_conversion_policy_change.clone();
+        Callback::from(move |e: Event| {
+            let select = e
+                .target()
+                .unwrap()
+                .dyn_into::<HtmlSelectElement>()
+                .expect("should be a select element");
+            if let Some(policy) = ConversionPolicy::from_label(&select.value()) {
+                on_conversion_policy_change.emit(policy);
+            }
+        })
+    };
+
+    let on_input_wander_seed = {
+        let on_wander_seed_change = props.on_wander_seed_change.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                if let Ok(seed) = input.value().trim().parse::<u64>() {
+                    on_wander_seed_change.emit(seed);
+                }
+            }
+        })
+    };
+
+    let on_change_sensor_range = Callback::from(move |e: InputEvent| {
+        if let Some(target) = e.target() {
+            if let Ok(input) = target.dyn_into::<HtmlInputElement>() {
+                if let Ok(range) = input.value().parse::<f64>() {
+                    on_sensor_change.emit(Sensor { range, ..sensor });
+                }
+            }
+        }
+    });
+
+    let on_change_brush = Callback::from(move |e: Event| {
+        let select = e
+            .target()
+            .unwrap()
+            .dyn_into::<HtmlSelectElement>()
+            .expect("should be a select element");
+        if let Ok(size) = select.value().parse::<usize>() {
+            on_brush_change.emit(size);
+        }
+    });
+
+    let on_change_frame_rate = Callback::from(move |e: Event| {
+        let select = e
+            .target()
+            .unwrap()
+            .dyn_into::<HtmlSelectElement>()
+            .expect("should be a select element");
+        if let Ok(fps) = select.value().parse::<u32>() {
+            on_frame_rate_change.emit(fps);
+        }
+    });
+
+    let on_change_color_scheme = Callback::from(move |e: Event| {
+        let select = e
+            .target()
+            .unwrap()
+            .dyn_into::<HtmlSelectElement>()
+            .expect("should be a select element");
+        if let Some(scheme) = ColorScheme::from_label(&select.value()) {
+            on_color_scheme_change.emit(scheme);
+        }
+    });
+
     let on_change_speed = Callback::from(move |e: InputEvent| {
         if let Some(target) = e.target() {
             if let Ok(input) = target.dyn_into::<HtmlInputElement>() {
@@ -59,30 +697,93 @@ pub fn controls(props: &ControlsProps) -> Html {
         }
     });
 
-    let find_path_text = if is_computing {
-        "Computing..."
+    let on_change_diagonal_cost = Callback::from(move |e: InputEvent| {
+        if let Some(target) = e.target() {
+            if let Ok(input) = target.dyn_into::<HtmlInputElement>() {
+                if let Ok(val) = input.value().parse::<f64>() {
+                    on_diagonal_cost_change.emit(val);
+                }
+            }
+        }
+    });
+
+    let on_change_turn_penalty = Callback::from(move |e: InputEvent| {
+        if let Some(target) = e.target() {
+            if let Ok(input) = target.dyn_into::<HtmlInputElement>() {
+                if let Ok(val) = input.value().parse::<f64>() {
+                    on_turn_penalty_change.emit(val);
+                }
+            }
+        }
+    });
+
+    let on_change_occupancy_threshold = Callback::from(move |e: InputEvent| {
+        if let Some(target) = e.target() {
+            if let Ok(input) = target.dyn_into::<HtmlInputElement>() {
+                if let Ok(val) = input.value().parse::<f64>() {
+                    on_occupancy_threshold_change.emit(val);
+                }
+            }
+        }
+    });
+
+    let on_change_replay = {
+        let on_replay_seek = on_replay_seek.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(target) = e.target() {
+                if let Ok(input) = target.dyn_into::<HtmlInputElement>() {
+                    if let Ok(val) = input.value().parse::<usize>() {
+                        on_replay_seek.emit(Some(val));
+                    }
+                }
+            }
+        })
+    };
+
+    let show_replay = replay_len > 0 && !is_animating;
+    // Replay shows the last frame until the slider is moved
+    let replay_position = replay_index.unwrap_or(replay_len.saturating_sub(1));
+
+    // While a search runs the same button cancels it
+    let (find_path_icon, find_path_text) = if is_computing {
+        ("✖", "Cancel Search")
     } else {
-        "Find Path"
+        ("🔍", "Find Path")
     };
 
     let start_journey_text = if is_animating {
         "Traveling..."
+    } else if is_paused {
+        "Resume"
     } else {
         "Start Journey"
     };
 
-    let find_path_disabled = is_computing || is_animating;
-    let start_journey_disabled = !path_computed || is_computing || is_animating;
+    let find_path_disabled = is_animating;
+    let start_journey_disabled = !(path_computed || is_paused) || is_computing || is_animating;
     let pause_button_disabled = !is_animating;
+    let step_back_disabled = !is_paused || !props.can_step_back;
 
     html! {
-        <div class={format!("controls-panel {}", if is_panel_minimized { "minimized" } else { "" })}>
+        <div
+            class={classes!(
+                "controls-panel",
+                is_panel_minimized.then_some("minimized"),
+                keyboard_focus.then_some("keyboard-focus"),
+            )}
+            role="region"
+            aria-label="Pathfinder controls"
+            onkeydown={on_panel_keydown}
+            onpointerdown={on_panel_pointerdown}
+        >
             <div class="panel-header">
                 <h3>{ "Scout Pathfinder" }</h3>
                 <div class="header-controls">
                     <button
                         class="toggle-btn"
                         onclick={Callback::from(move |_| on_toggle_panel.emit(()))}
+                        aria-label={if is_panel_minimized { "Expand controls" } else { "Minimize controls" }}
+                        aria-expanded={(!is_panel_minimized).to_string()}
                     >
                         { if is_panel_minimized { "▼" } else { "▲" } }
                     </button>
@@ -96,10 +797,17 @@ pub fn controls(props: &ControlsProps) -> Html {
                             <div class="button-grid">
                                 <button
                                     class={format!("btn btn-primary {}", if find_path_disabled { "disabled" } else { "" })}
-                                    onclick={if find_path_disabled { Callback::noop() } else { Callback::from(move |_| on_compute.emit(())) }}
+                                    onclick={if find_path_disabled {
+                                        Callback::noop()
+                                    } else if is_computing {
+                                        Callback::from(move |_| on_cancel_compute.emit(()))
+                                    } else {
+                                        Callback::from(move |_| on_compute.emit(()))
+                                    }}
                                     disabled={find_path_disabled}
+                                    aria-busy={is_computing.to_string()}
                                 >
-                                    <span class="btn-icon">{ "🔍" }</span>
+                                    <span class="btn-icon" aria-hidden="true">{ find_path_icon }</span>
                                     { find_path_text }
                                 </button>
 
@@ -107,8 +815,9 @@ pub fn controls(props: &ControlsProps) -> Html {
                                     class={format!("btn btn-success {}", if start_journey_disabled { "disabled" } else { "" })}
                                     onclick={if start_journey_disabled { Callback::noop() } else { Callback::from(move |_| on_start_journey.emit(())) }}
                                     disabled={start_journey_disabled}
+                                    aria-busy={is_animating.to_string()}
                                 >
-                                    <span class="btn-icon">{ "🚀" }</span>
+                                    <span class="btn-icon" aria-hidden="true">{ "🚀" }</span>
                                     { start_journey_text }
                                 </button>
 
@@ -116,17 +825,28 @@ pub fn controls(props: &ControlsProps) -> Html {
                                     class={format!("btn btn-secondary {}", if pause_button_disabled { "disabled" } else { "" })}
                                     onclick={if pause_button_disabled { Callback::noop() } else { Callback::from(move |_| on_pause.emit(())) }}
                                     disabled={pause_button_disabled}
+                                    aria-pressed={is_paused.to_string()}
                                 >
-                                    <span class="btn-icon">{ "⏸️" }</span>
+                                    <span class="btn-icon" aria-hidden="true">{ "⏸️" }</span>
                                     { "Pause" }
                                 </button>
 
+                                <button
+                                    class={format!("btn btn-secondary {}", if step_back_disabled { "disabled" } else { "" })}
+                                    onclick={if step_back_disabled { Callback::noop() } else { Callback::from(move |_| on_step_back.emit(())) }}
+                                    disabled={step_back_disabled}
+                                    title="Rewind the rover one cell and replan from there"
+                                >
+                                    <span class="btn-icon" aria-hidden="true">{ "⏮️" }</span>
+                                    { "Step Back" }
+                                </button>
+
                                 <button
                                     class="btn btn-warning"
                                     onclick={Callback::from(move |_| on_restart.emit(()))}
                                     disabled={is_animating}
                                 >
-                                    <span class="btn-icon">{ "🔄" }</span>
+                                    <span class="btn-icon" aria-hidden="true">{ "🔄" }</span>
                                     { "Restart" }
                                 </button>
 
@@ -135,34 +855,393 @@ pub fn controls(props: &ControlsProps) -> Html {
                                     onclick={Callback::from(move |_| on_reset.emit(()))}
                                     disabled={is_animating}
                                 >
-                                    <span class="btn-icon">{ "🔧" }</span>
+                                    <span class="btn-icon" aria-hidden="true">{ "🔧" }</span>
                                     { "Reset" }
                                 </button>
+
+                                <button
+                                    class="btn btn-secondary"
+                                    onclick={Callback::from(move |_| on_clear_dobs.emit(()))}
+                                    disabled={is_animating}
+                                >
+                                    <span class="btn-icon" aria-hidden="true">{ "🧹" }</span>
+                                    { "Clear DOBs" }
+                                </button>
                             </div>
                         </div>
 
+                        <div class="controls-section">
+                            <CoordEntry
+                                label="Start"
+                                value={start}
+                                other_endpoint={goal}
+                                grid_size={grid_size}
+                                blocked_cells={blocked_cells.clone()}
+                                on_place={on_start_drag}
+                                disabled={is_computing || is_animating}
+                            />
+                            <CoordEntry
+                                label="Goal"
+                                value={goal}
+                                other_endpoint={start}
+                                grid_size={grid_size}
+                                blocked_cells={blocked_cells}
+                                on_place={on_goal_drag}
+                                disabled={is_computing || is_animating}
+                            />
+                        </div>
+
+                        {if let Some(cell) = props.selected_cell {
+                            html! {
+                                <div class="controls-section">
+                                    <TerrainCostEntry
+                                        cell={cell}
+                                        cost={props.selected_cost}
+                                        on_set={props.on_terrain_cost_change.clone()}
+                                        on_close={props.on_deselect_cell.clone()}
+                                        disabled={is_computing || is_animating}
+                                    />
+                                    <OccupancyEntry
+                                        cell={cell}
+                                        probability={props.selected_occupancy}
+                                        on_set={props.on_occupancy_change.clone()}
+                                        disabled={is_computing || is_animating}
+                                    />
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        }}
+
+                        <div class="controls-section">
+                            <div class="coord-row" title="The same seed always builds the same maze; leave it blank for a random one">
+                                <label class="control-label" for="maze-seed-input">{ "Maze Seed" }</label>
+                                <input
+                                    id="maze-seed-input"
+                                    type="number"
+                                    class="coord-input"
+                                    min="0"
+                                    placeholder="random"
+                                    value={(*seed_text).clone()}
+                                    oninput={on_input_seed}
+                                    disabled={is_computing || is_animating}
+                                />
+                                <button
+                                    class="btn btn-secondary coord-set-btn"
+                                    onclick={on_generate_maze}
+                                    disabled={is_computing || is_animating}
+                                >
+                                    { "Generate Maze" }
+                                </button>
+                            </div>
+                            <MapImport
+                                on_import={props.on_import_map.clone()}
+                                disabled={is_computing || is_animating}
+                            />
+                            {if let (Some(seed), Some(query)) = (maze_seed, share_query) {
+                                html! {
+                                    <div class="maze-seed">
+                                        { format!("Seed {} · ", seed) }
+                                        <a href={query} title="Opens this exact maze, endpoints and algorithm">{ "🔗 Share link" }</a>
+                                    </div>
+                                }
+                            } else {
+                                html! {}
+                            }}
+                        </div>
+
                         <div class="controls-section">
                             <div class="select-wrapper">
-                                <label class="control-label">{ "Algorithm" }</label>
+                                <label class="control-label" for="algorithm-select">{ "Algorithm" }</label>
                                 <select
+                                    id="algorithm-select"
                                     class="select-input"
                                     onchange={on_change_algo}
                                     disabled={is_computing || is_animating}
-                                    value={current_algorithm.clone()}
+                                    value={current_preset.clone()}
+                                >
+                                    { for ALGORITHM_PRESETS.iter().map(|&preset| html! {
+                                        <option value={preset} selected={preset == current_preset}>
+                                            { preset }
+                                        </option>
+                                    }) }
+                                </select>
+                            </div>
+
+                            <div class="select-wrapper" title="Shape of the area in which DOBs are detected; independent of how the rover moves">
+                                <label class="control-label" for="sensor-shape-select">{ "Sensor Shape" }</label>
+                                <select
+                                    id="sensor-shape-select"
+                                    class="select-input"
+                                    onchange={on_change_metric}
+                                    disabled={is_animating}
+                                >
+                                    { for DistanceMetric::ALL.iter().map(|&metric| html! {
+                                        <option value={metric.label()} selected={metric == sensor.metric}>
+                                            { metric.label() }
+                                        </option>
+                                    }) }
+                                </select>
+                            </div>
+
+                            <div class="select-wrapper" title="Whether the rover sees all round or only ahead; a forward cone follows its last move and misses DOBs behind it">
+                                <label class="control-label" for="field-of-view-select">{ "Field of View" }</label>
+                                <select
+                                    id="field-of-view-select"
+                                    class="select-input"
+                                    onchange={on_change_field_of_view}
+                                    disabled={is_animating}
+                                >
+                                    { for FieldOfView::ALL.iter().map(|&field_of_view| html! {
+                                        <option value={field_of_view.label()} selected={field_of_view == sensor.field_of_view}>
+                                            { field_of_view.label() }
+                                        </option>
+                                    }) }
+                                </select>
+                            </div>
+
+                            <div class="slider-wrapper" title="How many cells away DOBs are detected">
+                                <label class="control-label" for="sensor-range-slider">
+                                    { "Sensor Range" }
+                                    <span class="speed-value">{ sensor.range }</span>
+                                </label>
+                                <input
+                                    id="sensor-range-slider"
+                                    type="range"
+                                    aria-valuetext={format!("{} cells", sensor.range)}
+                                    class="range-input"
+                                    min={SENSOR_RANGES.start().to_string()}
+                                    max={SENSOR_RANGES.end().to_string()}
+                                    step="1"
+                                    value={sensor.range.to_string()}
+                                    oninput={on_change_sensor_range}
+                                    disabled={is_animating}
+                                />
+                            </div>
+
+                            <div class="select-wrapper" title="Which detected DOBs become static obstacles: all of them, or only those on or beside the planned path">
+                                <label class="control-label" for="conversion-select">{ "Detection" }</label>
+                                <select
+                                    id="conversion-select"
+                                    class="select-input"
+                                    onchange={on_change_policy}
+                                    disabled={is_animating}
                                 >
-                                    <option value="D*-Lite" selected={current_algorithm == "D*-Lite"}>{ "D*-Lite (Dynamic)" }</option>
-                                    <option value="A*" selected={current_algorithm == "A*"}>{ "A* (Classic)" }</option>
-                                    <option value="Field D*" selected={current_algorithm == "Field D*"}>{ "Field D* (Smooth)" }</option>
+                                    { for ConversionPolicy::ALL.iter().map(|&policy| html! {
+                                        <option value={policy.label()} selected={policy == props.conversion_policy}>
+                                            { policy.label() }
+                                        </option>
+                                    }) }
+                                </select>
+                            </div>
+
+                            <div class="select-wrapper">
+                                <label class="control-label" for="brush-select">{ "Brush" }</label>
+                                <select id="brush-select" class="select-input" onchange={on_change_brush}>
+                                    { for BRUSH_SIZES.iter().map(|&size| html! {
+                                        <option value={size.to_string()} selected={size == brush_size}>
+                                            { format!("{}×{}", size, size) }
+                                        </option>
+                                    }) }
+                                </select>
+                            </div>
+
+                            <div class="select-wrapper" title="How often the canvas redraws while the rover moves; it stays still when idle">
+                                <label class="control-label" for="frame-rate-select">{ "Frame Rate" }</label>
+                                <select id="frame-rate-select" class="select-input" onchange={on_change_frame_rate}>
+                                    { for FRAME_RATES.iter().map(|&fps| html! {
+                                        <option value={fps.to_string()} selected={fps == frame_rate}>
+                                            { format!("{} fps", fps) }
+                                        </option>
+                                    }) }
+                                </select>
+                            </div>
+
+                            <div class="select-wrapper" title="Grid colors; the deuteranopia-safe set avoids red/green pairs">
+                                <label class="control-label" for="palette-select">{ "Palette" }</label>
+                                <select id="palette-select" class="select-input" onchange={on_change_color_scheme}>
+                                    { for ColorScheme::ALL.iter().map(|&scheme| html! {
+                                        <option value={scheme.label()} selected={scheme == color_scheme}>
+                                            { scheme.label() }
+                                        </option>
+                                    }) }
                                 </select>
                             </div>
 
+                            <label class="toggle-wrapper" title="Plan with only the obstacles inside the detection radius, replanning every step">
+                                <input
+                                    type="checkbox"
+                                    checked={sensor_limited}
+                                    onchange={Callback::from(move |_| on_toggle_sensor_limited.emit(()))}
+                                    disabled={is_computing || is_animating}
+                                />
+                                <span class="control-label">{ "Sensor-Limited" }</span>
+                            </label>
+
+                            <label class="toggle-wrapper" title="Treat every cell the rover has traveled as blocked, like a lawn mower that never crosses its own trail">
+                                <input
+                                    type="checkbox"
+                                    checked={no_revisit}
+                                    onchange={Callback::from(move |_| on_toggle_no_revisit.emit(()))}
+                                    disabled={is_computing || is_animating}
+                                />
+                                <span class="control-label">{ "No Revisit" }</span>
+                            </label>
+
+                            <label class="toggle-wrapper" title="Amber DOBs take one random step every cycle, never onto walls, the rover, the start or a goal">
+                                <input
+                                    type="checkbox"
+                                    checked={props.wander}
+                                    onchange={Callback::from(move |_| on_toggle_wander.emit(()))}
+                                    disabled={is_animating}
+                                />
+                                <span class="control-label">{ "Wandering DOBs" }</span>
+                            </label>
+
+                            {if props.wander {
+                                html! {
+                                    <div class="coord-row" title="The same seed replays the same wandering each time the journey starts">
+                                        <label class="control-label" for="wander-seed-input">{ "Wander Seed" }</label>
+                                        <input
+                                            id="wander-seed-input"
+                                            type="number"
+                                            class="coord-input"
+                                            min="0"
+                                            value={props.wander_seed.to_string()}
+                                            oninput={on_input_wander_seed}
+                                            disabled={is_animating}
+                                        />
+                                    </div>
+                                }
+                            } else {
+                                html! {}
+                            }}
+
+                            <label class="toggle-wrapper" title="Cost-to-goal for D*-Lite and Field D*, search footprint for A*, Dijkstra and Greedy Best-First">
+                                <input
+                                    type="checkbox"
+                                    checked={show_cost_field}
+                                    onchange={Callback::from(move |_| on_toggle_cost_field.emit(()))}
+                                    disabled={!has_cost_field}
+                                />
+                                <span class="control-label">{ "Cost Field" }</span>
+                            </label>
+
+                            <label class="toggle-wrapper" title="While setting up, faintly draw the plan you would get if the hovered cell were blocked. Replans on every hover, so it can be slow on large grids">
+                                <input
+                                    type="checkbox"
+                                    checked={what_if}
+                                    onchange={Callback::from(move |_| on_toggle_what_if.emit(()))}
+                                />
+                                <span class="control-label">{ "What-If Preview" }</span>
+                            </label>
+
+                            <label class="toggle-wrapper" title={format!("Keep a PNG of the grid after every redraw, for docs. Memory-heavy: stops at {} frames; turning it on again starts over", MAX_FRAMES)}>
+                                <input
+                                    type="checkbox"
+                                    checked={props.capture_frames}
+                                    onchange={Callback::from(move |_| on_toggle_capture_frames.emit(()))}
+                                />
+                                <span class="control-label">{ "Capture Frames" }</span>
+                            </label>
+
+                            {if props.captured_frames > 0 {
+                                html! {
+                                    <button
+                                        class="btn btn-secondary"
+                                        onclick={Callback::from(move |_| on_download_frames.emit(()))}
+                                        title="Every captured frame as a numbered PNG, in one zip"
+                                    >
+                                        { format!(
+                                            "⬇️ Download {} frame{}{}",
+                                            props.captured_frames,
+                                            if props.captured_frames == 1 { "" } else { "s" },
+                                            if props.captured_frames >= MAX_FRAMES { " (full)" } else { "" }
+                                        ) }
+                                    </button>
+                                }
+                            } else {
+                                html! {}
+                            }}
+
+                            <div class="slider-wrapper" title="Cost of one diagonal step; straight steps cost 1 (every 8-direction planner but Theta*)">
+                                <label class="control-label" for="diagonal-cost-slider">
+                                    { "Diagonal Cost" }
+                                    <span class="speed-value">{ format!("{:.2}", diagonal_cost) }</span>
+                                </label>
+                                <input
+                                    id="diagonal-cost-slider"
+                                    type="range"
+                                    aria-valuetext={format!("{:.2} per diagonal step", diagonal_cost)}
+                                    class="range-input"
+                                    min="1"
+                                    max="2"
+                                    step="0.05"
+                                    value={diagonal_cost.to_string()}
+                                    oninput={on_change_diagonal_cost}
+                                    disabled={is_computing || is_animating || !has_diagonal_cost}
+                                />
+                                <div class="speed-markers">
+                                    <span>{ "Cheap" }</span>
+                                    <span>{ "Two steps" }</span>
+                                </div>
+                            </div>
+
+                            <div class="slider-wrapper" title="Extra cost per change of direction (A*, Dijkstra and D*-Lite)">
+                                <label class="control-label" for="turn-penalty-slider">
+                                    { "Turn Penalty" }
+                                    <span class="speed-value">{ format!("{:.1}", turn_penalty) }</span>
+                                </label>
+                                <input
+                                    id="turn-penalty-slider"
+                                    type="range"
+                                    aria-valuetext={format!("{:.1} per turn", turn_penalty)}
+                                    class="range-input"
+                                    min="0"
+                                    max="5"
+                                    step="0.5"
+                                    value={turn_penalty.to_string()}
+                                    oninput={on_change_turn_penalty}
+                                    disabled={is_computing || is_animating || !has_turn_penalty}
+                                />
+                                <div class="speed-markers">
+                                    <span>{ "Shortest" }</span>
+                                    <span>{ "Straightest" }</span>
+                                </div>
+                            </div>
+
+                            <div class="slider-wrapper" title="Uncertain cells (Ctrl+click → Occupied p) this likely to be occupied or more count as walls when planning">
+                                <label class="control-label" for="occupancy-threshold-slider">
+                                    { "Occupancy Threshold" }
+                                    <span class="speed-value">{ format!("{:.2}", occupancy_threshold) }</span>
+                                </label>
+                                <input
+                                    id="occupancy-threshold-slider"
+                                    type="range"
+                                    aria-valuetext={format!("blocked from p = {:.2}", occupancy_threshold)}
+                                    class="range-input"
+                                    min="0.05"
+                                    max="0.95"
+                                    step="0.05"
+                                    value={occupancy_threshold.to_string()}
+                                    oninput={on_change_occupancy_threshold}
+                                    disabled={is_computing || is_animating}
+                                />
+                                <div class="speed-markers">
+                                    <span>{ "Cautious" }</span>
+                                    <span>{ "Bold" }</span>
+                                </div>
+                            </div>
+
                             <div class="slider-wrapper">
-                                <label class="control-label">
+                                <label class="control-label" for="speed-slider">
                                     { "Speed" }
                                     <span class="speed-value">{ current_speed }</span>
                                 </label>
                                 <input
+                                    id="speed-slider"
                                     type="range"
+                                    aria-valuetext={format!("{} of 10", current_speed)}
                                     class="range-input"
                                     min="1"
                                     max="10"
@@ -177,6 +1256,41 @@ pub fn controls(props: &ControlsProps) -> Html {
                             </div>
                         </div>
 
+                        {if show_replay {
+                            html! {
+                                <div class="controls-section">
+                                    <div class="slider-wrapper">
+                                        <label class="control-label" for="replay-slider">
+                                            { "Replay" }
+                                            <span class="speed-value">
+                                                { format!("{}/{}", replay_position + 1, replay_len) }
+                                            </span>
+                                        </label>
+                                        <input
+                                            id="replay-slider"
+                                            type="range"
+                                            aria-valuetext={format!("Frame {} of {}", replay_position + 1, replay_len)}
+                                            class="range-input"
+                                            min="0"
+                                            max={(replay_len - 1).to_string()}
+                                            value={replay_position.to_string()}
+                                            oninput={on_change_replay}
+                                        />
+                                        <button
+                                            class={format!("btn btn-secondary replay-live-btn {}", if replay_index.is_none() { "disabled" } else { "" })}
+                                            onclick={Callback::from(move |_| on_replay_seek.emit(None))}
+                                            disabled={replay_index.is_none()}
+                                            aria-pressed={replay_index.is_none().to_string()}
+                                        >
+                                            { "Back to Live" }
+                                        </button>
+                                    </div>
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        }}
+
                         <div class="controls-section">
                             <div class="legend">
                                 <div class="legend-title">{ "Map Legend" }</div>
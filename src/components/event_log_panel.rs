@@ -0,0 +1,49 @@
+// src/components/event_log_panel.rs
+
+use yew::prelude::*;
+
+use crate::event_log::LoggedEvent;
+
+#[derive(Properties, PartialEq)]
+pub struct EventLogPanelProps {
+    pub events: Vec<LoggedEvent>,
+}
+
+#[function_component(EventLogPanel)]
+pub fn event_log_panel(props: &EventLogPanelProps) -> Html {
+    let is_open = use_state(|| false);
+
+    let toggle_open = {
+        let is_open = is_open.clone();
+        Callback::from(move |_| {
+            is_open.set(!*is_open);
+        })
+    };
+
+    let first_timestamp = props.events.first().map(|e| e.timestamp).unwrap_or(0.0);
+
+    html! {
+        <div class={format!("event-log-panel {}", if *is_open { "open" } else { "" })}>
+            <button class="event-log-header" onclick={toggle_open}>
+                <span>{ format!("📜 Event Log ({})", props.events.len()) }</span>
+                <span>{ if *is_open { "▼" } else { "▲" } }</span>
+            </button>
+            {if *is_open {
+                html! {
+                    <ul class="event-log-list">
+                        { for props.events.iter().rev().map(|logged| html! {
+                            <li class="event-log-entry">
+                                <span class="event-log-time">
+                                    { format!("+{:.1}s", (logged.timestamp - first_timestamp) / 1000.0) }
+                                </span>
+                                <span>{ logged.event.describe() }</span>
+                            </li>
+                        }) }
+                    </ul>
+                }
+            } else {
+                html! {}
+            }}
+        </div>
+    }
+}
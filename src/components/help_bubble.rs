@@ -4,8 +4,22 @@ use wasm_bindgen::JsCast;
 use web_sys::MouseEvent;
 use yew::prelude::*;
 
+/// One titled group of tips; `MainApp` builds these from the active settings.
+#[derive(Clone, PartialEq)]
+pub struct HelpSection {
+    pub title: &'static str,
+    pub tips: Vec<String>,
+}
+
+impl HelpSection {
+    pub fn new(title: &'static str, tips: Vec<String>) -> Self {
+        Self { title, tips }
+    }
+}
+
 #[derive(Properties, PartialEq)]
 pub struct HelpBubbleProps {
+    pub tips: Vec<HelpSection>,
     pub on_close: Callback<()>,
 }
 
@@ -109,32 +123,14 @@ pub fn help_bubble(props: &HelpBubbleProps) -> Html {
             {if *is_expanded {
                 html! {
                     <div class="help-content">
-                        <div class="help-section">
-                            <strong>{ "Controls:" }</strong>
-                            <ul>
-                                <li>{ "🖱️ Click & drag to place obstacles" }</li>
-                                <li>{ "🎯 Drag S/G to move start/goal" }</li>
-                                <li>{ "🚀 Find Path → Start Journey" }</li>
-                            </ul>
-                        </div>
-                        <div class="help-section">
-                            <strong>{ "Memoryless Rover:" }</strong>
-                            <ul>
-                                <li>{ "🤖 Recalculates path EVERY cell" }</li>
-                                <li>{ "🟡 Yellow obstacles detect rover" }</li>
-                                <li>{ "🟠 Detection range = 2 cells" }</li>
-                                <li>{ "⚡ Auto-converts when detected" }</li>
-                                <li>{ "🔄 Never pauses - continuous motion" }</li>
-                                <li>{ "⚠️ Shows alert if trapped" }</li>
-                            </ul>
-                        </div>
-                        <div class="help-section">
-                            <strong>{ "Path Colors:" }</strong>
-                            <ul>
-                                <li>{ "🟦 Turquoise = traveled path" }</li>
-                                <li>{ "🟪 Purple = future path" }</li>
-                            </ul>
-                        </div>
+                        { for props.tips.iter().map(|section| html! {
+                            <div class="help-section">
+                                <strong>{ format!("{}:", section.title) }</strong>
+                                <ul>
+                                    { for section.tips.iter().map(|tip| html! { <li>{ tip }</li> }) }
+                                </ul>
+                            </div>
+                        }) }
                     </div>
                 }
             } else {
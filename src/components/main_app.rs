@@ -1,542 +1,557 @@
 // src/components/main_app.rs
 
+use std::cell::RefCell;
 use std::collections::HashSet;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
 use web_sys::window;
 use yew::prelude::*;
 
-use crate::components::canvas::Canvas;
+use crate::components::canvas::{Canvas, CELEBRATION_MS, DEFAULT_FRAME_MS};
 use crate::components::controls::Controls;
-use crate::components::help_bubble::HelpBubble;
-use crate::pathfinding::Coord;
-use crate::rover::Rover;
+use crate::components::event_log_panel::EventLogPanel;
+use crate::components::help_bubble::{HelpBubble, HelpSection};
+use crate::event_log::{EventLog, JourneyEvent};
+use crate::frames::{decode_png_data_url, frame_name, zip_stored, MAX_FRAMES};
+use crate::layers::{path_length, DobLayer, PlanError, PlanOrigin, RoverLayer, SomLayer};
+use crate::maze::{fresh_seed, generate_maze};
+use crate::palette::ColorScheme;
+use crate::pathfinding::analysis::{isolation, reachable_fraction, unreachable_cells, Isolation};
+use crate::pathfinding::movement::{count_turns, DEFAULT_DIAGONAL_COST};
+use crate::pathfinding::{parse_algorithm, uses_terrain, Coord, ALGORITHMS};
+use crate::prefs;
+use crate::rover::{cells_per_second, now_ms, step_delay_ms, validate_terrain_costs, Rover};
+use crate::sensor::{ConversionPolicy, Direction, FieldOfView, Sensor};
+use crate::solver::{solve, GridKind, SolveRequest};
+use crate::url_params::Scenario;
+
+/// Delay before the initial solve, long enough for the spinner to paint.
+const COMPUTE_YIELD_MS: u32 = 20;
+
+/// Quiet time after the last map edit before the dead-end preview refreshes,
+/// so dragging a wall doesn't flood-fill on every cell.
+const DEAD_END_DEBOUNCE_MS: u32 = 150;
+
+/// Pointer rest time before the what-if preview replans, so sweeping across
+/// the grid doesn't run a search per cell.
+const WHAT_IF_DEBOUNCE_MS: u32 = 120;
+
+/// Reroutes in a row without a step before the rover counts as trapped, well
+/// short of the 1000-step safety stop.
+const REROUTE_LOOP_LIMIT: u32 = 12;
+
+/// A movement cycle slower than this stutters visibly even at low speeds.
+const SLOW_CYCLE_MS: f64 = 100.0;
 
 #[derive(Clone, PartialEq)]
 struct JourneyStats {
     start_time: Option<f64>,
     end_time: Option<f64>,
     total_distance: f64,
-    reroute_count: u32,
+    reroutes: Vec<(Coord, usize)>, // Where each reroute happened and how many DOBs set it off
     nodes_visited: u32,
     obstacles_detected: u32,
     path_efficiency: f64,
+    paused_at: Option<f64>,
+    paused_ms: f64,
+    max_planned_len: usize, // Longest planned path the rover committed to, in cells
+    cycles_without_move: u32, // Consecutive reroute cycles since the last step
 }
 
-#[derive(Clone, PartialEq)]
-struct SomLayer {
-    original_static_obstacles: HashSet<Coord>, 
-    converted_dob_obstacles: HashSet<Coord>,   
-}
-
-impl SomLayer {
+impl JourneyStats {
     fn new() -> Self {
         Self {
-            original_static_obstacles: HashSet::new(),
-            converted_dob_obstacles: HashSet::new(),
+            start_time: None,
+            end_time: None,
+            total_distance: 0.0,
+            reroutes: Vec::new(),
+            nodes_visited: 0,
+            obstacles_detected: 0,
+            path_efficiency: 100.0,
+            paused_at: None,
+            paused_ms: 0.0,
+            max_planned_len: 0,
+            cycles_without_move: 0,
         }
     }
 
-    fn get_complete_obstacle_map(&self) -> Vec<Coord> {
-        self.original_static_obstacles
-            .union(&self.converted_dob_obstacles)
-            .cloned()
-            .collect()
-    }
-
-    fn add_converted_dob(&mut self, coord: Coord) {
-        self.converted_dob_obstacles.insert(coord);
-        web_sys::console::log_1(
-            &format!(
-                "🗺️ SOM Layer 4: Added blocked coordinate {:?} for pathfinding",
-                coord
-            )
-            .into(),
-        );
+    /// Record the length of a plan the rover is about to follow.
+    fn note_planned_len(&mut self, len: usize) {
+        self.max_planned_len = self.max_planned_len.max(len);
     }
 
-    fn set_initial_obstacles(&mut self, obstacles: HashSet<Coord>) {
-        self.original_static_obstacles = obstacles;
-        web_sys::console::log_1(
-            &format!(
-                "🗺️ SOM Layer 4: Set {} initial obstacles",
-                self.original_static_obstacles.len()
-            )
-            .into(),
-        );
+    /// Count a reroute cycle that ended without a step; once there have been
+    /// `REROUTE_LOOP_LIMIT` in a row, the rover counts as trapped.
+    fn note_stalled_cycle(&mut self) -> Option<TrappedReason> {
+        self.cycles_without_move += 1;
+        (self.cycles_without_move >= REROUTE_LOOP_LIMIT)
+            .then_some(TrappedReason::RerouteLoop(self.cycles_without_move))
     }
 
-    fn is_cell_occupied(&self, coord: Coord) -> bool {
-        self.original_static_obstacles.contains(&coord)
-            || self.converted_dob_obstacles.contains(&coord)
+    /// Time spent actually travelling, excluding every pause so far.
+    fn elapsed_ms(&self, now: f64) -> f64 {
+        let Some(start) = self.start_time else {
+            return 0.0;
+        };
+        let end = self.end_time.or(self.paused_at).unwrap_or(now);
+        (end - start - self.paused_ms).max(0.0)
     }
 
-    fn clear_converted_dob_obstacles(&mut self) {
-        self.converted_dob_obstacles.clear();
+    /// Stop the clock and score the route: straight-line start→goal distance
+    /// over the distance actually travelled, as a percentage.
+    fn finish(&mut self, now: f64, start: Coord, goal: Coord) {
+        self.end_time.get_or_insert(now);
+        let dx = start.0.abs_diff(goal.0) as f64;
+        let dy = start.1.abs_diff(goal.1) as f64;
+        let straight_line = (dx * dx + dy * dy).sqrt();
+        self.path_efficiency = if self.total_distance > 0.0 {
+            (straight_line / self.total_distance * 100.0).clamp(0.0, 100.0)
+        } else {
+            100.0
+        };
     }
-}
-
-#[derive(Clone, PartialEq)]
-struct RoverLayer {
-    current_position: Coord,
-    goal_position: Coord,
-    start_position: Coord,
-    traveled_path: Vec<Coord>, 
-    planned_path: Vec<Coord>,  
-    algorithm: String,
-    is_journey_active: bool,
-}
 
-impl RoverLayer {
-    fn new(start: Coord, goal: Coord) -> Self {
-        Self {
-            current_position: start,
-            goal_position: goal,
-            start_position: start,
-            traveled_path: vec![start],
-            planned_path: Vec::new(),
-            algorithm: "A*".to_string(),
-            is_journey_active: false,
+    /// How much farther the rover went than `optimal_length`, as a percentage.
+    fn detour_percent(&self, optimal_length: f64) -> f64 {
+        if optimal_length > 0.0 {
+            ((self.total_distance / optimal_length - 1.0) * 100.0).max(0.0)
+        } else {
+            0.0
         }
     }
 
-    fn compute_path_from_som(&mut self, obstacle_map: Vec<Coord>) -> bool {
-        web_sys::console::log_1(&format!("🤖 Rover Layer 3: Computing COMPLETELY NEW planned path from {:?} to {:?} using {}", 
-            self.current_position, self.goal_position, self.algorithm).into());
-        web_sys::console::log_1(
-            &format!(
-                "🗺️ Using {} SOM obstacles (NO amber DOBs included)",
-                obstacle_map.len()
-            )
-            .into(),
-        );
-        web_sys::console::log_1(
-            &format!(
-                "📍 Traveled path UNCHANGED: {} steps | Planning NEW path",
-                self.traveled_path.len()
-            )
-            .into(),
-        );
-
-        self.planned_path.clear();
-        self.planned_path.shrink_to_fit();
-        web_sys::console::log_1(&"🔥 CLEARED planned path (traveled path untouched)".into());
-
-        if obstacle_map.contains(&self.goal_position) {
-            web_sys::console::log_1(&"❌ Goal is directly blocked by SOM obstacle".into());
-            return false;
-        }
-
-        if obstacle_map.is_empty() {
-            let simple_path =
-                Self::create_simple_direct_path(self.current_position, self.goal_position);
-            if !simple_path.is_empty() {
-                self.planned_path = simple_path;
-                web_sys::console::log_1(
-                    &format!(
-                        "NEW planned path (simple direct) - {} steps | Traveled: {} unchanged",
-                        self.planned_path.len(),
-                        self.traveled_path.len()
-                    )
-                    .into(),
-                );
-                return true;
-            }
-        }
-
-        let mut rover = Rover::new(50, 30);
-        rover.set_position(self.current_position);
-        rover.set_goal(self.goal_position);
-        rover.set_obstacles(obstacle_map.clone());
-        rover.set_algorithm(&self.algorithm);
-
-        let new_path = rover.compute_path_now();
-
-        if new_path.is_empty() {
-            let fallback_path =
-                Self::create_greedy_path(self.current_position, self.goal_position, &obstacle_map);
-            if !fallback_path.is_empty() {
-                self.planned_path = fallback_path;
-                web_sys::console::log_1(
-                    &format!(
-                        "NEW planned path (fallback) - {} steps | Traveled: {} unchanged",
-                        self.planned_path.len(),
-                        self.traveled_path.len()
-                    )
-                    .into(),
-                );
-                return true;
-            }
-
-            web_sys::console::log_1(&"Rover Layer 3: All pathfinding methods failed".into());
-            return false;
+    /// Estimated share of the journey done, 0.0–1.0: distance traveled over
+    /// traveled plus what the current plan still has to cover. A reroute
+    /// changes `remaining`, so the estimate can step back as well as forward.
+    fn progress(&self, remaining: f64) -> f64 {
+        let total = self.total_distance + remaining;
+        if total > 0.0 {
+            (self.total_distance / total).clamp(0.0, 1.0)
+        } else {
+            0.0
         }
+    }
 
-        if !new_path.is_empty() && new_path[0] != self.current_position {
-            web_sys::console::log_1(
-                &format!(
-                    "Rover Layer 3: Path validation failed - starts at {:?}, expected {:?}",
-                    new_path[0], self.current_position
+    /// One line per reroute, e.g. "(12, 7): 2 DOBs", for the stats tooltip.
+    fn reroute_summary(&self) -> String {
+        self.reroutes
+            .iter()
+            .map(|((x, y), dobs)| {
+                format!(
+                    "({}, {}): {} DOB{}",
+                    x,
+                    y,
+                    dobs,
+                    if *dobs == 1 { "" } else { "s" }
                 )
-                .into(),
-            );
-            return false;
-        }
-
-        self.planned_path = new_path;
-        web_sys::console::log_1(
-            &format!(
-                "NEW planned path COMPLETE - {} steps: {:?} -> {:?} | Traveled: {} unchanged",
-                self.planned_path.len(),
-                self.planned_path.first().unwrap_or(&(0, 0)),
-                self.planned_path.last().unwrap_or(&(0, 0)),
-                self.traveled_path.len()
-            )
-            .into(),
-        );
-        true
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 
-    fn create_simple_direct_path(start: Coord, goal: Coord) -> Vec<Coord> {
-        let mut path = vec![start];
-        let mut current = start;
-
-        while current != goal {
-            let (cx, cy) = current;
-            let (gx, gy) = goal;
-
-            let next_x = if cx < gx {
-                cx + 1
-            } else if cx > gx {
-                cx - 1
-            } else {
-                cx
-            };
-            let next_y = if cy < gy {
-                cy + 1
-            } else if cy > gy {
-                cy - 1
-            } else {
-                cy
-            };
-
-            current = (next_x, next_y);
-            path.push(current);
-
-            if path.len() > 1000 {
-                break;
-            }
+    fn pause(&mut self, now: f64) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(now);
         }
-
-        path
     }
 
-    fn create_greedy_path(start: Coord, goal: Coord, obstacles: &[Coord]) -> Vec<Coord> {
-        use std::collections::HashSet;
-
-        let obstacle_set: HashSet<Coord> = obstacles.iter().cloned().collect();
-        let mut path = vec![start];
-        let mut current = start;
+    fn resume(&mut self, now: f64) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_ms += now - paused_at;
+        }
+    }
+}
 
-        for _ in 0..1000 {
-            if current == goal {
-                break;
-            }
+// Explicit journey lifecycle so "paused" is never confused with "never started"
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum JourneyPhase {
+    NotStarted,
+    Running,
+    Paused,
+    Finished,
+}
 
-            let (cx, cy) = current;
-            let (gx, gy) = goal;
-
-            let mut best_next = current;
-            let mut best_distance = f64::INFINITY;
-
-            for (dx, dy) in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
-                let next_x = cx as i32 + dx;
-                let next_y = cy as i32 + dy;
-
-                if next_x >= 0 && next_x < 50 && next_y >= 0 && next_y < 30 {
-                    let next_coord = (next_x as usize, next_y as usize);
-                    if !obstacle_set.contains(&next_coord) {
-                        let distance = ((next_x as f64 - gx as f64).powi(2)
-                            + (next_y as f64 - gy as f64).powi(2))
-                        .sqrt();
-                        if distance < best_distance {
-                            best_distance = distance;
-                            best_next = next_coord;
-                        }
-                    }
-                }
-            }
+impl JourneyPhase {
+    /// Running or paused: map edits are DOBs, not changes to the static map.
+    fn is_mid_journey(self) -> bool {
+        matches!(self, JourneyPhase::Running | JourneyPhase::Paused)
+    }
+}
 
-            if best_next == current {
-                for (dx, dy) in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
-                    let next_x = cx as i32 + dx;
-                    let next_y = cy as i32 + dy;
+// One recorded cycle of a journey, rendered by the replay scrubber
+#[derive(Clone, PartialEq)]
+struct ReplayFrame {
+    position: Coord,
+    planned_path: Vec<Coord>,
+    converted_obstacles: HashSet<Coord>,
+    amber_dobs: Vec<Coord>,
+    traveled_len: usize,
+}
 
-                    if next_x >= 0 && next_x < 50 && next_y >= 0 && next_y < 30 {
-                        let next_coord = (next_x as usize, next_y as usize);
-                        if !obstacle_set.contains(&next_coord) {
-                            best_next = next_coord;
-                            break;
-                        }
-                    }
-                }
-            }
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum TrappedReason {
+    GoalUnreachable,
+    GoalBlocked,
+    BoxedIn(Coord),
+    SearchBudgetExceeded(usize),
+    RerouteLoop(u32),
+}
 
-            if best_next == current {
-                break;
+impl TrappedReason {
+    /// Before the journey any failure means the map has no route; mid-journey it means
+    /// the rover got boxed in, unless the goal cell itself is blocked.
+    fn from_error(error: PlanError, mid_journey_at: Option<Coord>) -> Self {
+        match (error, mid_journey_at) {
+            (PlanError::BudgetExceeded(expanded), _) => {
+                TrappedReason::SearchBudgetExceeded(expanded)
             }
-
-            current = best_next;
-            path.push(current);
-        }
-
-        if current == goal {
-            path
-        } else {
-            Vec::new()
+            (PlanError::GoalBlocked, _) => TrappedReason::GoalBlocked,
+            (_, None) => TrappedReason::GoalUnreachable,
+            (_, Some(pos)) => TrappedReason::BoxedIn(pos),
         }
     }
 
-    fn execute_movement_step(&mut self) -> bool {
-        if self.planned_path.len() < 2 {
-            web_sys::console::log_1(
-                &format!(
-                    "Cannot move - planned path too short: {}",
-                    self.planned_path.len()
-                )
-                .into(),
-            );
-            return false;
-        }
-
-        let current_step = self.planned_path[0];
-        if current_step != self.current_position {
-            web_sys::console::log_1(&format!("PATH DESYNC: Expected current position {:?}, but planned path starts at {:?}", 
-                self.current_position, current_step).into());
-            if self.planned_path.len() > 1 {
-                self.planned_path[0] = self.current_position;
-                web_sys::console::log_1(&"🔧 Fixed path desync".into());
-            } else {
-                web_sys::console::log_1(&"Cannot fix path desync - path too short".into());
-                return false;
+    fn message(&self) -> String {
+        match self {
+            TrappedReason::GoalUnreachable => "Goal is unreachable — no path exists.".to_string(),
+            TrappedReason::GoalBlocked => {
+                "Goal is blocked — clear its cell or move the goal.".to_string()
             }
+            TrappedReason::BoxedIn((x, y)) => format!("Rover boxed in at ({}, {}).", x, y),
+            TrappedReason::SearchBudgetExceeded(expanded) => format!(
+                "Search budget exceeded — gave up after {} expansions.",
+                expanded
+            ),
+            TrappedReason::RerouteLoop(cycles) => format!(
+                "Stuck rerouting — {} reroutes in a row without moving.",
+                cycles
+            ),
         }
-
-        let next_position = self.planned_path[1];
-
-        let dx = (self.current_position.0 as i32 - next_position.0 as i32).abs();
-        let dy = (self.current_position.1 as i32 - next_position.1 as i32).abs();
-        if dx > 1 || dy > 1 {
-            web_sys::console::log_1(
-                &format!(
-                    "INVALID STEP: From {:?} to {:?} - not adjacent (dx={}, dy={})",
-                    self.current_position, next_position, dx, dy
-                )
-                .into(),
-            );
-            return false;
-        }
-
-        let old_position = self.current_position;
-        self.current_position = next_position;
-
-        // CRITICAL SEPARATION:
-        // 1. Add new position to TRAVELED PATH (historical, immutable, only grows)
-        self.traveled_path.push(next_position);
-        web_sys::console::log_1(
-            &format!(
-                "📍 TRAVELED PATH: Added {:?} (total traveled: {})",
-                next_position,
-                self.traveled_path.len()
-            )
-            .into(),
-        );
-
-        // 2. Remove completed step from PLANNED PATH (current plan, shrinks as we move)
-        self.planned_path.remove(0);
-        web_sys::console::log_1(
-            &format!(
-                "🗺️ PLANNED PATH: Removed completed step (remaining planned: {})",
-                self.planned_path.len()
-            )
-            .into(),
-        );
-
-        web_sys::console::log_1(
-            &format!(
-                "MOVED: {:?} -> {:?} | Traveled: {} | Planned: {}",
-                old_position,
-                self.current_position,
-                self.traveled_path.len(),
-                self.planned_path.len()
-            )
-            .into(),
-        );
-
-        if self.planned_path.len() >= 2 {
-            web_sys::console::log_1(
-                &format!(
-                    "🗺️ Next planned steps: {:?} -> {:?}",
-                    self.planned_path[0], self.planned_path[1]
-                )
-                .into(),
-            );
-        } else if self.planned_path.len() == 1 {
-            web_sys::console::log_1(
-                &format!("🏁 Final step in planned path: {:?}", self.planned_path[0]).into(),
-            );
-        }
-
-        true
     }
+}
 
-    fn has_reached_goal(&self) -> bool {
-        self.current_position == self.goal_position
-    }
+/// Where the rover stood when it got trapped and what the map around it
+/// looks like, so the alert can say what to change.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct TrappedDiagnostics {
+    position: Coord,
+    isolation: Isolation,
+    own_trail: bool, // No-revisit is on and only the rover's trail is in the way
+}
 
-    fn set_algorithm(&mut self, algo: &str) {
-        self.algorithm = algo.to_string();
-        self.planned_path.clear(); 
+impl TrappedDiagnostics {
+    fn message(&self) -> String {
+        let (x, y) = self.position;
+        let neighbors = match self.isolation.free_neighbors {
+            1 => "1 free neighbor".to_string(),
+            n => format!("{} free neighbors", n),
+        };
+        let advice = match (self.isolation.separated, self.isolation.wall_hint) {
+            (true, Some((wx, wy))) => format!(
+                "Goal is in a separate region; remove a wall near ({}, {}).",
+                wx, wy
+            ),
+            (true, None) => "Goal is in a separate region.".to_string(),
+            (false, _) if self.own_trail => {
+                "Its own trail cuts it off from the goal; turn off No Revisit.".to_string()
+            }
+            (false, _) => "Goal is in the same region; try replanning.".to_string(),
+        };
+        format!("At ({}, {}) with {}. {}", x, y, neighbors, advice)
     }
+}
 
-    fn set_goal(&mut self, new_goal: Coord) {
-        self.goal_position = new_goal;
-        self.planned_path.clear(); 
-    }
+/// One obstacle brush stroke. Whether it adds or removes is decided on the
+/// first cell and never re-evaluated, so crossing a cell twice can't toggle it back.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Stroke {
+    adding: bool,
+    last_cell: Coord,
+}
 
-    fn reset_to_start(&mut self, start: Coord) {
-        self.start_position = start;
-        self.current_position = start;
-        self.traveled_path = vec![start]; 
-        self.planned_path.clear(); 
-        self.is_journey_active = false;
+impl Stroke {
+    /// Move the stroke onto `cell` and return its mode, or `None` if it is already there.
+    fn enter(&mut self, cell: Coord) -> Option<bool> {
+        let moved = cell != self.last_cell;
+        self.last_cell = cell;
+        moved.then_some(self.adding)
     }
 }
 
-// LAYER 1: DOB LAYER - OWNS: All DOB states and display classifications
-#[derive(Clone, PartialEq)]
-struct DobLayer {
-    amber_dobs: Vec<Coord>, // OWNED: Active dynamic obstacles (yellow display)
-    blue_converted_dobs: HashSet<Coord>, // OWNED: Converted obstacles (blue display)
+/// Cells of a `size` x `size` brush centered on `center`, clipped to the grid.
+fn brush_footprint(center: Coord, size: usize, width: usize, height: usize) -> Vec<Coord> {
+    let reach = size / 2;
+    let xs = center.0.saturating_sub(reach)..=(center.0 + reach).min(width.saturating_sub(1));
+    let ys = center.1.saturating_sub(reach)..=(center.1 + reach).min(height.saturating_sub(1));
+    xs.flat_map(|x| ys.clone().map(move |y| (x, y))).collect()
 }
 
-impl DobLayer {
-    fn new() -> Self {
-        Self {
-            amber_dobs: Vec::new(),
-            blue_converted_dobs: HashSet::new(),
-        }
+/// Journey progress bar for the stats bar.
+fn progress_stat(progress: f64) -> Html {
+    let percent = (progress * 100.0).floor();
+    html! {
+        <span
+            class="stat-item stat-progress"
+            title="Distance traveled ÷ (traveled + rest of the current plan); with several goals, the plan only runs to the next one"
+        >
+            <progress max="100" value={percent.to_string()} aria-label="Journey progress" />
+            { format!("{:.0}%", percent) }
+        </span>
     }
+}
 
-    fn check_proximity_and_convert(&mut self, rover_position: Coord) -> Vec<Coord> {
-        let mut converted_coords = Vec::new();
-        let mut remaining_amber = Vec::new();
-
-        for &dob_coord in &self.amber_dobs {
-            let dx = (rover_position.0 as i32 - dob_coord.0 as i32).abs();
-            let dy = (rover_position.1 as i32 - dob_coord.1 as i32).abs();
-            let distance = dx.max(dy); // Chebyshev distance
-
-            if distance <= 2 {
-                self.blue_converted_dobs.insert(dob_coord);
-                converted_coords.push(dob_coord);
-                web_sys::console::log_1(
-                    &format!("🟡→🔵 DOB Layer 1: Converted DOB {:?}", dob_coord).into(),
-                );
-            } else {
-                remaining_amber.push(dob_coord);
-            }
+/// Help tips for the features currently switched on, so the guide never
+/// describes a mode the user has turned off.
+fn help_sections(rover: &RoverLayer, dobs: &DobLayer, brush_size: usize) -> Vec<HelpSection> {
+    let sensor = dobs.sensor;
+    let tips = |tips: &[&str]| tips.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+    let mut controls = tips(&[
+        "🖱️ Click & drag to place obstacles",
+        "🧽 Right-drag (or Alt) to erase",
+    ]);
+    if brush_size > 1 {
+        controls.push(format!("🖌️ Brush paints {0}×{0} blocks", brush_size));
+    }
+    controls.extend(tips(&[
+        "🎯 Drag S/G to move start/goal",
+        "➕ Shift+click adds extra goals (nearest first)",
+        "⚖️ Ctrl+click a cell to type its terrain cost or occupancy",
+        "📏 Click right by a cell border to wall off just that edge",
+        "🚀 Find Path → Start Journey",
+        "⏮️ Paused? Step Back rewinds one cell and replans",
+        "🗺️ Scroll to zoom; click the mini-map to jump around",
+    ]));
+    let mut sections = vec![HelpSection::new("Controls", controls)];
+
+    let mut rover_tips = tips(&[
+        "🤖 Recalculates path EVERY cell",
+        "🟡 Yellow obstacles detect rover",
+    ]);
+    rover_tips.push(format!(
+        "🟠 Detection range = {} cells ({})",
+        sensor.range,
+        sensor.metric.label()
+    ));
+    if sensor.field_of_view == FieldOfView::ForwardCone {
+        rover_tips.push("🔦 Sees only ahead: DOBs behind the rover go unnoticed".to_string());
+    }
+    rover_tips.push(
+        match dobs.policy {
+            ConversionPolicy::PermanentOnDetect => "⚡ Auto-converts when detected",
+            ConversionPolicy::AdjacentBlockOnly => "⚡ Converts only when beside the planned path",
         }
-
-        self.amber_dobs = remaining_amber;
-        converted_coords
+        .to_string(),
+    );
+    if dobs.wander {
+        rover_tips.push(format!(
+            "🎲 DOBs wander a cell each cycle (seed {})",
+            dobs.wander_seed
+        ));
+    }
+    rover_tips.extend(tips(&[
+        "🔄 Never pauses - continuous motion",
+        "⚠️ Shows alert if trapped",
+    ]));
+    sections.push(HelpSection::new("Memoryless Rover", rover_tips));
+
+    if rover.sensor_range.is_some() {
+        sections.push(HelpSection::new(
+            "Sensor-Limited Planning",
+            tips(&[
+                "📡 Only sees obstacles within detection range",
+                "🔁 Replans each step as new walls come into view",
+                "↩️ May head into dead ends and backtrack",
+            ]),
+        ));
     }
 
-    fn toggle_dob(&mut self, coord: Coord, som_layer: &SomLayer) -> bool {
-        if som_layer.is_cell_occupied(coord) {
-            return false; 
-        }
+    if rover.algorithm == "Greedy Best-First" {
+        sections.push(HelpSection::new(
+            "Greedy Best-First",
+            tips(&[
+                "🏹 Ranks cells by distance to the goal only, ignoring the way so far",
+                "🔎 Expands far fewer cells than Dijkstra or A*",
+                "📐 The path can be longer than the shortest",
+            ]),
+        ));
+    }
 
-        if let Some(pos) = self.amber_dobs.iter().position(|&c| c == coord) {
-            self.amber_dobs.remove(pos);
-            web_sys::console::log_1(
-                &format!("🟡 DOB Layer 1: Removed amber DOB {:?}", coord).into(),
-            );
-        } else {
-            self.amber_dobs.push(coord);
-            web_sys::console::log_1(&format!("🟡 DOB Layer 1: Added amber DOB {:?}", coord).into());
-        }
-        true
+    if rover.no_revisit {
+        sections.push(HelpSection::new(
+            "No Revisit",
+            tips(&[
+                "🌱 Traveled cells count as walls, like a mower's cut grass",
+                "⚠️ Trapped if the trail leaves no way to the goal",
+            ]),
+        ));
     }
 
-    fn add_dob(&mut self, coord: Coord, som_layer: &SomLayer) -> bool {
-        if som_layer.is_cell_occupied(coord) || self.amber_dobs.contains(&coord) {
-            return false;
+    let mut movement = Vec::new();
+    if rover.algorithm == "Theta*" {
+        movement.push("📐 Theta* cuts any-angle shortcuts between visible cells".to_string());
+    } else if rover.allow_diagonals {
+        movement.push("↗️ Diagonal steps allowed, never through a blocked corner".to_string());
+        if rover.diagonal_cost != DEFAULT_DIAGONAL_COST {
+            movement.push(format!(
+                "📏 Each diagonal step costs {:.2} instead of √2",
+                rover.diagonal_cost
+            ));
         }
-        self.amber_dobs.push(coord);
-        true
     }
-
-    fn get_amber_dobs_for_display(&self) -> Vec<Coord> {
-        self.amber_dobs.clone()
+    if rover.turn_penalty > 0.0 && matches!(rover.algorithm.as_str(), "A*" | "Dijkstra" | "D*-Lite")
+    {
+        movement.push(format!(
+            "↪️ Each turn costs {:.1} extra steps, so straighter routes win",
+            rover.turn_penalty
+        ));
+    }
+    if !rover.terrain_costs.is_empty() && !uses_terrain(&rover.algorithm) {
+        movement.push(format!(
+            "⚖️ {} ignores terrain costs; A*, Dijkstra and Greedy Best-First plan with them",
+            rover.algorithm
+        ));
     }
+    if !movement.is_empty() {
+        sections.push(HelpSection::new("Movement", movement));
+    }
+
+    sections.push(HelpSection::new(
+        "Path Colors",
+        tips(&[
+            "🟦 Turquoise = traveled path; the shade changes at each reroute",
+            "🟪 Purple = future path",
+            "🩷 Pink arrow = next step",
+            "🔶 Orange diamond = reroute (DOBs that caused it)",
+            "🎨 Palette swaps these for a deuteranopia-safe or high-contrast set",
+        ]),
+    ));
+    sections
+}
 
-    fn get_blue_dobs_for_display(&self) -> HashSet<Coord> {
-        self.blue_converted_dobs.clone()
+/// Hand `bytes` to the browser as a download named `file_name`.
+fn download_bytes(file_name: &str, mime_type: &str, bytes: &[u8]) {
+    let url =
+        gloo::file::ObjectUrl::from(gloo::file::Blob::new_with_options(bytes, Some(mime_type)));
+    let Some(document) = window().and_then(|w| w.document()) else {
+        return;
+    };
+    let Ok(link) = document.create_element("a") else {
+        return;
+    };
+    let _ = link.set_attribute("href", &url);
+    let _ = link.set_attribute("download", file_name);
+    if let Ok(link) = link.dyn_into::<web_sys::HtmlElement>() {
+        link.click();
     }
+    // Revoking the URL right after the click can cancel the download in some browsers
+    gloo_timers::callback::Timeout::new(60_000, move || drop(url)).forget();
+}
 
-    fn clear_all(&mut self) {
-        self.amber_dobs.clear();
-        self.blue_converted_dobs.clear();
+fn record_events(event_log: &UseStateHandle<EventLog>, events: Vec<JourneyEvent>) {
+    let mut log = (**event_log).clone();
+    for event in events {
+        log.record(event);
     }
+    event_log.set(log);
 }
 
+#[allow(clippy::too_many_arguments)]
 fn execute_one_cycle(
     som_layer: &UseStateHandle<SomLayer>,
     rover_layer: &UseStateHandle<RoverLayer>,
     dob_layer: &UseStateHandle<DobLayer>,
     journey_stats: &UseStateHandle<JourneyStats>,
-    trapped_alert: &UseStateHandle<bool>,
+    trapped_alert: &UseStateHandle<Option<TrappedReason>>,
     is_animating: &UseStateHandle<bool>,
+    journey_phase: &UseStateHandle<JourneyPhase>,
+    event_log: &UseStateHandle<EventLog>,
+    planner: &Rc<RefCell<Rover>>,
 ) {
     // Clone the actual values from UseStateHandle
     let mut current_rover: RoverLayer = (**rover_layer).clone();
 
-    web_sys::console::log_1(
-        &format!(
-            "STEP 1: Checking if {:?} == {:?}",
-            current_rover.current_position, current_rover.goal_position
-        )
-        .into(),
+    debug_log!(
+        "STEP 1: Checking if {:?} == {:?}",
+        current_rover.current_position,
+        current_rover.goal_position
     );
 
-    if current_rover.current_position == current_rover.goal_position {
-        web_sys::console::log_1(&"STEP 1: TRUE - Goal reached! STOPPING LOOP".into());
+    if current_rover.has_reached_goal() {
+        debug_log!("STEP 1: TRUE - Goal reached! STOPPING LOOP");
 
         let mut stats: JourneyStats = (**journey_stats).clone();
-        stats.end_time = Some(js_sys::Date::now());
+        stats.finish(
+            js_sys::Date::now(),
+            current_rover.journey_start(),
+            current_rover.current_position,
+        );
         journey_stats.set(stats);
+        record_events(
+            event_log,
+            vec![JourneyEvent::GoalReached {
+                at: current_rover.current_position,
+            }],
+        );
+        journey_phase.set(JourneyPhase::Finished);
         is_animating.set(false);
-        return; 
+        return;
     }
 
-    web_sys::console::log_1(&"STEP 1: FALSE - Continue to step 2".into());
+    debug_log!("STEP 1: FALSE - Continue to step 2");
 
-    web_sys::console::log_1(&"STEP 2: DOB Layer checking proximity and converting".into());
+    debug_log!("STEP 2: DOB Layer checking proximity and converting");
 
     let mut current_dob: DobLayer = (**dob_layer).clone();
     let mut current_som: SomLayer = (**som_layer).clone();
 
-    let newly_converted_coords =
-        current_dob.check_proximity_and_convert(current_rover.current_position);
+    // Wandering DOBs move before the sensor looks, so the rover reacts to where they are now
+    if current_dob.wander {
+        let grid_size = {
+            let planner = planner.borrow();
+            (planner.width, planner.height)
+        };
+        if current_dob.wander_step(&current_som, &current_rover, grid_size) {
+            dob_layer.set(current_dob.clone());
+        }
+    }
+
+    let newly_converted_coords = current_dob.check_proximity_and_convert(
+        current_rover.current_position,
+        current_rover.heading,
+        &current_rover.planned_path,
+    );
 
     let obstacles_detected = !newly_converted_coords.is_empty();
 
+    // The same look updates the belief in every uncertain cell in view: a
+    // DOB there, converted or not, reads occupied
+    let beliefs_flipped = if current_som.occupancy.is_empty() {
+        false
+    } else {
+        let (sensor, position, heading) = (
+            current_dob.sensor,
+            current_rover.current_position,
+            current_rover.heading,
+        );
+        let dobs: HashSet<Coord> = current_dob
+            .amber_dobs
+            .iter()
+            .copied()
+            .chain(current_dob.get_blue_dobs_for_display())
+            .collect();
+        let flipped = current_som.observe_occupancy(
+            |c| sensor.detects_facing(position, heading, c),
+            |c| dobs.contains(&c),
+        );
+        som_layer.set(current_som.clone());
+        flipped
+    };
+
     if obstacles_detected {
-        web_sys::console::log_1(
-            &format!(
-                "🚨 OBSTACLES DETECTED: {} DOBs converted - STOPPING MOVEMENT TO RECOMPUTE",
-                newly_converted_coords.len()
-            )
-            .into(),
+        debug_log!(
+            "🚨 OBSTACLES DETECTED: {} DOBs converted - STOPPING MOVEMENT TO RECOMPUTE",
+            newly_converted_coords.len()
         );
 
         for &coord in &newly_converted_coords {
@@ -544,150 +559,567 @@ fn execute_one_cycle(
         }
 
         let obstacle_map = current_som.get_complete_obstacle_map();
-        web_sys::console::log_1(
-            &format!(
-                "STEP 4: Retrieved {} total blocked coordinates from SOM",
-                obstacle_map.len()
-            )
-            .into(),
+        debug_log!(
+            "STEP 4: Retrieved {} total blocked coordinates from SOM",
+            obstacle_map.len()
         );
 
-        web_sys::console::log_1(
-            &format!(
-                "STEP 5: FORCED PATH RECOMPUTATION from {:?} to {:?}",
-                current_rover.current_position, current_rover.goal_position
-            )
-            .into(),
+        debug_log!(
+            "STEP 5: FORCED PATH RECOMPUTATION from {:?} to {:?}",
+            current_rover.current_position,
+            current_rover.goal_position
         );
 
-        let path_computed = current_rover.compute_path_from_som(obstacle_map);
+        // A plan of fewer than two cells has no step to take
+        let outcome = current_rover
+            .compute_path_from_som(obstacle_map, &mut planner.borrow_mut())
+            .and_then(|origin| {
+                if current_rover.planned_path.len() < 2 {
+                    Err(PlanError::NoPath)
+                } else {
+                    Ok(origin)
+                }
+            });
 
-        if !path_computed || current_rover.planned_path.len() < 2 {
-            web_sys::console::log_1(&"STEP 5 FAILED: No valid path - rover trapped".into());
-            trapped_alert.set(true);
-            is_animating.set(false);
-            return; 
-        }
+        let mut events: Vec<JourneyEvent> = newly_converted_coords
+            .iter()
+            .map(|&coord| JourneyEvent::ObstacleDetected { coord })
+            .collect();
+
+        let origin = match outcome {
+            Ok(origin) => origin,
+            Err(error) => {
+                debug_log!("STEP 5 FAILED: {} - rover trapped", error);
+                events.push(JourneyEvent::Trapped {
+                    at: current_rover.current_position,
+                });
+                record_events(event_log, events);
+                trapped_alert.set(Some(TrappedReason::from_error(
+                    error,
+                    Some(current_rover.current_position),
+                )));
+                is_animating.set(false);
+                return;
+            }
+        };
 
-        web_sys::console::log_1(
-            &format!(
-                "STEP 5 SUCCESS: NEW path computed - {} steps, next: {:?}",
-                current_rover.planned_path.len(),
-                current_rover.planned_path.get(1).unwrap_or(&(0, 0))
-            )
-            .into(),
+        debug_log!(
+            "STEP 5 SUCCESS: NEW path computed - {} steps, next: {:?}",
+            current_rover.planned_path.len(),
+            current_rover.planned_path.get(1).unwrap_or(&(0, 0))
         );
 
-        dob_layer.set(current_dob);
-        som_layer.set(current_som);
-        rover_layer.set(current_rover.clone());
+        events.push(JourneyEvent::Rerouted {
+            at: current_rover.current_position,
+            planned_len: current_rover.planned_path.len(),
+            nodes_expanded: current_rover.nodes_expanded(),
+        });
+        if origin == PlanOrigin::GreedyFallback {
+            events.push(JourneyEvent::FallbackPlan {
+                at: current_rover.current_position,
+                method: origin.label(),
+            });
+        }
+
+        current_rover.note_reroute();
 
         let mut stats: JourneyStats = (**journey_stats).clone();
         stats.obstacles_detected += newly_converted_coords.len() as u32;
-        stats.reroute_count += 1;
+        stats.note_planned_len(current_rover.planned_path.len());
+        stats
+            .reroutes
+            .push((current_rover.current_position, newly_converted_coords.len()));
+        let reroute_loop = stats.note_stalled_cycle();
         journey_stats.set(stats);
 
-        web_sys::console::log_1(
-            &format!(
-                "CYCLE COMPLETE: Path recomputed for rover at {:?}, NO movement this cycle",
-                current_rover.current_position
-            )
-            .into(),
+        dob_layer.set(current_dob);
+        som_layer.set(current_som);
+        rover_layer.set(current_rover.clone());
+
+        // Each new plan walking straight into another detection is a loop, not progress
+        if let Some(reason) = reroute_loop {
+            debug_log!("STEP 5 LOOP: {:?} - rover trapped", reason);
+            events.push(JourneyEvent::Trapped {
+                at: current_rover.current_position,
+            });
+            record_events(event_log, events);
+            trapped_alert.set(Some(reason));
+            is_animating.set(false);
+            return;
+        }
+        record_events(event_log, events);
+
+        debug_log!(
+            "CYCLE COMPLETE: Path recomputed for rover at {:?}, NO movement this cycle",
+            current_rover.current_position
         );
         return;
     }
 
-    web_sys::console::log_1(&"🚶 STEP 6: No obstacles detected - proceeding with movement".into());
+    debug_log!("🚶 STEP 6: No obstacles detected - proceeding with movement");
+
+    // Sensor-limited planning learns about the map as it moves, so replan every
+    // step; so does a belief crossing the occupancy threshold
+    if current_rover.sensor_range.is_some() || beliefs_flipped {
+        let outcome = current_rover.compute_path_from_som(
+            current_som.get_complete_obstacle_map(),
+            &mut planner.borrow_mut(),
+        );
+        if let Err(error) = outcome {
+            debug_log!("STEP 6 FAILED: {} - rover trapped", error);
+            record_events(
+                event_log,
+                vec![JourneyEvent::Trapped {
+                    at: current_rover.current_position,
+                }],
+            );
+            trapped_alert.set(Some(TrappedReason::from_error(
+                error,
+                Some(current_rover.current_position),
+            )));
+            rover_layer.set(current_rover);
+            is_animating.set(false);
+            return;
+        }
+    }
 
     if current_rover.planned_path.len() < 2 {
-        web_sys::console::log_1(
-            &format!(
-                "STEP 6 ABORT: Path too short for movement: {}",
-                current_rover.planned_path.len()
-            )
-            .into(),
+        debug_log!(
+            "STEP 6 ABORT: Path too short for movement: {}",
+            current_rover.planned_path.len()
+        );
+        record_events(
+            event_log,
+            vec![JourneyEvent::Trapped {
+                at: current_rover.current_position,
+            }],
         );
-        trapped_alert.set(true);
+        trapped_alert.set(Some(TrappedReason::BoxedIn(current_rover.current_position)));
         is_animating.set(false);
         return;
     }
 
     let next_step = current_rover.planned_path[1];
-    web_sys::console::log_1(&format!("🚶 STEP 6: Taking step to {:?}", next_step).into());
+    debug_log!("🚶 STEP 6: Taking step to {:?}", next_step);
 
     let old_position = current_rover.current_position;
-    let movement_success = current_rover.execute_movement_step();
-
-    if !movement_success || current_rover.current_position == old_position {
-        web_sys::console::log_1(&"STEP 6 FAILED: Movement unsuccessful".into());
-        trapped_alert.set(true);
+    // The plan this step follows, whether replanned above or set since the last cycle
+    let planned_len = current_rover.planned_path.len();
+    let step = current_rover.execute_movement_step(&current_som);
+
+    if step.is_err() || current_rover.current_position == old_position {
+        debug_log!("STEP 6 FAILED: Movement unsuccessful ({:?})", step);
+        record_events(
+            event_log,
+            vec![JourneyEvent::Trapped {
+                at: current_rover.current_position,
+            }],
+        );
+        trapped_alert.set(Some(TrappedReason::BoxedIn(current_rover.current_position)));
         is_animating.set(false);
-        return; 
+        return;
     }
 
-    web_sys::console::log_1(
-        &format!(
-            "🚶 STEP 6 COMPLETE: Moved to {:?}, remaining path: {}",
-            current_rover.current_position,
-            current_rover.planned_path.len()
-        )
-        .into(),
+    debug_log!(
+        "🚶 STEP 6 COMPLETE: Moved to {:?}, remaining path: {}",
+        current_rover.current_position,
+        current_rover.planned_path.len()
     );
 
-    web_sys::console::log_1(&"⏹️ STEP 7: Updating layers with movement data".into());
-
+    debug_log!("⏹️ STEP 7: Updating layers with movement data");
+
+    let mut events = vec![JourneyEvent::Moved {
+        from: old_position,
+        to: current_rover.current_position,
+    }];
+    if current_rover.is_at_waypoint_goal() {
+        events.extend(current_rover.advance_to_next_goal(
+            current_som.get_complete_obstacle_map(),
+            &mut planner.borrow_mut(),
+        ));
+    }
+    record_events(event_log, events);
     rover_layer.set(current_rover.clone());
 
     let mut stats: JourneyStats = (**journey_stats).clone();
     stats.nodes_visited += 1;
-    stats.total_distance += 1.0;
+    stats.cycles_without_move = 0;
+    stats.note_planned_len(planned_len);
+    let (dx, dy) = (
+        old_position.0.abs_diff(current_rover.current_position.0) as f64,
+        old_position.1.abs_diff(current_rover.current_position.1) as f64,
+    );
+    stats.total_distance += (dx * dx + dy * dy).sqrt();
     journey_stats.set(stats);
 
-    web_sys::console::log_1(
-        &format!(
-            "STEP 7 COMPLETE: Movement cycle complete - rover at {:?}",
-            current_rover.current_position
-        )
-        .into(),
+    debug_log!(
+        "STEP 7 COMPLETE: Movement cycle complete - rover at {:?}",
+        current_rover.current_position
     );
 }
 
-#[function_component(MainApp)]
-pub fn main_app() -> Html {
-    web_sys::console::log_1(
-        &"🏗️ Scout Pathfinder: COMPLETE with PROPER LAYER DATA OWNERSHIP".into(),
-    );
-
-    let grid_width = 50usize;
-    let grid_height = 30usize;
+#[derive(Properties, PartialEq, Default)]
+pub struct MainAppProps {
+    /// Fired once per journey, with the goal cell, when the rover arrives.
+    #[prop_or_default]
+    pub on_goal_reached: Option<Callback<Coord>>,
+}
 
-    let som_layer = use_state(|| SomLayer::new()); 
-    let rover_layer = use_state(|| RoverLayer::new((5, 5), (45, 25)));
-    let dob_layer = use_state(|| DobLayer::new()); 
+#[function_component(MainApp)]
+pub fn main_app(props: &MainAppProps) -> Html {
+    debug_log!("🏗️ Scout Pathfinder: COMPLETE with PROPER LAYER DATA OWNERSHIP");
+
+    // Read once on mount; Reset returns to this scenario's endpoints
+    let scenario = use_memo((), |_| Scenario::from_location());
+    let grid_width = scenario.width;
+    let grid_height = scenario.height;
+
+    let som_layer = use_state(|| {
+        let mut layer = SomLayer::new();
+        layer.set_initial_obstacles(scenario.obstacles.iter().copied().collect());
+        if let Some(seed) = scenario.maze_seed {
+            let maze = generate_maze(
+                grid_width,
+                grid_height,
+                seed,
+                &[scenario.start, scenario.goal],
+            );
+            layer.set_initial_obstacles(maze.walls().collect());
+        }
+        layer
+    });
+    // Cell whose terrain cost the panel is editing (Ctrl+click)
+    let selected_cell = use_state(|| None::<Coord>);
+    // Seed of the last generated maze; only shared while the map still matches it
+    let maze_seed = use_state(|| scenario.maze_seed);
+    let rover_layer = use_state(|| {
+        let mut layer = RoverLayer::new(scenario.start, scenario.goal);
+        // A link's algorithm wins over the saved preference
+        if let Some(algorithm) = scenario.algorithm.map(str::to_string).or_else(|| {
+            prefs::load::<String>(prefs::ALGORITHM).filter(|a| ALGORITHMS.contains(&a.as_str()))
+        }) {
+            layer.algorithm = algorithm;
+        }
+        layer.allow_diagonals = prefs::load(prefs::ALLOW_DIAGONALS).unwrap_or(false);
+        layer
+    });
+    let planner = use_mut_ref(|| Rover::new(grid_width, grid_height));
+    // Kept outside the RefCell so Cancel never has to borrow the planner
+    let cancel_flag = {
+        let planner = planner.clone();
+        use_memo((), move |_| planner.borrow().cancel_flag())
+    };
+    let dob_layer = use_state(DobLayer::new);
 
-    
     let is_computing = use_state(|| false);
     let is_animating = use_state(|| false);
     let path_computed = use_state(|| false);
     let is_panel_minimized = use_state(|| false);
     let show_help = use_state(|| true);
-    let is_dark = use_state(|| false);
-    let trapped_alert = use_state(|| false);
-    let current_speed = use_state(|| 5u32);
-
-    let visual_start = use_state(|| (5, 5)); 
-
-    let journey_stats = use_state(|| JourneyStats {
-        start_time: None,
-        end_time: None,
-        total_distance: 0.0,
-        reroute_count: 0,
-        nodes_visited: 0,
-        obstacles_detected: 0,
-        path_efficiency: 100.0,
+    let is_dark = use_state(|| prefs::load(prefs::DARK_MODE).unwrap_or(false));
+    let trapped_alert = use_state(|| None::<TrappedReason>);
+    let trapped_diagnostics = use_state(|| None::<TrappedDiagnostics>);
+    let compute_cancelled = use_state(|| false);
+    let current_speed = use_state(|| {
+        prefs::load::<u32>(prefs::SPEED)
+            .map(|speed| speed.clamp(1, 10))
+            .unwrap_or(5)
+    });
+    // Canvas redraws per second while the rover moves
+    let frame_rate = use_state(|| {
+        prefs::load::<u32>(prefs::FRAME_RATE)
+            .map(|fps| fps.clamp(1, 60))
+            .unwrap_or(1000 / DEFAULT_FRAME_MS)
     });
+    let color_scheme = use_state(|| {
+        prefs::load::<String>(prefs::PALETTE)
+            .and_then(|label| ColorScheme::from_label(&label))
+            .unwrap_or_default()
+    });
+
+    let visual_start = use_state(|| scenario.start);
+
+    let journey_stats = use_state(JourneyStats::new);
+    let journey_phase = use_state(|| JourneyPhase::NotStarted);
+    let event_log = use_state(EventLog::default);
+    // Latest over-threshold cycle (where, how long); logged once it beats the slowest
+    let slow_cycle = use_state(|| None::<(Coord, f64)>);
+    // The slow-cycle warning shows once per session, however many journeys run
+    let slow_cycle_warned = use_mut_ref(|| false);
+    let slow_cycle_warning = use_state(|| None::<f64>);
+    let replay_frames = use_state(Vec::<ReplayFrame>::new);
+    let replay_index = use_state(|| None::<usize>);
+    let show_cost_field = use_state(|| false);
+    // PNG bytes of each redraw while capturing; too big to clone on every render
+    let capture_frames = use_state(|| false);
+    let captured_frames = use_mut_ref(Vec::<Vec<u8>>::new);
+    let captured_count = use_state(|| 0usize);
+    let what_if = use_state(|| prefs::load(prefs::WHAT_IF).unwrap_or(false));
+    let hovered_cell = use_state(|| None::<Coord>);
+    let what_if_path = use_state(Vec::<Coord>::new);
+    let goal_celebration = use_state(|| None::<f64>);
+    let dead_end_cells = use_state(HashSet::<Coord>::new);
+    let reachable_share = use_state(|| 1.0);
+    let plan_invalidated = use_state(|| false);
+    let optimal_route = use_state(|| None::<Vec<Coord>>);
+
+    // Preview which free cells are walled off from the goal, and how much of the
+    // free space the start can reach, once edits settle
+    {
+        let dead_end_cells = dead_end_cells.clone();
+        let reachable_share = reachable_share.clone();
+        let som_layer = som_layer.clone();
+        let (start, goal) = (rover_layer.start_position, rover_layer.goal_position);
+        // Thin walls live on the rover layer, so toggling one refreshes too
+        let thin_walls = rover_layer.thin_walls.clone();
+        let deps = ((*som_layer).clone(), start, goal, thin_walls.clone());
+        use_effect_with(deps, move |_| {
+            let refresh = gloo_timers::callback::Timeout::new(DEAD_END_DEBOUNCE_MS, move || {
+                let mut grid = vec![vec![false; grid_height]; grid_width];
+                for (x, y) in som_layer.get_complete_obstacle_map() {
+                    if x < grid_width && y < grid_height {
+                        grid[x][y] = true;
+                    }
+                }
+                dead_end_cells.set(unreachable_cells(&grid, &thin_walls, goal));
+                reachable_share.set(reachable_fraction(&grid, &thin_walls, start));
+            });
+            move || drop(refresh)
+        });
+    }
+
+    // A setup edit that lands on the plan drops it, so no path is ever drawn
+    // through a wall; the stats bar then asks for a recompute
+    {
+        let plan_invalidated = plan_invalidated.clone();
+        let rover_layer = rover_layer.clone();
+        let journey_phase = journey_phase.clone();
+        use_effect_with((*som_layer).clone(), move |som| {
+            if *journey_phase == JourneyPhase::NotStarted {
+                let mut updated_rover = (*rover_layer).clone();
+                if updated_rover.drop_plan_if_blocked(som) {
+                    debug_log!("🧱 Map edit blocked the planned path - plan dropped");
+                    rover_layer.set(updated_rover);
+                    plan_invalidated.set(true);
+                }
+            }
+        });
+    }
+    {
+        let plan_invalidated = plan_invalidated.clone();
+        use_effect_with(*path_computed, move |&computed| {
+            if computed {
+                plan_invalidated.set(false);
+            }
+        });
+    }
+
+    // Diagnose a trap only when one is raised: the flood fills are too costly to
+    // keep fresh on every edit
+    {
+        let trapped_diagnostics = trapped_diagnostics.clone();
+        let som_layer = som_layer.clone();
+        let rover_layer = rover_layer.clone();
+        use_effect_with(*trapped_alert, move |reason| {
+            let diagnostics = reason.map(|_| {
+                let mut grid = vec![vec![false; grid_height]; grid_width];
+                for (x, y) in som_layer.get_complete_obstacle_map() {
+                    if x < grid_width && y < grid_height {
+                        grid[x][y] = true;
+                    }
+                }
+                let position = rover_layer.current_position;
+                let goal = rover_layer
+                    .pending_goals()
+                    .first()
+                    .copied()
+                    .unwrap_or(rover_layer.goal_position);
+                let isolation = isolation(
+                    &grid,
+                    &rover_layer.thin_walls,
+                    position,
+                    goal,
+                    rover_layer.connectivity(),
+                );
+                TrappedDiagnostics {
+                    position,
+                    isolation,
+                    own_trail: rover_layer.no_revisit && !isolation.separated,
+                }
+            });
+            trapped_diagnostics.set(diagnostics);
+            || ()
+        });
+    }
+
+    // What-if preview: the plan with the hovered cell blocked. Only a cell on the
+    // current plan can change it, so any other cell previews nothing
+    {
+        let what_if_path = what_if_path.clone();
+        let som_layer = som_layer.clone();
+        let rover_layer = rover_layer.clone();
+        let setup = *journey_phase == JourneyPhase::NotStarted && *path_computed;
+        let active = (*what_if && setup).then_some(*hovered_cell).flatten();
+        use_effect_with(
+            (active, (*som_layer).clone(), (*rover_layer).clone()),
+            move |_| {
+                let rover = (*rover_layer).clone();
+                let preview = what_if_path.clone();
+                let refresh = active
+                    .filter(|&cell| rover.planned_path.contains(&cell) && !rover.is_protected(cell))
+                    .map(|cell| {
+                        gloo_timers::callback::Timeout::new(WHAT_IF_DEBOUNCE_MS, move || {
+                            let mut obstacles = som_layer.get_complete_obstacle_map();
+                            obstacles.push(cell);
+                            let response = solve(&SolveRequest {
+                                width: grid_width,
+                                height: grid_height,
+                                obstacles,
+                                start: rover.current_position,
+                                goal: rover.goal_position,
+                                algorithm: rover.algorithm.clone(),
+                                allow_diagonals: rover.allow_diagonals,
+                                diagonal_cost: rover.diagonal_cost,
+                                turn_penalty: rover.turn_penalty,
+                                terrain_costs: rover
+                                    .terrain_costs
+                                    .iter()
+                                    .map(|(&c, &v)| (c, v))
+                                    .collect(),
+                                thin_walls: rover.thin_walls.iter().copied().collect(),
+                                obstacle_mask: None,
+                                record_trace: false,
+                                grid_kind: GridKind::Square,
+                            });
+                            preview.set(response.path);
+                        })
+                    });
+                if refresh.is_none() && !what_if_path.is_empty() {
+                    what_if_path.set(Vec::new());
+                }
+                move || drop(refresh)
+            },
+        );
+    }
+
+    // Obstacles left beyond the grid after a resize would be invisible but still
+    // count as map state, so drop them whenever the dimensions change
+    {
+        let som_layer = som_layer.clone();
+        let dob_layer = dob_layer.clone();
+        use_effect_with((grid_width, grid_height), move |&(width, height)| {
+            let mut updated_som = (*som_layer).clone();
+            if updated_som.prune_to_bounds(width, height) {
+                som_layer.set(updated_som);
+            }
+            let mut updated_dob = (*dob_layer).clone();
+            if updated_dob.prune_to_bounds(width, height) {
+                dob_layer.set(updated_dob);
+            }
+        });
+    }
+
+    // Reaching the goal is the only way into Finished, so this fires exactly
+    // once per journey; restart/reset leave Finished and clear it again
+    {
+        let goal_celebration = goal_celebration.clone();
+        let on_goal_reached = props.on_goal_reached.clone();
+        let goal = rover_layer.current_position;
+        use_effect_with(*journey_phase, move |phase| {
+            let clear_later = if *phase == JourneyPhase::Finished {
+                goal_celebration.set(Some(js_sys::Date::now()));
+                if let Some(callback) = &on_goal_reached {
+                    callback.emit(goal);
+                }
+                let goal_celebration = goal_celebration.clone();
+                Some(gloo_timers::callback::Timeout::new(
+                    CELEBRATION_MS,
+                    move || goal_celebration.set(None),
+                ))
+            } else {
+                if goal_celebration.is_some() {
+                    goal_celebration.set(None);
+                }
+                None
+            };
+            move || drop(clear_later)
+        });
+    }
+
+    // At the goal, find what full knowledge of the final map would have walked,
+    // to overlay on the traveled path; any other phase drops it
+    {
+        let optimal_route = optimal_route.clone();
+        let rover_layer = rover_layer.clone();
+        let som_layer = som_layer.clone();
+        use_effect_with(*journey_phase, move |phase| {
+            if *phase == JourneyPhase::Finished {
+                optimal_route.set(rover_layer.optimal_route(&som_layer, (grid_width, grid_height)));
+            } else if optimal_route.is_some() {
+                optimal_route.set(None);
+            }
+        });
+    }
+
+    // Snapshot every cycle of a running journey; replay never touches the live layers
+    {
+        let replay_frames = replay_frames.clone();
+        let rover_layer = rover_layer.clone();
+        let dob_layer = dob_layer.clone();
+        use_effect_with(
+            (
+                rover_layer.current_position,
+                rover_layer.planned_path.clone(),
+                dob_layer.blue_converted_dobs.len(),
+                *journey_phase,
+            ),
+            move |(position, planned_path, _, phase)| {
+                if *phase == JourneyPhase::Running {
+                    let frame = ReplayFrame {
+                        position: *position,
+                        planned_path: planned_path.clone(),
+                        converted_obstacles: dob_layer.get_blue_dobs_for_display(),
+                        amber_dobs: dob_layer.get_amber_dobs_for_display(),
+                        traveled_len: rover_layer.traveled_path.len(),
+                    };
+                    if replay_frames.last() != Some(&frame) {
+                        let mut frames = (*replay_frames).clone();
+                        frames.push(frame);
+                        replay_frames.set(frames);
+                    }
+                }
+            },
+        );
+    }
+
+    {
+        let event_log = event_log.clone();
+        use_effect_with((*event_log).clone(), move |_| {
+            event_log.publish();
+        });
+    }
+
+    {
+        let event_log = event_log.clone();
+        let slow_cycle_warned = slow_cycle_warned.clone();
+        let slow_cycle_warning = slow_cycle_warning.clone();
+        use_effect_with(*slow_cycle, move |slow_cycle| {
+            if let Some((at, cycle_ms)) = *slow_cycle {
+                // Only a new slowest is logged, so the last entry is the max
+                if event_log
+                    .slowest_cycle_ms()
+                    .is_none_or(|slowest| cycle_ms > slowest)
+                {
+                    record_events(&event_log, vec![JourneyEvent::SlowCycle { at, cycle_ms }]);
+                }
+                if !*slow_cycle_warned.borrow() {
+                    *slow_cycle_warned.borrow_mut() = true;
+                    slow_cycle_warning.set(Some(cycle_ms));
+                }
+            }
+        });
+    }
 
-    
     {
         let som_layer = som_layer.clone();
         let rover_layer = rover_layer.clone();
@@ -695,86 +1127,116 @@ pub fn main_app() -> Html {
         let journey_stats = journey_stats.clone();
         let trapped_alert = trapped_alert.clone();
         let is_animating = is_animating.clone();
+        let journey_phase = journey_phase.clone();
+        let event_log = event_log.clone();
         let current_speed = current_speed.clone();
+        let planner = planner.clone();
+        let slow_cycle = slow_cycle.clone();
 
         use_effect_with(
             (
-                (*rover_layer).current_position,
+                rover_layer.current_position,
                 *is_animating,
                 *current_speed,
-                (*dob_layer).amber_dobs.len(),
+                dob_layer.amber_dobs.len(),
             ),
             move |(rover_position, is_active, speed, dob_count)| {
-                if !*is_active {
-                    return;
-                }
+                // Keep the handle so pausing cancels a cycle that is still pending
+                let pending_cycle = 'schedule: {
+                    if !*is_active {
+                        break 'schedule None;
+                    }
 
-                let current_stats = (*journey_stats).clone();
-                if current_stats.nodes_visited > 1000 {
-                    web_sys::console::log_1(&"Safety stop - too many steps".into());
-                    is_animating.set(false);
-                    return;
-                }
+                    let current_stats = (*journey_stats).clone();
+                    if current_stats.nodes_visited > 1000 {
+                        debug_log!("Safety stop - too many steps");
+                        is_animating.set(false);
+                        break 'schedule None;
+                    }
 
-                let current_rover_state = (*rover_layer).clone();
-                if current_rover_state.current_position == current_rover_state.goal_position {
-                    web_sys::console::log_1(&"🎯 Goal reached - stopping animation".into());
-                    is_animating.set(false);
-                    return;
-                }
+                    let current_rover_state = (*rover_layer).clone();
+                    if current_rover_state.has_reached_goal() {
+                        debug_log!("🎯 Goal reached - stopping animation");
+                        let mut stats = current_stats;
+                        stats.finish(
+                            js_sys::Date::now(),
+                            current_rover_state.journey_start(),
+                            current_rover_state.current_position,
+                        );
+                        journey_stats.set(stats);
+                        record_events(
+                            &event_log,
+                            vec![JourneyEvent::GoalReached {
+                                at: current_rover_state.current_position,
+                            }],
+                        );
+                        journey_phase.set(JourneyPhase::Finished);
+                        is_animating.set(false);
+                        break 'schedule None;
+                    }
 
-                if current_rover_state.planned_path.len() < 2 && *dob_count == 0 {
-                    web_sys::console::log_1(
-                        &"🛑 No valid path and no obstacles to process - stopping".into(),
-                    );
-                    trapped_alert.set(true);
-                    is_animating.set(false);
-                    return;
-                }
+                    if current_rover_state.planned_path.len() < 2 && *dob_count == 0 {
+                        debug_log!("🛑 No valid path and no obstacles to process - stopping");
+                        record_events(
+                            &event_log,
+                            vec![JourneyEvent::Trapped {
+                                at: current_rover_state.current_position,
+                            }],
+                        );
+                        trapped_alert.set(Some(TrappedReason::BoxedIn(
+                            current_rover_state.current_position,
+                        )));
+                        is_animating.set(false);
+                        break 'schedule None;
+                    }
 
-                web_sys::console::log_1(
-                    &format!(
+                    debug_log!(
                         "CYCLE TRIGGER: Rover at {:?}, speed {}, DOBs: {}, path_len: {}",
                         rover_position,
                         speed,
                         dob_count,
                         current_rover_state.planned_path.len()
-                    )
-                    .into(),
-                );
+                    );
 
-                let debug_dob = (*dob_layer).clone();
-                web_sys::console::log_1(
-                    &format!(
+                    let debug_dob = (*dob_layer).clone();
+                    debug_log!(
                         "PRE-CYCLE DOB CHECK: {} amber DOBs: {:?}",
                         debug_dob.amber_dobs.len(),
                         debug_dob.amber_dobs
-                    )
-                    .into(),
-                );
-
-                // Calculate delay based on speed (1-10 scale)
-                // Speed 1 = 1000ms, Speed 5 = 500ms, Speed 10 = 100ms
-                let delay_ms = 1100 - (*speed as u32 * 100);
-
-                let timeout = gloo_timers::callback::Timeout::new(delay_ms, move || {
-                    execute_one_cycle(
-                        &som_layer,
-                        &rover_layer,
-                        &dob_layer,
-                        &journey_stats,
-                        &trapped_alert,
-                        &is_animating,
                     );
-                });
-                timeout.forget();
+
+                    let delay_ms = step_delay_ms(*speed);
+                    debug_log!("⏲️ Speed {} → {}ms per step", speed, delay_ms);
+
+                    Some(gloo_timers::callback::Timeout::new(delay_ms, move || {
+                        let at = rover_layer.current_position;
+                        let started = now_ms();
+                        execute_one_cycle(
+                            &som_layer,
+                            &rover_layer,
+                            &dob_layer,
+                            &journey_stats,
+                            &trapped_alert,
+                            &is_animating,
+                            &journey_phase,
+                            &event_log,
+                            &planner,
+                        );
+                        let cycle_ms = now_ms() - started;
+                        if cycle_ms > SLOW_CYCLE_MS {
+                            debug_log!("🐢 Cycle at {:?} took {:.1}ms", at, cycle_ms);
+                            slow_cycle.set(Some((at, cycle_ms)));
+                        }
+                    }))
+                };
+                move || drop(pending_cycle)
             },
         );
     }
 
-    let is_dragging = use_state(|| false);
-    let drag_mode = use_state(|| false);
-    let last_drag_cell = use_state(|| None::<Coord>);
+    // A ref, not state: pointer events can arrive before the next render
+    let stroke = use_mut_ref(|| None::<Stroke>);
+    let brush_size = use_state(|| 1usize);
 
     {
         let is_dark = is_dark.clone();
@@ -793,57 +1255,96 @@ pub fn main_app() -> Html {
         });
     }
 
-
     let on_compute = {
         let som_layer = som_layer.clone();
         let rover_layer = rover_layer.clone();
         let is_computing = is_computing.clone();
         let path_computed = path_computed.clone();
+        let trapped_alert = trapped_alert.clone();
+        let compute_cancelled = compute_cancelled.clone();
+        let planner = planner.clone();
+        let cancel_flag = cancel_flag.clone();
 
         Callback::from(move |_| {
-            web_sys::console::log_1(&"COMPUTE PATH: Creating initial planned path".into());
+            if *is_computing {
+                return;
+            }
+            debug_log!("COMPUTE PATH: Creating initial planned path");
+            cancel_flag.reset();
+            compute_cancelled.set(false);
             is_computing.set(true);
 
-            let current_som = (*som_layer).clone();
-            let mut current_rover = (*rover_layer).clone();
-
-            current_rover.planned_path.clear();
-            current_rover.planned_path.shrink_to_fit();
-            web_sys::console::log_1(
-                &format!(
+            // Yield to the browser first so "Computing..." paints before the
+            // solver blocks the thread; see solver.rs for the worker route.
+            let som_layer = som_layer.clone();
+            let rover_layer = rover_layer.clone();
+            let is_computing = is_computing.clone();
+            let path_computed = path_computed.clone();
+            let trapped_alert = trapped_alert.clone();
+            let compute_cancelled = compute_cancelled.clone();
+            let planner = planner.clone();
+            let cancel_flag = cancel_flag.clone();
+            gloo_timers::callback::Timeout::new(COMPUTE_YIELD_MS, move || {
+                let current_som = (*som_layer).clone();
+                let mut current_rover = (*rover_layer).clone();
+
+                current_rover.planned_path.clear();
+                current_rover.planned_path.shrink_to_fit();
+                debug_log!(
                     "🧹 Cleared planned path | Traveled path: {} steps",
                     current_rover.traveled_path.len()
-                )
-                .into(),
-            );
+                );
 
-            let obstacle_map = current_som.get_complete_obstacle_map();
-            web_sys::console::log_1(
-                &format!(
+                let obstacle_map = current_som.get_complete_obstacle_map();
+                debug_log!(
                     "🗺️ Using {} obstacles from SOM for pathfinding",
                     obstacle_map.len()
-                )
-                .into(),
-            );
+                );
 
-            let path_found = current_rover.compute_path_from_som(obstacle_map);
+                let outcome =
+                    current_rover.compute_path_from_som(obstacle_map, &mut planner.borrow_mut());
+                let path_found = outcome.is_ok();
+
+                match outcome {
+                    Ok(_) => {
+                        debug_log!(
+                            "Path computation SUCCESS: {} planned steps | {} traveled steps",
+                            current_rover.planned_path.len(),
+                            current_rover.traveled_path.len()
+                        );
+                        trapped_alert.set(None);
+                    }
+                    Err(PlanError::Cancelled) => {
+                        debug_log!("Path computation CANCELLED");
+                        trapped_alert.set(None);
+                        compute_cancelled.set(true);
+                    }
+                    Err(error) => {
+                        debug_log!("Path computation FAILED: {}", error);
+                        trapped_alert.set(Some(TrappedReason::from_error(error, None)));
+                    }
+                }
+                // A late click must not stop the journey's replans
+                cancel_flag.reset();
+
+                rover_layer.set(current_rover);
+                is_computing.set(false);
+                path_computed.set(path_found);
+            })
+            .forget();
+        })
+    };
 
-            if path_found {
-                web_sys::console::log_1(
-                    &format!(
-                        "Path computation SUCCESS: {} planned steps | {} traveled steps",
-                        current_rover.planned_path.len(),
-                        current_rover.traveled_path.len()
-                    )
-                    .into(),
-                );
-            } else {
-                web_sys::console::log_1(&"Path computation FAILED".into());
-            }
+    // Searches poll the flag; one already finished just ignores it
+    let on_cancel_compute = {
+        let cancel_flag = cancel_flag.clone();
+        let is_computing = is_computing.clone();
 
-            rover_layer.set(current_rover);
-            is_computing.set(false);
-            path_computed.set(path_found);
+        Callback::from(move |_| {
+            if *is_computing {
+                debug_log!("🛑 Cancelling path computation");
+                cancel_flag.cancel();
+            }
         })
     };
 
@@ -851,57 +1352,71 @@ pub fn main_app() -> Html {
         let is_animating = is_animating.clone();
         let trapped_alert = trapped_alert.clone();
         let journey_stats = journey_stats.clone();
+        let journey_phase = journey_phase.clone();
+        let event_log = event_log.clone();
+        let replay_frames = replay_frames.clone();
+        let replay_index = replay_index.clone();
         let visual_start = visual_start.clone();
         let rover_layer = rover_layer.clone();
+        let dob_layer = dob_layer.clone();
 
         Callback::from(move |_| {
-            web_sys::console::log_1(&"🚀 START JOURNEY CLICKED!".into());
-
-            let mut current_rover = (*rover_layer).clone();
+            debug_log!("🚀 START JOURNEY CLICKED!");
+
+            if *journey_phase == JourneyPhase::Paused {
+                // Resume: keep traveled path, counters and the original start time
+                let mut stats = (*journey_stats).clone();
+                stats.resume(js_sys::Date::now());
+                journey_stats.set(stats);
+                trapped_alert.set(None);
+                journey_phase.set(JourneyPhase::Running);
+                replay_index.set(None);
+
+                debug_log!(
+                    "▶️ Journey resumed at {:?} | Traveled: {} steps",
+                    rover_layer.current_position,
+                    rover_layer.traveled_path.len()
+                );
 
-            if current_rover.planned_path.is_empty() {
-                web_sys::console::log_1(&"Cannot start - no planned path computed".into());
+                is_animating.set(true);
                 return;
             }
 
-            if current_rover.planned_path[0] != current_rover.current_position {
-                web_sys::console::log_1(
-                    &format!(
-                        "Fixing planned path start: {:?} -> {:?}",
-                        current_rover.planned_path[0], current_rover.current_position
-                    )
-                    .into(),
-                );
-                current_rover.planned_path[0] = current_rover.current_position;
+            let current_rover = (*rover_layer).clone();
+
+            if current_rover.planned_path.is_empty() {
+                debug_log!("Cannot start - no planned path computed");
+                return;
             }
 
-            web_sys::console::log_1(
-                &format!(
-                    "Starting journey | Traveled: {} steps | Planned: {} steps: {:?} -> {:?}",
-                    current_rover.traveled_path.len(),
-                    current_rover.planned_path.len(),
-                    current_rover.planned_path.first().unwrap_or(&(0, 0)),
-                    current_rover.planned_path.last().unwrap_or(&(0, 0))
-                )
-                .into(),
+            debug_log!(
+                "Starting journey | Traveled: {} steps | Planned: {} steps: {:?} -> {:?}",
+                current_rover.traveled_path.len(),
+                current_rover.planned_path.len(),
+                current_rover.planned_path.first().unwrap_or(&(0, 0)),
+                current_rover.planned_path.last().unwrap_or(&(0, 0))
             );
 
-            trapped_alert.set(false);
+            trapped_alert.set(None);
             visual_start.set(current_rover.start_position);
 
             journey_stats.set(JourneyStats {
                 start_time: Some(js_sys::Date::now()),
-                end_time: None,
-                total_distance: 0.0,
-                reroute_count: 0,
                 nodes_visited: 1,
-                obstacles_detected: 0,
-                path_efficiency: 100.0,
+                max_planned_len: current_rover.planned_path.len(),
+                ..JourneyStats::new()
             });
+            event_log.set(EventLog::default());
+            replay_frames.set(Vec::new());
+            replay_index.set(None);
+            journey_phase.set(JourneyPhase::Running);
+            let mut updated_dob = (*dob_layer).clone();
+            updated_dob.reseed_wander();
+            dob_layer.set(updated_dob);
 
             rover_layer.set(current_rover);
 
-            web_sys::console::log_1(&"🚀 Journey initialized - starting movement execution".into());
+            debug_log!("🚀 Journey initialized - starting movement execution");
 
             is_animating.set(true);
         })
@@ -909,27 +1424,88 @@ pub fn main_app() -> Html {
 
     let on_pause = {
         let is_animating = is_animating.clone();
+        let journey_stats = journey_stats.clone();
+        let journey_phase = journey_phase.clone();
         Callback::from(move |_| {
-            web_sys::console::log_1(&"⏸️ EMERGENCY STOP: Journey paused by user".into());
+            debug_log!("⏸️ EMERGENCY STOP: Journey paused by user");
+            if *journey_phase == JourneyPhase::Running {
+                let mut stats = (*journey_stats).clone();
+                stats.pause(js_sys::Date::now());
+                journey_stats.set(stats);
+                journey_phase.set(JourneyPhase::Paused);
+            }
             is_animating.set(false);
         })
     };
 
+    // Rewind one cell while paused and replan from there
+    let on_step_back = {
+        let rover_layer = rover_layer.clone();
+        let som_layer = som_layer.clone();
+        let planner = planner.clone();
+        let trapped_alert = trapped_alert.clone();
+        let journey_stats = journey_stats.clone();
+        let journey_phase = journey_phase.clone();
+        let replay_frames = replay_frames.clone();
+        let replay_index = replay_index.clone();
+        Callback::from(move |_| {
+            if *journey_phase != JourneyPhase::Paused {
+                return;
+            }
+            let mut current_rover = (*rover_layer).clone();
+            let Some(left) = current_rover.step_back() else {
+                return;
+            };
+            debug_log!(
+                "⏮️ Stepped back {:?} -> {:?}",
+                left,
+                current_rover.current_position
+            );
+
+            let outcome = current_rover.compute_path_from_som(
+                som_layer.get_complete_obstacle_map(),
+                &mut planner.borrow_mut(),
+            );
+            trapped_alert.set(outcome.err().map(|error| {
+                TrappedReason::from_error(error, Some(current_rover.current_position))
+            }));
+
+            let mut stats = (*journey_stats).clone();
+            let here = current_rover.current_position;
+            let (dx, dy) = (
+                left.0.abs_diff(here.0) as f64,
+                left.1.abs_diff(here.1) as f64,
+            );
+            stats.total_distance = (stats.total_distance - (dx * dx + dy * dy).sqrt()).max(0.0);
+            stats.nodes_visited = stats.nodes_visited.saturating_sub(1);
+            stats.cycles_without_move = 0;
+            journey_stats.set(stats);
+
+            let traveled_len = current_rover.traveled_path.len();
+            let mut frames = (*replay_frames).clone();
+            frames.retain(|frame| frame.traveled_len <= traveled_len);
+            replay_frames.set(frames);
+            replay_index.set(None);
+
+            rover_layer.set(current_rover);
+        })
+    };
+
     let on_algo_change = {
         let rover_layer = rover_layer.clone();
         let path_computed = path_computed.clone();
 
         Callback::from(move |alg_str: String| {
-            web_sys::console::log_1(
-                &format!("🔄 ALGORITHM CHANGE: Switching to {}", alg_str).into(),
-            );
+            debug_log!("🔄 ALGORITHM CHANGE: Switching to {}", alg_str);
 
             let mut current_rover = (*rover_layer).clone();
             current_rover.set_algorithm(&alg_str);
+            prefs::save(prefs::ALGORITHM, &current_rover.algorithm);
+            prefs::save(prefs::ALLOW_DIAGONALS, current_rover.allow_diagonals);
             rover_layer.set(current_rover);
             path_computed.set(false);
 
-            web_sys::console::log_1(&format!("Algorithm changed to: {}", alg_str).into());
+            debug_log!("Algorithm changed to: {}", alg_str);
         })
     };
 
@@ -937,6 +1513,7 @@ pub fn main_app() -> Html {
         let current_speed = current_speed.clone();
         Callback::from(move |new_speed: u32| {
             current_speed.set(new_speed);
+            prefs::save(prefs::SPEED, new_speed);
         })
     };
 
@@ -944,114 +1521,85 @@ pub fn main_app() -> Html {
         let som_layer = som_layer.clone();
         let rover_layer = rover_layer.clone();
         let dob_layer = dob_layer.clone();
-        let is_dragging = is_dragging.clone();
-        let drag_mode = drag_mode.clone();
-        let last_drag_cell = last_drag_cell.clone();
-        let is_animating = is_animating.clone();
+        let event_log = event_log.clone();
+        let stroke = stroke.clone();
+        let journey_phase = journey_phase.clone();
         let visual_start = visual_start.clone();
         let path_computed = path_computed.clone();
+        let brush_size = brush_size.clone();
 
         Callback::from(move |coord: Coord| {
-            web_sys::console::log_1(
-                &format!(
-                    "MOUSE DOWN at {:?} - Animation: {}",
-                    coord, *is_animating
-                )
-                .into(),
-            );
+            debug_log!("MOUSE DOWN at {:?} - Phase: {:?}", coord, *journey_phase);
 
             let current_rover = (*rover_layer).clone();
             let current_som = (*som_layer).clone();
             let current_dob = (*dob_layer).clone();
 
-            if coord == *visual_start
-                || coord == current_rover.goal_position
-                || coord == current_rover.current_position
-            {
-                web_sys::console::log_1(
-                    &format!("Cannot place at {:?} - protected position", coord).into(),
-                );
+            let footprint: Vec<Coord> =
+                brush_footprint(coord, *brush_size, grid_width, grid_height)
+                    .into_iter()
+                    .filter(|&c| c != *visual_start && !current_rover.is_protected(c))
+                    .collect();
+            if footprint.is_empty() {
+                debug_log!("Cannot place at {:?} - protected position", coord);
                 return;
             }
 
-            is_dragging.set(true);
-            last_drag_cell.set(Some(coord));
-
-            if *is_animating {
-                web_sys::console::log_1(
-                    &format!("JOURNEY MODE: DOB operation at {:?}", coord).into(),
-                );
-                web_sys::console::log_1(
-                    &format!(
-                        "Current DOB state: {} amber DOBs",
-                        current_dob.amber_dobs.len()
-                    )
-                    .into(),
+            if journey_phase.is_mid_journey() {
+                debug_log!(
+                    "JOURNEY MODE: DOB operation at {:?} ({} amber DOBs)",
+                    coord,
+                    current_dob.amber_dobs.len()
                 );
 
-                let mut updated_dob = current_dob.clone();
-
-                if current_som.is_cell_occupied(coord) {
-                    web_sys::console::log_1(
-                        &format!(
-                            "Cannot place DOB at {:?} - cell occupied by static obstacle",
-                            coord
-                        )
-                        .into(),
-                    );
-                    return;
+                // The cell under the cursor decides whether the whole stroke adds or removes
+                let adding = !current_dob.amber_dobs.contains(&coord);
+                *stroke.borrow_mut() = Some(Stroke {
+                    adding,
+                    last_cell: coord,
+                });
+                let mut updated_dob = current_dob;
+                let mut changed = false;
+                let mut refused = None;
+                for cell in footprint {
+                    if adding {
+                        match updated_dob.add_dob(cell, &current_som, &current_rover) {
+                            Ok(()) => changed = true,
+                            Err(error) if cell == coord => refused = Some(error),
+                            Err(_) => {}
+                        }
+                    } else if updated_dob.amber_dobs.contains(&cell) {
+                        updated_dob.amber_dobs.retain(|&c| c != cell);
+                        changed = true;
+                    }
                 }
-
-                let already_has_dob = updated_dob.amber_dobs.contains(&coord);
-                web_sys::console::log_1(
-                    &format!("DOB exists at {:?}: {}", coord, already_has_dob).into(),
-                );
-
-                if already_has_dob {
-                    updated_dob.amber_dobs.retain(|&c| c != coord);
-                    web_sys::console::log_1(
-                        &format!(
-                            "REMOVED amber DOB at {:?} - total: {}",
-                            coord,
-                            updated_dob.amber_dobs.len()
-                        )
-                        .into(),
-                    );
-                    drag_mode.set(false);
-                } else {
-                    updated_dob.amber_dobs.push(coord);
-                    web_sys::console::log_1(
-                        &format!(
-                            "ADDED amber DOB at {:?} - total: {}",
-                            coord,
-                            updated_dob.amber_dobs.len()
-                        )
-                        .into(),
+                if changed {
+                    dob_layer.set(updated_dob);
+                } else if let Some(error) = refused {
+                    // Only a click that placed nothing at all is worth a log line
+                    record_events(
+                        &event_log,
+                        vec![JourneyEvent::DobRefused {
+                            at: coord,
+                            reason: error.label(),
+                        }],
                     );
-                    drag_mode.set(true);
                 }
-
-                web_sys::console::log_1(
-                    &format!(
-                        "Setting DOB layer with {} amber DOBs: {:?}",
-                        updated_dob.amber_dobs.len(),
-                        updated_dob.amber_dobs
-                    )
-                    .into(),
-                );
-                dob_layer.set(updated_dob);
             } else {
-                web_sys::console::log_1(
-                    &format!("SETUP MODE: Adding static obstacle at {:?}", coord).into(),
-                );
+                debug_log!("SETUP MODE: Static obstacle stroke at {:?}", coord);
                 let mut updated_som = current_som;
-                let has_static = updated_som.original_static_obstacles.contains(&coord);
-                drag_mode.set(!has_static);
+                let adding = !updated_som.original_static_obstacles.contains(&coord);
+                *stroke.borrow_mut() = Some(Stroke {
+                    adding,
+                    last_cell: coord,
+                });
 
-                if has_static {
-                    updated_som.original_static_obstacles.remove(&coord);
-                } else {
-                    updated_som.original_static_obstacles.insert(coord);
+                for cell in footprint {
+                    if adding {
+                        updated_som.original_static_obstacles.insert(cell);
+                    } else {
+                        updated_som.original_static_obstacles.remove(&cell);
+                    }
                 }
 
                 som_layer.set(updated_som);
@@ -1064,159 +1612,410 @@ pub fn main_app() -> Html {
         let som_layer = som_layer.clone();
         let rover_layer = rover_layer.clone();
         let dob_layer = dob_layer.clone();
-        let is_dragging = is_dragging.clone();
-        let drag_mode = drag_mode.clone();
-        let last_drag_cell = last_drag_cell.clone();
-        let is_animating = is_animating.clone();
+        let stroke = stroke.clone();
+        let journey_phase = journey_phase.clone();
         let visual_start = visual_start.clone();
         let path_computed = path_computed.clone();
+        let brush_size = brush_size.clone();
 
         Callback::from(move |coord: Coord| {
-            if !*is_dragging || Some(coord) == *last_drag_cell {
+            // Every cell gets the stroke's mode: set, never toggled
+            let Some(adding) = stroke.borrow_mut().as_mut().and_then(|s| s.enter(coord)) else {
                 return;
-            }
+            };
 
             let current_rover = (*rover_layer).clone();
             let current_som = (*som_layer).clone();
             let current_dob = (*dob_layer).clone();
 
-            if coord == *visual_start
-                || coord == current_rover.goal_position
-                || coord == current_rover.current_position
-            {
+            let footprint: Vec<Coord> =
+                brush_footprint(coord, *brush_size, grid_width, grid_height)
+                    .into_iter()
+                    .filter(|&c| c != *visual_start && !current_rover.is_protected(c))
+                    .collect();
+            if footprint.is_empty() {
                 return;
             }
 
-            last_drag_cell.set(Some(coord));
-
-            if *is_animating {
-                web_sys::console::log_1(
-                    &format!(
-                        "MOUSE DRAG: DOB operation at {:?} (mode: {})",
-                        coord,
-                        if *drag_mode { "ADD" } else { "REMOVE" }
-                    )
-                    .into(),
+            if journey_phase.is_mid_journey() {
+                debug_log!(
+                    "MOUSE DRAG: DOB operation at {:?} (mode: {})",
+                    coord,
+                    if adding { "ADD" } else { "REMOVE" }
                 );
 
                 let mut updated_dob = current_dob;
-
-                if current_som.is_cell_occupied(coord) {
-                    return;
-                }
-
-                let has_amber = updated_dob.amber_dobs.contains(&coord);
-
-                if *drag_mode && !has_amber {
-                    updated_dob.amber_dobs.push(coord);
-                    web_sys::console::log_1(
-                        &format!(
-                            "Dragged amber DOB added at {:?} - total: {}",
-                            coord,
-                            updated_dob.amber_dobs.len()
-                        )
-                        .into(),
-                    );
-                } else if !*drag_mode && has_amber {
-                    updated_dob.amber_dobs.retain(|&c| c != coord);
-                    web_sys::console::log_1(
-                        &format!(
-                            "Dragged amber DOB removed at {:?} - total: {}",
-                            coord,
-                            updated_dob.amber_dobs.len()
-                        )
-                        .into(),
-                    );
+                for cell in footprint {
+                    if adding {
+                        // Refused cells are skipped silently mid-drag
+                        let _ = updated_dob.add_dob(cell, &current_som, &current_rover);
+                    } else {
+                        updated_dob.amber_dobs.retain(|&c| c != cell);
+                    }
                 }
+                debug_log!("Dragged DOBs - total: {}", updated_dob.amber_dobs.len());
 
                 dob_layer.set(updated_dob);
             } else {
                 let mut updated_som = current_som;
-                let has_static = updated_som.original_static_obstacles.contains(&coord);
+                for cell in footprint {
+                    if adding {
+                        updated_som.original_static_obstacles.insert(cell);
+                    } else {
+                        updated_som.original_static_obstacles.remove(&cell);
+                    }
+                }
+
+                som_layer.set(updated_som);
+                path_computed.set(false);
+            }
+        })
+    };
+
+    // Right-button / Alt drags: always remove, never toggle
+    let on_erase = {
+        let som_layer = som_layer.clone();
+        let dob_layer = dob_layer.clone();
+        let stroke = stroke.clone();
+        let journey_phase = journey_phase.clone();
+        let path_computed = path_computed.clone();
+        let brush_size = brush_size.clone();
+
+        Callback::from(move |coord: Coord| {
+            *stroke.borrow_mut() = Some(Stroke {
+                adding: false,
+                last_cell: coord,
+            });
+
+            let footprint = brush_footprint(coord, *brush_size, grid_width, grid_height);
+            if journey_phase.is_mid_journey() {
+                if footprint.iter().any(|c| dob_layer.amber_dobs.contains(c)) {
+                    let mut updated_dob = (*dob_layer).clone();
+                    updated_dob.amber_dobs.retain(|c| !footprint.contains(c));
+                    debug_log!("ERASED amber DOBs around {:?}", coord);
+                    dob_layer.set(updated_dob);
+                }
+            } else if footprint
+                .iter()
+                .any(|c| som_layer.original_static_obstacles.contains(c))
+            {
+                let mut updated_som = (*som_layer).clone();
+                for cell in &footprint {
+                    updated_som.original_static_obstacles.remove(cell);
+                }
+                debug_log!("ERASED static obstacles around {:?}", coord);
+                som_layer.set(updated_som);
+                path_computed.set(false);
+            }
+        })
+    };
+
+    let on_mouse_up = {
+        let stroke = stroke.clone();
+        Callback::from(move |_| {
+            *stroke.borrow_mut() = None;
+        })
+    };
+
+    let on_start_drag = {
+        let rover_layer = rover_layer.clone();
+        let path_computed = path_computed.clone();
+        let visual_start = visual_start.clone();
+        let journey_phase = journey_phase.clone();
+
+        Callback::from(move |new_pos: Coord| {
+            let mut updated_rover = (*rover_layer).clone();
+            updated_rover.reset_to_start(new_pos);
+            rover_layer.set(updated_rover);
+            path_computed.set(false);
+            journey_phase.set(JourneyPhase::NotStarted);
+            visual_start.set(new_pos);
+        })
+    };
+
+    let on_goal_drag = {
+        let rover_layer = rover_layer.clone();
+        let path_computed = path_computed.clone();
+
+        Callback::from(move |new_goal: Coord| {
+            let mut updated_rover = (*rover_layer).clone();
+            updated_rover.set_goal(new_goal);
+            rover_layer.set(updated_rover);
+            path_computed.set(false);
+        })
+    };
+
+    let on_goal_toggle = {
+        let rover_layer = rover_layer.clone();
+        let som_layer = som_layer.clone();
+        let dob_layer = dob_layer.clone();
+        let path_computed = path_computed.clone();
+        let journey_phase = journey_phase.clone();
+
+        // Goals go on free cells, and only while setting up
+        Callback::from(move |cell: Coord| {
+            if *journey_phase != JourneyPhase::NotStarted
+                || som_layer.is_cell_occupied(cell)
+                || dob_layer.amber_dobs.contains(&cell)
+            {
+                return;
+            }
+            let mut updated_rover = (*rover_layer).clone();
+            if updated_rover.toggle_goal(cell) {
+                rover_layer.set(updated_rover);
+                path_computed.set(false);
+            }
+        })
+    };
+
+    let on_cell_select = {
+        let selected_cell = selected_cell.clone();
+        let som_layer = som_layer.clone();
+        let journey_phase = journey_phase.clone();
+
+        // Clicking the selected cell again deselects it. Uncertain cells stay
+        // selectable, even while counted as blocked, so their belief can be edited
+        Callback::from(move |cell: Coord| {
+            if journey_phase.is_mid_journey() || som_layer.has_wall(cell) {
+                return;
+            }
+            selected_cell.set((*selected_cell != Some(cell)).then_some(cell));
+        })
+    };
+
+    // Belief that the selected cell is occupied; 0 makes it plain ground again
+    let on_occupancy_change = {
+        let som_layer = som_layer.clone();
+        let path_computed = path_computed.clone();
+        let journey_phase = journey_phase.clone();
+
+        Callback::from(move |(cell, probability): (Coord, f64)| {
+            if journey_phase.is_mid_journey() {
+                return;
+            }
+            let mut updated_som = (*som_layer).clone();
+            updated_som.set_occupancy(cell, probability);
+            som_layer.set(updated_som);
+            path_computed.set(false);
+        })
+    };
+
+    let on_occupancy_threshold_change = {
+        let som_layer = som_layer.clone();
+        let path_computed = path_computed.clone();
+
+        Callback::from(move |threshold: f64| {
+            let mut updated_som = (*som_layer).clone();
+            updated_som.occupancy_threshold = threshold;
+            som_layer.set(updated_som);
+            path_computed.set(false);
+        })
+    };
 
-                if *drag_mode && !has_static {
-                    updated_som.original_static_obstacles.insert(coord);
-                } else if !*drag_mode && has_static {
-                    updated_som.original_static_obstacles.remove(&coord);
-                }
+    // Walls between cells are part of the map, so they too are set up before a journey
+    let on_thin_wall_toggle = {
+        let rover_layer = rover_layer.clone();
+        let som_layer = som_layer.clone();
+        let path_computed = path_computed.clone();
+        let plan_invalidated = plan_invalidated.clone();
+        let journey_phase = journey_phase.clone();
 
-                som_layer.set(updated_som);
-                path_computed.set(false);
+        Callback::from(move |(a, b): (Coord, Coord)| {
+            if journey_phase.is_mid_journey() {
+                return;
+            }
+            let mut updated_rover = (*rover_layer).clone();
+            let added = updated_rover.toggle_thin_wall(a, b);
+            debug_log!(
+                "🧱 Thin wall {:?} | {:?} {}",
+                a,
+                b,
+                if added { "added" } else { "removed" }
+            );
+            if updated_rover.drop_plan_if_blocked(&som_layer) {
+                plan_invalidated.set(true);
             }
+            rover_layer.set(updated_rover);
+            path_computed.set(false);
         })
     };
 
-    let on_mouse_up = {
-        let is_dragging = is_dragging.clone();
-        let last_drag_cell = last_drag_cell.clone();
-        Callback::from(move |_| {
-            is_dragging.set(false);
-            last_drag_cell.set(None);
-        })
+    let on_deselect_cell = {
+        let selected_cell = selected_cell.clone();
+        Callback::from(move |_| selected_cell.set(None))
     };
 
-    let on_start_drag = {
+    let on_terrain_cost_change = {
+        let som_layer = som_layer.clone();
         let rover_layer = rover_layer.clone();
+        let planner = planner.clone();
         let path_computed = path_computed.clone();
-        let visual_start = visual_start.clone();
+        let trapped_alert = trapped_alert.clone();
 
-        Callback::from(move |new_pos: Coord| {
+        Callback::from(move |(cell, cost): (Coord, f64)| {
+            if validate_terrain_costs(&[(cell, cost)]).is_err() {
+                return;
+            }
             let mut updated_rover = (*rover_layer).clone();
-            updated_rover.reset_to_start(new_pos);
+            if cost == 1.0 {
+                updated_rover.terrain_costs.remove(&cell);
+            } else {
+                updated_rover.terrain_costs.insert(cell, cost);
+            }
+
+            if !updated_rover.planned_path.is_empty() {
+                let outcome = updated_rover.compute_path_from_som(
+                    som_layer.get_complete_obstacle_map(),
+                    &mut planner.borrow_mut(),
+                );
+                trapped_alert.set(outcome.err().map(|e| TrappedReason::from_error(e, None)));
+                path_computed.set(outcome.is_ok());
+            }
             rover_layer.set(updated_rover);
-            path_computed.set(false);
-            visual_start.set(new_pos);
         })
     };
 
-    let on_goal_drag = {
+    // Replace the static map with a maze; no seed picks a fresh one
+    let on_generate_maze = {
+        let som_layer = som_layer.clone();
         let rover_layer = rover_layer.clone();
+        let dob_layer = dob_layer.clone();
+        let maze_seed = maze_seed.clone();
         let path_computed = path_computed.clone();
+        let trapped_alert = trapped_alert.clone();
+        let journey_phase = journey_phase.clone();
 
-        Callback::from(move |new_goal: Coord| {
+        Callback::from(move |seed: Option<u64>| {
+            if journey_phase.is_mid_journey() {
+                return;
+            }
+            let seed = seed.unwrap_or_else(fresh_seed);
             let mut updated_rover = (*rover_layer).clone();
-            updated_rover.set_goal(new_goal);
+            let maze = generate_maze(
+                grid_width,
+                grid_height,
+                seed,
+                &[updated_rover.start_position, updated_rover.goal_position],
+            );
+            let walls: HashSet<Coord> = maze.walls().collect();
+            debug_log!(
+                "🧱 Generated maze from seed {} ({} walls)",
+                seed,
+                walls.len()
+            );
+
+            updated_rover.goals.retain(|goal| !walls.contains(goal));
+            updated_rover.thin_walls.clear();
+            updated_rover.reset_to_start(updated_rover.start_position);
             rover_layer.set(updated_rover);
+
+            let mut updated_som = SomLayer::new();
+            updated_som.occupancy_threshold = som_layer.occupancy_threshold;
+            updated_som.set_initial_obstacles(walls);
+            som_layer.set(updated_som);
+            let mut updated_dob = (*dob_layer).clone();
+            updated_dob.clear_all();
+            dob_layer.set(updated_dob);
+
+            maze_seed.set(Some(seed));
             path_computed.set(false);
+            trapped_alert.set(None);
+            journey_phase.set(JourneyPhase::NotStarted);
         })
     };
 
-    let on_reset = {
+    // Start over on `loaded`'s endpoints and walls (same grid size)
+    let load_scenario = {
+        let maze_seed = maze_seed.clone();
+        let selected_cell = selected_cell.clone();
         let som_layer = som_layer.clone();
         let rover_layer = rover_layer.clone();
         let dob_layer = dob_layer.clone();
         let path_computed = path_computed.clone();
         let is_animating = is_animating.clone();
-        let show_help = show_help.clone();
         let journey_stats = journey_stats.clone();
+        let journey_phase = journey_phase.clone();
+        let event_log = event_log.clone();
+        let replay_frames = replay_frames.clone();
+        let replay_index = replay_index.clone();
         let visual_start = visual_start.clone();
         let trapped_alert = trapped_alert.clone();
+        let compute_cancelled = compute_cancelled.clone();
+        let plan_invalidated = plan_invalidated.clone();
 
-        Callback::from(move |_| {
-            web_sys::console::log_1(&"🔄 RESET: All layers cleared".into());
-
+        Callback::from(move |loaded: Scenario| {
             is_animating.set(false);
             path_computed.set(false);
-            show_help.set(true);
-            trapped_alert.set(false);
-            visual_start.set((5, 5));
+            plan_invalidated.set(false);
+            trapped_alert.set(None);
+            compute_cancelled.set(false);
+            visual_start.set(loaded.start);
+
+            let mut fresh_som = SomLayer::new();
+            // The threshold is a setting, not map state
+            fresh_som.occupancy_threshold = som_layer.occupancy_threshold;
+            fresh_som.set_initial_obstacles(loaded.obstacles.into_iter().collect());
+            som_layer.set(fresh_som);
+            maze_seed.set(None);
+            selected_cell.set(None);
+            // Keep the chosen algorithm and diagonals; they're preferences, not map state
+            let mut fresh_rover = RoverLayer::new(loaded.start, loaded.goal);
+            fresh_rover.algorithm = rover_layer.algorithm.clone();
+            fresh_rover.allow_diagonals = rover_layer.allow_diagonals;
+            fresh_rover.diagonal_cost = rover_layer.diagonal_cost;
+            fresh_rover.turn_penalty = rover_layer.turn_penalty;
+            fresh_rover.sensor_range = rover_layer.sensor_range;
+            fresh_rover.no_revisit = rover_layer.no_revisit;
+            rover_layer.set(fresh_rover);
+            let mut updated_dob = (*dob_layer).clone();
+            updated_dob.clear_all();
+            dob_layer.set(updated_dob);
+
+            journey_stats.set(JourneyStats::new());
+            event_log.set(EventLog::default());
+            replay_frames.set(Vec::new());
+            replay_index.set(None);
+            journey_phase.set(JourneyPhase::NotStarted);
+        })
+    };
 
-            som_layer.set(SomLayer::new());
-            rover_layer.set(RoverLayer::new((5, 5), (45, 25)));
-            dob_layer.set(DobLayer::new());
+    let on_reset = {
+        let load_scenario = load_scenario.clone();
+        let show_help = show_help.clone();
+        let scenario = scenario.clone();
 
-            journey_stats.set(JourneyStats {
-                start_time: None,
-                end_time: None,
-                total_distance: 0.0,
-                reroute_count: 0,
-                nodes_visited: 0,
-                obstacles_detected: 0,
-                path_efficiency: 100.0,
+        Callback::from(move |_| {
+            debug_log!("🔄 RESET: All layers cleared");
+            show_help.set(true);
+            load_scenario.emit(Scenario {
+                obstacles: Vec::new(),
+                ..(*scenario).clone()
             });
         })
     };
 
+    // A pasted map of another size needs a fresh grid, so reload with it as a link
+    let on_import_map = {
+        let rover_layer = rover_layer.clone();
+
+        Callback::from(move |mut imported: Scenario| {
+            debug_log!(
+                "📋 Imported {}x{} ASCII map ({} walls)",
+                imported.width,
+                imported.height,
+                imported.obstacles.len()
+            );
+            if (imported.width, imported.height) == (grid_width, grid_height) {
+                load_scenario.emit(imported);
+                return;
+            }
+            imported.algorithm = parse_algorithm(&rover_layer.algorithm).map(|(a, _)| a);
+            if let Some(window) = window() {
+                let _ = window.location().set_search(&imported.to_query());
+            }
+        })
+    };
+
     let on_restart = {
         let rover_layer = rover_layer.clone();
         let dob_layer = dob_layer.clone();
@@ -1225,11 +2024,15 @@ pub fn main_app() -> Html {
         let is_animating = is_animating.clone();
         let visual_start = visual_start.clone();
         let journey_stats = journey_stats.clone();
+        let journey_phase = journey_phase.clone();
+        let event_log = event_log.clone();
+        let replay_frames = replay_frames.clone();
+        let replay_index = replay_index.clone();
         let trapped_alert = trapped_alert.clone();
 
         Callback::from(move |_| {
             is_animating.set(false);
-            trapped_alert.set(false);
+            trapped_alert.set(None);
             path_computed.set(false);
 
             let start_pos = *visual_start;
@@ -1238,21 +2041,19 @@ pub fn main_app() -> Html {
             updated_rover.reset_to_start(start_pos);
             rover_layer.set(updated_rover);
 
-            dob_layer.set(DobLayer::new());
+            let mut updated_dob = (*dob_layer).clone();
+            updated_dob.clear_all();
+            dob_layer.set(updated_dob);
 
             let mut updated_som = (*som_layer).clone();
             updated_som.clear_converted_dob_obstacles();
             som_layer.set(updated_som);
 
-            journey_stats.set(JourneyStats {
-                start_time: None,
-                end_time: None,
-                total_distance: 0.0,
-                reroute_count: 0,
-                nodes_visited: 0,
-                obstacles_detected: 0,
-                path_efficiency: 100.0,
-            });
+            journey_stats.set(JourneyStats::new());
+            event_log.set(EventLog::default());
+            replay_frames.set(Vec::new());
+            replay_index.set(None);
+            journey_phase.set(JourneyPhase::NotStarted);
         })
     };
 
@@ -1267,6 +2068,7 @@ pub fn main_app() -> Html {
         let is_dark = is_dark.clone();
         Callback::from(move |_| {
             is_dark.set(!*is_dark);
+            prefs::save(prefs::DARK_MODE, !*is_dark);
         })
     };
 
@@ -1277,27 +2079,378 @@ pub fn main_app() -> Html {
         })
     };
 
+    let on_replay_seek = {
+        let replay_index = replay_index.clone();
+        Callback::from(move |index: Option<usize>| {
+            replay_index.set(index);
+        })
+    };
+
+    // A link reproduces the map only while it is still exactly the seeded maze
+    let shareable_seed = *use_memo(
+        (
+            *maze_seed,
+            som_layer.original_static_obstacles.clone(),
+            rover_layer.start_position,
+            rover_layer.goal_position,
+        ),
+        move |(seed, walls, start, goal)| {
+            seed.filter(|&seed| {
+                let maze = generate_maze(grid_width, grid_height, seed, &[*start, *goal]);
+                maze.walls().collect::<HashSet<_>>() == *walls
+            })
+        },
+    );
+    let share_query = shareable_seed.map(|seed| {
+        Scenario {
+            width: grid_width,
+            height: grid_height,
+            start: rover_layer.start_position,
+            goal: rover_layer.goal_position,
+            algorithm: parse_algorithm(&rover_layer.algorithm).map(|(algorithm, _)| algorithm),
+            maze_seed: Some(seed),
+            obstacles: Vec::new(),
+        }
+        .to_query()
+    });
+
     let current_som = (*som_layer).clone();
     let current_rover = (*rover_layer).clone();
     let current_dob = (*dob_layer).clone();
+    let replay_frame = if *is_animating {
+        None
+    } else {
+        replay_index.and_then(|i| replay_frames.get(i).cloned())
+    };
 
     // CRITICAL: DOB Layer separation - rover system NEVER sees amber DOBs
     // Only converted DOBs (blue) are passed via SOM layer for pathfinding
-    let display_rover_state = crate::rover::RoverState {
+    let mut display_rover_state = crate::rover::RoverState {
         pos: current_rover.current_position,
         goal: current_rover.goal_position,
         path: current_rover.planned_path.clone(),
-        obstacles: current_som.original_static_obstacles.clone(), 
-        dynamic_obstacles: Vec::new(),         
+        obstacles: current_som.original_static_obstacles.clone(),
+        dynamic_obstacles: Vec::new(),
         converted_obstacles: current_dob.get_blue_dobs_for_display(),
         algorithm: current_rover.algorithm.clone(),
+        tie_break: crate::pathfinding::TieBreak::CrossProduct,
+        allow_diagonals: current_rover.allow_diagonals,
+        diagonal_cost: current_rover.diagonal_cost,
+        turn_penalty: current_rover.turn_penalty,
         speed: *current_speed,
-        width: 50,
-        height: 30,
+        width: grid_width,
+        height: grid_height,
+        terrain_costs: current_rover.terrain_costs.clone(),
+        thin_walls: current_rover.thin_walls.clone(),
+    };
+    let display_goals: Vec<(Coord, bool)> = if current_rover.goals.is_empty() {
+        Vec::new()
+    } else {
+        std::iter::once(current_rover.goal_position)
+            .chain(current_rover.goals.iter().copied())
+            .map(|goal| (goal, current_rover.reached_goals.contains(&goal)))
+            .collect()
+    };
+    let mut display_traveled_path = current_rover.traveled_path.clone();
+    let mut display_reroute_indices = current_rover.reroute_indices.clone();
+    let mut display_amber_dobs = current_dob.get_amber_dobs_for_display();
+
+    let mut display_heading = current_rover.heading;
+
+    if let Some(frame) = replay_frame {
+        display_rover_state.pos = frame.position;
+        display_rover_state.path = frame.planned_path;
+        display_rover_state.converted_obstacles = frame.converted_obstacles;
+        display_traveled_path.truncate(frame.traveled_len);
+        display_heading = Direction::of_last_step(&display_traveled_path).unwrap_or_default();
+        display_reroute_indices.retain(|&i| i < frame.traveled_len);
+        display_amber_dobs = frame.amber_dobs;
+    }
+
+    // Drop amber and blue DOBs only; static map, progress and stats stay
+    let on_clear_dobs = {
+        let som_layer = som_layer.clone();
+        let rover_layer = rover_layer.clone();
+        let dob_layer = dob_layer.clone();
+        let planner = planner.clone();
+        let path_computed = path_computed.clone();
+        let trapped_alert = trapped_alert.clone();
+
+        Callback::from(move |_| {
+            let mut updated_dob = (*dob_layer).clone();
+            updated_dob.clear_all();
+            dob_layer.set(updated_dob);
+
+            let mut updated_som = (*som_layer).clone();
+            updated_som.clear_converted_dob_obstacles();
+
+            let mut updated_rover = (*rover_layer).clone();
+            if !updated_rover.planned_path.is_empty() {
+                let outcome = updated_rover.compute_path_from_som(
+                    updated_som.get_complete_obstacle_map(),
+                    &mut planner.borrow_mut(),
+                );
+                let mid_journey_at = (updated_rover.traveled_path.len() > 1)
+                    .then_some(updated_rover.current_position);
+                trapped_alert.set(
+                    outcome
+                        .err()
+                        .map(|error| TrappedReason::from_error(error, mid_journey_at)),
+                );
+                path_computed.set(outcome.is_ok());
+                rover_layer.set(updated_rover);
+            }
+
+            som_layer.set(updated_som);
+            debug_log!("🧹 Cleared all DOBs; static obstacles kept");
+        })
+    };
+
+    let on_turn_penalty_change = {
+        let som_layer = som_layer.clone();
+        let rover_layer = rover_layer.clone();
+        let planner = planner.clone();
+        let path_computed = path_computed.clone();
+        let trapped_alert = trapped_alert.clone();
+
+        Callback::from(move |penalty: f64| {
+            let mut updated_rover = (*rover_layer).clone();
+            updated_rover.turn_penalty = penalty;
+
+            if !updated_rover.planned_path.is_empty() {
+                let outcome = updated_rover.compute_path_from_som(
+                    som_layer.get_complete_obstacle_map(),
+                    &mut planner.borrow_mut(),
+                );
+                trapped_alert.set(outcome.err().map(|e| TrappedReason::from_error(e, None)));
+                path_computed.set(outcome.is_ok());
+            }
+            rover_layer.set(updated_rover);
+        })
+    };
+
+    let on_diagonal_cost_change = {
+        let som_layer = som_layer.clone();
+        let rover_layer = rover_layer.clone();
+        let planner = planner.clone();
+        let path_computed = path_computed.clone();
+        let trapped_alert = trapped_alert.clone();
+
+        Callback::from(move |cost: f64| {
+            let mut updated_rover = (*rover_layer).clone();
+            updated_rover.diagonal_cost = cost;
+
+            if !updated_rover.planned_path.is_empty() {
+                let outcome = updated_rover.compute_path_from_som(
+                    som_layer.get_complete_obstacle_map(),
+                    &mut planner.borrow_mut(),
+                );
+                trapped_alert.set(outcome.err().map(|e| TrappedReason::from_error(e, None)));
+                path_computed.set(outcome.is_ok());
+            }
+            rover_layer.set(updated_rover);
+        })
+    };
+
+    let on_toggle_sensor_limited = {
+        let som_layer = som_layer.clone();
+        let rover_layer = rover_layer.clone();
+        let dob_layer = dob_layer.clone();
+        let planner = planner.clone();
+        let path_computed = path_computed.clone();
+        let trapped_alert = trapped_alert.clone();
+
+        Callback::from(move |_| {
+            let mut updated_rover = (*rover_layer).clone();
+            updated_rover.sensor_range = match updated_rover.sensor_range {
+                Some(_) => None,
+                None => Some(dob_layer.sensor),
+            };
+
+            if !updated_rover.planned_path.is_empty() {
+                let outcome = updated_rover.compute_path_from_som(
+                    som_layer.get_complete_obstacle_map(),
+                    &mut planner.borrow_mut(),
+                );
+                trapped_alert.set(outcome.err().map(|e| TrappedReason::from_error(e, None)));
+                path_computed.set(outcome.is_ok());
+            }
+            rover_layer.set(updated_rover);
+        })
+    };
+
+    let on_toggle_no_revisit = {
+        let som_layer = som_layer.clone();
+        let rover_layer = rover_layer.clone();
+        let planner = planner.clone();
+        let path_computed = path_computed.clone();
+        let trapped_alert = trapped_alert.clone();
+
+        Callback::from(move |_| {
+            let mut updated_rover = (*rover_layer).clone();
+            updated_rover.no_revisit = !updated_rover.no_revisit;
+
+            if !updated_rover.planned_path.is_empty() {
+                let outcome = updated_rover.compute_path_from_som(
+                    som_layer.get_complete_obstacle_map(),
+                    &mut planner.borrow_mut(),
+                );
+                trapped_alert.set(outcome.err().map(|e| TrappedReason::from_error(e, None)));
+                path_computed.set(outcome.is_ok());
+            }
+            rover_layer.set(updated_rover);
+        })
+    };
+
+    // Sensor shape and range only; movement connectivity is `allow_diagonals`
+    let on_sensor_change = {
+        let dob_layer = dob_layer.clone();
+        let rover_layer = rover_layer.clone();
+        Callback::from(move |sensor: Sensor| {
+            let mut updated_dob = (*dob_layer).clone();
+            updated_dob.sensor = sensor;
+            dob_layer.set(updated_dob);
+
+            // Sensor-limited planning sees the same footprint the DOB sensor does
+            if rover_layer.sensor_range.is_some() {
+                let mut updated_rover = (*rover_layer).clone();
+                updated_rover.sensor_range = Some(sensor);
+                rover_layer.set(updated_rover);
+            }
+        })
+    };
+
+    // Turning wandering on draws a fresh seed; typing one replays its walk
+    let on_toggle_wander = {
+        let dob_layer = dob_layer.clone();
+        Callback::from(move |_| {
+            let mut updated_dob = (*dob_layer).clone();
+            updated_dob.wander = !updated_dob.wander;
+            if updated_dob.wander {
+                updated_dob.wander_seed = fresh_seed();
+                updated_dob.reseed_wander();
+            }
+            dob_layer.set(updated_dob);
+        })
+    };
+
+    let on_wander_seed_change = {
+        let dob_layer = dob_layer.clone();
+        Callback::from(move |seed: u64| {
+            let mut updated_dob = (*dob_layer).clone();
+            updated_dob.wander_seed = seed;
+            updated_dob.reseed_wander();
+            dob_layer.set(updated_dob);
+        })
+    };
+
+    let on_conversion_policy_change = {
+        let dob_layer = dob_layer.clone();
+        Callback::from(move |policy: ConversionPolicy| {
+            let mut updated_dob = (*dob_layer).clone();
+            updated_dob.policy = policy;
+            dob_layer.set(updated_dob);
+        })
+    };
+
+    let on_brush_change = {
+        let brush_size = brush_size.clone();
+        Callback::from(move |size: usize| brush_size.set(size))
+    };
+
+    let on_frame_rate_change = {
+        let frame_rate = frame_rate.clone();
+        Callback::from(move |fps: u32| {
+            frame_rate.set(fps);
+            prefs::save(prefs::FRAME_RATE, fps);
+        })
+    };
+
+    let on_color_scheme_change = {
+        let color_scheme = color_scheme.clone();
+        Callback::from(move |scheme: ColorScheme| {
+            color_scheme.set(scheme);
+            prefs::save(prefs::PALETTE, scheme.label());
+        })
+    };
+
+    let on_toggle_cost_field = {
+        let show_cost_field = show_cost_field.clone();
+        Callback::from(move |_| show_cost_field.set(!*show_cost_field))
+    };
+
+    // Turning capture on starts a fresh recording
+    let on_toggle_capture_frames = {
+        let capture_frames = capture_frames.clone();
+        let captured_frames = captured_frames.clone();
+        let captured_count = captured_count.clone();
+        Callback::from(move |_| {
+            if !*capture_frames {
+                captured_frames.borrow_mut().clear();
+                captured_count.set(0);
+            }
+            capture_frames.set(!*capture_frames);
+        })
+    };
+
+    let on_frame = {
+        let captured_frames = captured_frames.clone();
+        let captured_count = captured_count.clone();
+        Callback::from(move |url: String| {
+            let mut frames = captured_frames.borrow_mut();
+            if frames.len() >= MAX_FRAMES {
+                return;
+            }
+            match decode_png_data_url(&url) {
+                Some(png) => {
+                    frames.push(png);
+                    captured_count.set(frames.len());
+                }
+                None => debug_log!("🎞️ Skipped a frame that wasn't a PNG data URL"),
+            }
+        })
+    };
+
+    let on_download_frames = {
+        let captured_frames = captured_frames.clone();
+        Callback::from(move |_| {
+            let frames = captured_frames.borrow();
+            let files: Vec<(String, &[u8])> = frames
+                .iter()
+                .enumerate()
+                .map(|(i, png)| (frame_name(i), png.as_slice()))
+                .collect();
+            download_bytes("scout-frames.zip", "application/zip", &zip_stored(&files));
+        })
+    };
+
+    let on_toggle_what_if = {
+        let what_if = what_if.clone();
+        Callback::from(move |_| {
+            what_if.set(!*what_if);
+            prefs::save(prefs::WHAT_IF, !*what_if);
+        })
+    };
+
+    let on_hover = {
+        let hovered_cell = hovered_cell.clone();
+        Callback::from(move |cell: Option<Coord>| hovered_cell.set(cell))
+    };
+    let display_cost_field = if *show_cost_field {
+        current_rover.cost_field.clone()
+    } else {
+        None
     };
 
     let visual_start_pos = *visual_start;
     let stats = (*journey_stats).clone();
+    let blocked_cells: Rc<HashSet<Coord>> = Rc::new(
+        som_layer
+            .get_complete_obstacle_map()
+            .into_iter()
+            .chain(dob_layer.amber_dobs.iter().copied())
+            .collect(),
+    );
 
     html! {
         <>
@@ -1305,17 +2458,74 @@ pub fn main_app() -> Html {
                 <div class={format!("main-content {}", if *is_panel_minimized { "panel-minimized" } else { "" })}>
                     <Controls
                         on_compute={on_compute}
+                        on_cancel_compute={on_cancel_compute}
                         on_start_journey={on_start_journey}
                         on_pause={on_pause}
+                        on_step_back={on_step_back}
+                        can_step_back={current_rover.traveled_path.len() > 1}
                         on_reset={on_reset}
                         on_restart={on_restart}
-                        on_algo_change={Callback::noop()}
+                        on_clear_dobs={on_clear_dobs}
+                        on_algo_change={on_algo_change}
                         on_speed_change={on_speed_change}
                         on_toggle_panel={on_toggle_panel}
-                        current_algorithm={"D*-Lite".to_string()} 
+                        current_algorithm={current_rover.algorithm.clone()}
                         current_speed={*current_speed}
                         is_computing={*is_computing}
                         is_animating={*is_animating}
+                        is_paused={*journey_phase == JourneyPhase::Paused}
+                        replay_len={replay_frames.len()}
+                        replay_index={*replay_index}
+                        on_replay_seek={on_replay_seek}
+                        sensor={dob_layer.sensor}
+                        on_sensor_change={on_sensor_change}
+                        conversion_policy={dob_layer.policy}
+                        wander={dob_layer.wander}
+                        wander_seed={dob_layer.wander_seed}
+                        on_toggle_wander={on_toggle_wander}
+                        on_wander_seed_change={on_wander_seed_change}
+                        on_conversion_policy_change={on_conversion_policy_change}
+                        show_cost_field={*show_cost_field}
+                        on_toggle_cost_field={on_toggle_cost_field}
+                        what_if={*what_if}
+                        on_toggle_what_if={on_toggle_what_if}
+                        capture_frames={*capture_frames}
+                        on_toggle_capture_frames={on_toggle_capture_frames}
+                        captured_frames={*captured_count}
+                        on_download_frames={on_download_frames}
+                        allow_diagonals={current_rover.allow_diagonals}
+                        diagonal_cost={current_rover.diagonal_cost}
+                        on_diagonal_cost_change={on_diagonal_cost_change}
+                        turn_penalty={current_rover.turn_penalty}
+                        on_turn_penalty_change={on_turn_penalty_change}
+                        sensor_limited={current_rover.sensor_range.is_some()}
+                        on_toggle_sensor_limited={on_toggle_sensor_limited}
+                        no_revisit={current_rover.no_revisit}
+                        on_toggle_no_revisit={on_toggle_no_revisit}
+                        start={visual_start_pos}
+                        goal={current_rover.goal_position}
+                        grid_size={(grid_width, grid_height)}
+                        blocked_cells={blocked_cells}
+                        on_start_drag={on_start_drag.clone()}
+                        on_goal_drag={on_goal_drag.clone()}
+                        maze_seed={shareable_seed}
+                        share_query={share_query}
+                        on_generate_maze={on_generate_maze}
+                        selected_cell={*selected_cell}
+                        selected_cost={selected_cell.and_then(|cell| current_rover.terrain_costs.get(&cell).copied()).unwrap_or(1.0)}
+                        on_terrain_cost_change={on_terrain_cost_change}
+                        selected_occupancy={selected_cell.and_then(|cell| som_layer.occupancy.get(&cell).copied()).unwrap_or(0.0)}
+                        on_occupancy_change={on_occupancy_change}
+                        occupancy_threshold={som_layer.occupancy_threshold}
+                        on_occupancy_threshold_change={on_occupancy_threshold_change}
+                        on_deselect_cell={on_deselect_cell}
+                        on_import_map={on_import_map}
+                        brush_size={*brush_size}
+                        on_brush_change={on_brush_change}
+                        frame_rate={*frame_rate}
+                        on_frame_rate_change={on_frame_rate_change}
+                        color_scheme={*color_scheme}
+                        on_color_scheme_change={on_color_scheme_change}
                         path_computed={*path_computed}
                         is_panel_minimized={*is_panel_minimized}
                     />
@@ -1325,32 +2535,112 @@ pub fn main_app() -> Html {
                             height={grid_height}
                             rover_state={display_rover_state}
                             visual_start={visual_start_pos}
-                            traveled_path={current_rover.traveled_path.clone()}  
-                            amber_dobs={current_dob.get_amber_dobs_for_display()}
+                            traveled_path={display_traveled_path}
+                            amber_dobs={display_amber_dobs}
+                            sensor={dob_layer.sensor}
+                            heading={display_heading}
+                            cost_field={display_cost_field}
+                            reroutes={stats.reroutes.clone()}
+                            reroute_indices={display_reroute_indices}
+                            preview_path={(*what_if_path).clone()}
+                            optimal_path={(*optimal_route).clone().unwrap_or_default()}
+                            plan_stale={*journey_phase == JourneyPhase::NotStarted && !*path_computed}
+                            on_hover={on_hover}
+                            goals={display_goals}
+                            on_goal_toggle={on_goal_toggle}
+                            selected_cell={*selected_cell}
+                            on_cell_select={on_cell_select}
+                            edit_thin_walls={!journey_phase.is_mid_journey()}
+                            on_thin_wall_toggle={on_thin_wall_toggle}
+                            panel_minimized={*is_panel_minimized}
+                            occupancy={som_layer.occupancy.clone()}
+                            occupancy_threshold={som_layer.occupancy_threshold}
+                            dead_end_cells={if *journey_phase == JourneyPhase::NotStarted { (*dead_end_cells).clone() } else { HashSet::new() }}
+                            celebration_started={*goal_celebration}
+                            is_dark={*is_dark}
+                            animating={*journey_phase == JourneyPhase::Running || goal_celebration.is_some()}
+                            frame_ms={1000 / *frame_rate}
+                            color_scheme={*color_scheme}
                             on_mouse_down={on_mouse_down}
                             on_mouse_move={on_mouse_move}
                             on_mouse_up={on_mouse_up}
+                            on_erase={on_erase}
                             on_start_drag={on_start_drag}
                             on_goal_drag={on_goal_drag}
+                            capture_frames={*capture_frames && *captured_count < MAX_FRAMES}
+                            on_frame={on_frame}
                         />
                     </div>
                     {if *show_help {
                         html! {
-                            <HelpBubble on_close={on_close_help} />
+                            <HelpBubble
+                                tips={help_sections(&current_rover, &dob_layer, *brush_size)}
+                                on_close={on_close_help}
+                            />
                         }
                     } else {
                         html! {}
                     }}
                 </div>
 
-                {if *trapped_alert {
+                {if *compute_cancelled {
+                    html! {
+                        <div class="trapped-alert cancelled-alert" role="status">
+                            <span class="alert-icon" aria-hidden="true">{ "🛑" }</span>
+                            <span class="alert-text">{ "Search cancelled — no path was computed." }</span>
+                            <button
+                                class="alert-close"
+                                aria-label="Dismiss"
+                                onclick={Callback::from(move |_| compute_cancelled.set(false))}
+                            >
+                                { "×" }
+                            </button>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }}
+
+                {if let Some(cycle_ms) = *slow_cycle_warning {
+                    html! {
+                        <div class="trapped-alert slow-cycle-alert" role="status">
+                            <span class="alert-icon" aria-hidden="true">{ "🐢" }</span>
+                            <span class="alert-text">
+                                { format!(
+                                    "A step took {:.0}ms to compute. A smaller grid or a faster algorithm (A* or Greedy Best-First) will keep the rover moving smoothly.",
+                                    cycle_ms
+                                ) }
+                            </span>
+                            <button
+                                class="alert-close"
+                                aria-label="Dismiss"
+                                onclick={Callback::from(move |_| slow_cycle_warning.set(None))}
+                            >
+                                { "×" }
+                            </button>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                }}
+
+                {if let Some(reason) = *trapped_alert {
                     html! {
                         <div class="trapped-alert">
                             <span class="alert-icon">{ "⚠️" }</span>
-                            <span class="alert-text">{ "Rover is blocked! Direct path to goal cannot be achieved." }</span>
+                            <div class="alert-body">
+                                <span class="alert-text">{ reason.message() }</span>
+                                {if let Some(diagnostics) = *trapped_diagnostics {
+                                    html! {
+                                        <span class="alert-detail">{ diagnostics.message() }</span>
+                                    }
+                                } else {
+                                    html! {}
+                                }}
+                            </div>
                             <button
                                 class="alert-close"
-                                onclick={Callback::from(move |_| trapped_alert.set(false))}
+                                onclick={Callback::from(move |_| trapped_alert.set(None))}
                             >
                                 { "×" }
                             </button>
@@ -1360,33 +2650,62 @@ pub fn main_app() -> Html {
                     html! {}
                 }}
 
+                <EventLogPanel events={event_log.events().to_vec()} />
+
                 <div class="stats-bar">
                     <div class="stats-content">
                         {
-                            if let (Some(start), Some(end)) = (stats.start_time, stats.end_time) {
-                                let duration = (end - start) / 1000.0;
+                            if stats.start_time.is_some() && stats.end_time.is_some() {
+                                let duration = stats.elapsed_ms(js_sys::Date::now()) / 1000.0;
                                 let avg_speed = if duration > 0.0 { stats.nodes_visited as f64 / duration } else { 0.0 };
                                 html! {
                                     <div class="stats-complete">
                                         <span class="stat-item">{ "~ Complete ~" }</span>
                                         <span class="stat-item">{ format!("⏱️ {:.1}s", duration) }</span>
                                         <span class="stat-item">{ format!("📏 {:.1} cells", stats.total_distance) }</span>
-                                        <span class="stat-item">{ format!("🔄 {} reroutes", stats.reroute_count) }</span>
+                                        <span class="stat-item" title={stats.reroute_summary()}>{ format!("🔄 {} reroutes", stats.reroutes.len()) }</span>
                                         <span class="stat-item">{ format!("🚧 {} obstacles detected", stats.obstacles_detected) }</span>
                                         <span class="stat-item">{ format!("📍 {} nodes", stats.nodes_visited) }</span>
                                         <span class="stat-item">{ format!("⚡ {:.1} n/s", avg_speed) }</span>
-                                        <span class="stat-item">{ format!("📊 {:.0}% efficiency", stats.path_efficiency) }</span>
+                                        <span class="stat-item" title="Straight-line distance ÷ distance traveled">{ format!("📊 {:.0}% efficiency", stats.path_efficiency) }</span>
+                                        {if let Some(route) = &*optimal_route {
+                                            html! { <span class="stat-item" title="Distance traveled vs the shortest route over the final known map (dashed)">{ format!("🎯 {:.0}% longer than optimal", stats.detour_percent(path_length(route))) }</span> }
+                                        } else {
+                                            html! {}
+                                        }}
+                                        <span class="stat-item" title="Longest planned path the rover committed to, often right after a big reroute">{ format!("🛤️ {} cells peak plan", stats.max_planned_len) }</span>
+                                    </div>
+                                }
+                            } else if *journey_phase == JourneyPhase::Paused {
+                                let elapsed = stats.elapsed_ms(js_sys::Date::now()) / 1000.0;
+                                html! {
+                                    <div class="stats-traveling">
+                                        <span class="stat-item">{ "|| Paused ||" }</span>
+                                        { progress_stat(stats.progress(current_rover.remaining_planned_distance())) }
+                                        <span class="stat-item">{ format!("⏱️ {:.1}s", elapsed) }</span>
+                                        <span class="stat-item">{ format!("📏 {:.1} cells", stats.total_distance) }</span>
+                                        <span class="stat-item" title={stats.reroute_summary()}>{ format!("🔄 {} reroutes", stats.reroutes.len()) }</span>
+                                        <span class="stat-item">{ format!("📍 {} nodes", stats.nodes_visited) }</span>
+                                        <span class="stat-item">{ "Click 'Resume' to continue" }</span>
                                     </div>
                                 }
                             } else if stats.start_time.is_some() && *is_animating {
-                                let elapsed = (js_sys::Date::now() - stats.start_time.unwrap()) / 1000.0;
+                                let elapsed = stats.elapsed_ms(js_sys::Date::now()) / 1000.0;
                                 html! {
                                     <div class="stats-traveling">
                                         <span class="stat-item">{ ">> Traveling >>" }</span>
+                                        { progress_stat(stats.progress(current_rover.remaining_planned_distance())) }
                                         <span class="stat-item">{ format!("⏱️ {:.1}s", elapsed) }</span>
                                         <span class="stat-item">{ format!("📏 {:.1} cells", stats.total_distance) }</span>
-                                        <span class="stat-item">{ format!("🔄 {} reroutes", stats.reroute_count) }</span>
+                                        <span class="stat-item" title={stats.reroute_summary()}>{ format!("🔄 {} reroutes", stats.reroutes.len()) }</span>
                                         <span class="stat-item">{ format!("🚧 {} detected", stats.obstacles_detected) }</span>
+                                        <span class="stat-item">{ format!("🔎 {} expanded", current_rover.nodes_expanded()) }</span>
+                                        {if let Some(origin) = current_rover.plan_origin_label() {
+                                            html! { <span class="stat-item" title="Method that produced the current plan">{ format!("🧩 via {}", origin) }</span> }
+                                        } else {
+                                            html! {}
+                                        }}
+                                        <span class="stat-item" title={format!("{}ms per step", step_delay_ms(*current_speed))}>{ format!("⏲️ {:.1} cells/s", cells_per_second(*current_speed)) }</span>
                                         <span class="stat-item">{ format!("📍 {} nodes", stats.nodes_visited) }</span>
                                     </div>
                                 }
@@ -1395,7 +2714,42 @@ pub fn main_app() -> Html {
                                     <div class="stats-idle">
                                         <span class="stat-item">{ "🎯 Ready!" }</span>
                                         <span class="stat-item">{ format!("🧭 {}", current_rover.algorithm) }</span>
-                                        <span class="stat-item">{ format!("🏃 Speed: {}", *current_speed) }</span>
+                                        <span class="stat-item" title={format!("{}ms per step", step_delay_ms(*current_speed))}>{ format!("🏃 Speed: {} ({:.1} cells/s)", *current_speed, cells_per_second(*current_speed)) }</span>
+                                        <span class="stat-item" title="Obstacle cells ÷ grid area">{ format!("🧱 {:.1}% density", som_layer.density_percent(grid_width, grid_height)) }</span>
+                                        <span class="stat-item" title="Share of free cells the start can reach; below 100% the walls split the map into pockets">{ format!("🌐 {:.0}% reachable", (*reachable_share * 100.0).floor()) }</span>
+                                        {if *path_computed && !current_rover.planned_path.is_empty() {
+                                            html! {
+                                                <>
+                                                    <span class="stat-item">{ format!("🛤️ {} cells", current_rover.planned_path.len()) }</span>
+                                                    {if let Some(origin) = current_rover.plan_origin_label() {
+                                                        html! { <span class="stat-item" title="Method that produced the current plan">{ format!("🧩 via {}", origin) }</span> }
+                                                    } else {
+                                                        html! {}
+                                                    }}
+                                                </>
+                                            }
+                                        } else if *plan_invalidated || !current_rover.planned_path.is_empty() {
+                                            html! { <span class="stat-item stat-warning" title="The map changed since the last plan was found">{ "⚠️ Map changed — Find Path to recompute" }</span> }
+                                        } else {
+                                            html! {}
+                                        }}
+                                        {if let Some(result) = &current_rover.last_result {
+                                            html! {
+                                                <>
+                                                    <span class="stat-item">{ format!("📐 Cost {:.1}", result.cost) }</span>
+                                                    {if current_rover.algorithm == "Greedy Best-First" {
+                                                        html! { <span class="stat-item stat-warning" title="Greedy best-first ignores the distance already traveled, so a shorter path may exist">{ "⚠️ May be suboptimal" }</span> }
+                                                    } else {
+                                                        html! {}
+                                                    }}
+                                                    <span class="stat-item">{ format!("↪️ {} turns", count_turns(&result.path)) }</span>
+                                                    <span class="stat-item">{ format!("🔎 {} expanded", result.nodes_expanded) }</span>
+                                                    <span class="stat-item">{ format!("⏱️ {:.1}ms", result.elapsed_ms) }</span>
+                                                </>
+                                            }
+                                        } else {
+                                            html! {}
+                                        }}
                                         <span class="stat-item">{ "Click 'Find Path' → 'Start Journey'" }</span>
                                     </div>
                                 }
@@ -1405,7 +2759,8 @@ pub fn main_app() -> Html {
                     <button
                         class="dark-mode-toggle-footer"
                         onclick={on_toggle_dark}
-                        aria_label="Toggle dark mode"
+                        aria-label="Toggle dark mode"
+                        aria-pressed={(*is_dark).to_string()}
                     >
                         { if *is_dark { "☀️" } else { "🌙" } }
                     </button>
@@ -1414,3 +2769,114 @@ pub fn main_app() -> Html {
         </>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn efficiency_is_straight_line_over_distance_traveled() {
+        let mut clear = JourneyStats::new();
+        clear.total_distance = 10.0;
+        clear.finish(0.0, (0, 0), (10, 0));
+        assert_eq!(clear.path_efficiency, 100.0);
+
+        // Around a wall: twice as far as the straight line
+        let mut obstructed = JourneyStats::new();
+        obstructed.total_distance = 20.0;
+        obstructed.finish(0.0, (0, 0), (10, 0));
+        assert_eq!(obstructed.path_efficiency, 50.0);
+
+        // A diagonal trip taken as a staircase scores √2 / 2
+        let mut staircase = JourneyStats::new();
+        staircase.total_distance = 10.0;
+        staircase.finish(0.0, (0, 0), (5, 5));
+        assert!((staircase.path_efficiency - 50.0 * std::f64::consts::SQRT_2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn a_paused_edit_places_a_dob_and_the_resumed_rover_reroutes() {
+        assert!(JourneyPhase::Paused.is_mid_journey());
+        assert!(JourneyPhase::Running.is_mid_journey());
+        assert!(!JourneyPhase::NotStarted.is_mid_journey());
+        assert!(!JourneyPhase::Finished.is_mid_journey());
+
+        let mut som = SomLayer::new();
+        let mut rover = RoverLayer::new((0, 0), (9, 0));
+        let mut planner = Rover::new(10, 10);
+        // Any wall keeps the planner off the straight-line shortcut
+        som.set_initial_obstacles([(5, 1)].into_iter().collect());
+        rover
+            .compute_path_from_som(som.get_complete_obstacle_map(), &mut planner)
+            .unwrap();
+        rover.execute_movement_step(&som).unwrap();
+        rover.execute_movement_step(&som).unwrap();
+
+        // Paused at (2, 0): a click on the plan is a DOB, not a wall
+        let mut dobs = DobLayer::new();
+        dobs.add_dob((4, 0), &som, &rover).unwrap();
+        assert!(!som.is_cell_occupied((4, 0)));
+
+        // Resumed: the sensor picks it up and the new plan goes around it
+        for dob in dobs.check_proximity_and_convert(
+            rover.current_position,
+            rover.heading,
+            &rover.planned_path,
+        ) {
+            som.add_converted_dob(dob);
+        }
+        assert!(som.is_cell_occupied((4, 0)));
+        rover
+            .compute_path_from_som(som.get_complete_obstacle_map(), &mut planner)
+            .unwrap();
+        assert!(!rover.planned_path.contains(&(4, 0)));
+        assert_eq!(rover.planned_path.last(), Some(&(9, 0)));
+        assert_eq!(rover.traveled_path, vec![(0, 0), (1, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn a_stroke_keeps_its_first_mode_across_cells() {
+        let mut stroke = Stroke {
+            adding: true,
+            last_cell: (2, 2),
+        };
+        assert_eq!(stroke.enter((2, 2)), None);
+        assert_eq!(stroke.enter((3, 2)), Some(true));
+        assert_eq!(stroke.enter((3, 2)), None);
+        // Coming back over the first cell still adds instead of toggling
+        assert_eq!(stroke.enter((2, 2)), Some(true));
+
+        let mut erasing = Stroke {
+            adding: false,
+            last_cell: (0, 0),
+        };
+        assert_eq!(erasing.enter((0, 1)), Some(false));
+        assert_eq!(erasing.enter((0, 0)), Some(false));
+    }
+
+    #[test]
+    fn the_brush_footprint_is_clipped_to_the_grid() {
+        assert_eq!(brush_footprint((4, 4), 1, 10, 10), vec![(4, 4)]);
+        assert_eq!(brush_footprint((4, 4), 3, 10, 10).len(), 9);
+        let corner = brush_footprint((0, 0), 3, 10, 10);
+        assert_eq!(corner, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+        assert_eq!(brush_footprint((9, 9), 5, 10, 10).len(), 9);
+    }
+
+    #[test]
+    fn only_an_unbroken_run_of_reroutes_is_a_loop() {
+        let mut stats = JourneyStats::new();
+        for _ in 1..REROUTE_LOOP_LIMIT {
+            assert_eq!(stats.note_stalled_cycle(), None);
+        }
+        // A step in between starts the count again
+        stats.cycles_without_move = 0;
+        for _ in 1..REROUTE_LOOP_LIMIT {
+            assert_eq!(stats.note_stalled_cycle(), None);
+        }
+        assert_eq!(
+            stats.note_stalled_cycle(),
+            Some(TrappedReason::RerouteLoop(REROUTE_LOOP_LIMIT))
+        );
+    }
+}
@@ -2,6 +2,7 @@
 
 pub mod canvas;
 pub mod controls;
+pub mod event_log_panel;
 pub mod help_bubble;
 pub mod main_app;
 
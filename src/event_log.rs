@@ -0,0 +1,137 @@
+// src/event_log.rs
+//
+// Typed journey events collected by `MainApp` and rendered in the log panel.
+// The most recent log is mirrored into a thread-local so `dump_event_log`
+// can hand it to JS for bug reports.
+
+use std::cell::RefCell;
+
+use serde::Serialize;
+
+use crate::pathfinding::Coord;
+
+#[derive(Clone, PartialEq, Debug, Serialize)]
+#[serde(tag = "kind")]
+pub enum JourneyEvent {
+    ObstacleDetected {
+        coord: Coord,
+    },
+    Rerouted {
+        at: Coord,
+        planned_len: usize,
+        nodes_expanded: usize,
+    },
+    Moved {
+        from: Coord,
+        to: Coord,
+    },
+    FallbackPlan {
+        at: Coord,
+        method: &'static str,
+    },
+    Trapped {
+        at: Coord,
+    },
+    GoalReached {
+        at: Coord,
+    },
+    GoalSkipped {
+        goal: Coord,
+    },
+    DobRefused {
+        at: Coord,
+        reason: &'static str,
+    },
+    /// A movement cycle, planning included, that ran past the slow threshold
+    /// and beat every cycle logged before it.
+    SlowCycle {
+        at: Coord,
+        cycle_ms: f64,
+    },
+}
+
+impl JourneyEvent {
+    pub fn describe(&self) -> String {
+        match self {
+            JourneyEvent::ObstacleDetected { coord } => {
+                format!("🚧 Obstacle detected at {:?}", coord)
+            }
+            JourneyEvent::Rerouted {
+                at,
+                planned_len,
+                nodes_expanded,
+            } => format!(
+                "🔄 Rerouted at {:?} ({} planned steps, {} nodes expanded)",
+                at, planned_len, nodes_expanded
+            ),
+            JourneyEvent::Moved { from, to } => format!("🚶 Moved {:?} → {:?}", from, to),
+            JourneyEvent::FallbackPlan { at, method } => {
+                format!("🧩 Solver found no path at {:?}; using {}", at, method)
+            }
+            JourneyEvent::Trapped { at } => format!("⚠️ Trapped at {:?}", at),
+            JourneyEvent::GoalReached { at } => format!("🎯 Goal reached at {:?}", at),
+            JourneyEvent::GoalSkipped { goal } => {
+                format!("⏭️ Skipped goal at {:?}: no path reaches it", goal)
+            }
+            JourneyEvent::DobRefused { at, reason } => {
+                format!("🚫 No DOB placed at {:?}: {}", at, reason)
+            }
+            JourneyEvent::SlowCycle { at, cycle_ms } => {
+                format!("🐢 Slow cycle at {:?}: {:.0}ms", at, cycle_ms)
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct LoggedEvent {
+    pub timestamp: f64,
+    #[serde(flatten)]
+    pub event: JourneyEvent,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct EventLog {
+    events: Vec<LoggedEvent>,
+}
+
+thread_local! {
+    static PUBLISHED: RefCell<EventLog> = RefCell::new(EventLog::default());
+}
+
+impl EventLog {
+    pub fn record(&mut self, event: JourneyEvent) {
+        self.events.push(LoggedEvent {
+            timestamp: js_sys::Date::now(),
+            event,
+        });
+    }
+
+    pub fn events(&self) -> &[LoggedEvent] {
+        &self.events
+    }
+
+    /// The slowest cycle logged as a `SlowCycle`, if any ran slow.
+    pub fn slowest_cycle_ms(&self) -> Option<f64> {
+        self.events
+            .iter()
+            .filter_map(|logged| match logged.event {
+                JourneyEvent::SlowCycle { cycle_ms, .. } => Some(cycle_ms),
+                _ => None,
+            })
+            .reduce(f64::max)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.events).unwrap_or_else(|_| "[]".to_string())
+    }
+
+    /// Make this log the one returned by `dump_event_log`.
+    pub fn publish(&self) {
+        PUBLISHED.with(|log| *log.borrow_mut() = self.clone());
+    }
+
+    pub fn published_json() -> String {
+        PUBLISHED.with(|log| log.borrow().to_json())
+    }
+}
@@ -0,0 +1,212 @@
+// src/frames.rs
+//
+// Canvas frames captured for documentation: each redraw's PNG, taken from
+// the canvas as a `data:image/png;base64,...` URL, kept as raw bytes and
+// handed out as one zip. The zip is "stored" (no compression): PNG data is
+// already deflated, so a second pass would gain next to nothing.
+
+/// Frames kept before capture stops; a long journey on a big grid is
+/// tens of megabytes well before this.
+pub const MAX_FRAMES: usize = 600;
+
+const PNG_DATA_URL_PREFIX: &str = "data:image/png;base64,";
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// PNG bytes from a canvas `toDataURL("image/png")` result, or `None` if it
+/// isn't one.
+pub fn decode_png_data_url(url: &str) -> Option<Vec<u8>> {
+    let encoded = url.strip_prefix(PNG_DATA_URL_PREFIX)?.trim_end_matches('=');
+    if encoded.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(encoded.len() / 4 * 3 + 2);
+    for chunk in encoded.as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = BASE64.iter().position(|&a| a == c)?;
+            n |= (value as u32) << (18 - 6 * i);
+        }
+        bytes.extend((0..chunk.len() - 1).map(|i| (n >> (16 - 8 * i)) as u8));
+    }
+    Some(bytes)
+}
+
+/// `frame_0001.png`, `frame_0002.png`, ... so the files sort in capture order.
+pub fn frame_name(index: usize) -> String {
+    format!("frame_{:04}.png", index + 1)
+}
+
+/// A zip archive holding `files` uncompressed, in order.
+pub fn zip_stored(files: &[(String, &[u8])]) -> Vec<u8> {
+    // 1980-01-01 00:00, the earliest date the format can hold
+    const DOS_TIME: u16 = 0;
+    const DOS_DATE: u16 = (1 << 5) | 1;
+    const VERSION: u16 = 20;
+
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in files {
+        let offset = out.len() as u32;
+        let crc = crc32(data);
+        let size = data.len() as u32;
+
+        put_u32(&mut out, 0x0403_4b50);
+        for field in [VERSION, 0, 0, DOS_TIME, DOS_DATE] {
+            put_u16(&mut out, field);
+        }
+        for field in [crc, size, size] {
+            put_u32(&mut out, field);
+        }
+        put_u16(&mut out, name.len() as u16);
+        put_u16(&mut out, 0);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        put_u32(&mut central, 0x0201_4b50);
+        for field in [VERSION, VERSION, 0, 0, DOS_TIME, DOS_DATE] {
+            put_u16(&mut central, field);
+        }
+        for field in [crc, size, size] {
+            put_u32(&mut central, field);
+        }
+        // Name length, then no extra field, comment, disk or attributes
+        for field in [name.len() as u16, 0, 0, 0, 0] {
+            put_u16(&mut central, field);
+        }
+        put_u32(&mut central, 0);
+        put_u32(&mut central, offset);
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let central_offset = out.len() as u32;
+    let central_size = central.len() as u32;
+    out.extend_from_slice(&central);
+    put_u32(&mut out, 0x0605_4b50);
+    for field in [0, 0, files.len() as u16, files.len() as u16] {
+        put_u16(&mut out, field);
+    }
+    put_u32(&mut out, central_size);
+    put_u32(&mut out, central_offset);
+    put_u16(&mut out, 0);
+    out
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// CRC-32 (IEEE), bit by bit; a few frames a second don't need a table.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_url(bytes: &[u8]) -> String {
+        let mut url = PNG_DATA_URL_PREFIX.to_string();
+        for chunk in bytes.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+            for i in 0..4 {
+                url.push(if i <= chunk.len() {
+                    BASE64[(n >> (18 - 6 * i)) as usize & 63] as char
+                } else {
+                    '='
+                });
+            }
+        }
+        url
+    }
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]])
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn crc32_matches_the_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn base64_round_trips_with_and_without_padding() {
+        assert_eq!(data_url(b"M"), format!("{}TQ==", PNG_DATA_URL_PREFIX));
+        assert_eq!(data_url(b"Ma"), format!("{}TWE=", PNG_DATA_URL_PREFIX));
+        assert_eq!(data_url(b"Man"), format!("{}TWFu", PNG_DATA_URL_PREFIX));
+
+        let bytes: Vec<u8> = (0..=255).collect();
+        for len in 0..=7 {
+            let slice = &bytes[200..200 + len];
+            assert_eq!(
+                decode_png_data_url(&data_url(slice)).as_deref(),
+                Some(slice)
+            );
+        }
+        assert_eq!(decode_png_data_url(&data_url(&bytes)), Some(bytes));
+        assert_eq!(decode_png_data_url("data:image/jpeg;base64,TWFu"), None);
+        assert_eq!(
+            decode_png_data_url(&format!("{}TWF!", PNG_DATA_URL_PREFIX)),
+            None
+        );
+    }
+
+    #[test]
+    fn zip_headers_parse_back() {
+        let first: &[u8] = b"first frame";
+        let second: &[u8] = b"2";
+        let zip = zip_stored(&[(frame_name(0), first), (frame_name(1), second)]);
+
+        // End of central directory: entry count, then the directory's size and offset
+        let end = zip.len() - 22;
+        assert_eq!(u32_at(&zip, end), 0x0605_4b50);
+        assert_eq!(u16_at(&zip, end + 10), 2);
+        let central_size = u32_at(&zip, end + 12) as usize;
+        let mut central = u32_at(&zip, end + 16) as usize;
+        assert_eq!(central + central_size, end);
+
+        for (name, data) in [(frame_name(0), first), (frame_name(1), second)] {
+            assert_eq!(u32_at(&zip, central), 0x0201_4b50);
+            assert_eq!(u32_at(&zip, central + 16), crc32(data));
+            assert_eq!(u32_at(&zip, central + 20) as usize, data.len());
+            let name_len = u16_at(&zip, central + 28) as usize;
+            assert_eq!(&zip[central + 46..central + 46 + name_len], name.as_bytes());
+            let local = u32_at(&zip, central + 42) as usize;
+
+            assert_eq!(u32_at(&zip, local), 0x0403_4b50);
+            assert_eq!(u32_at(&zip, local + 14), crc32(data));
+            assert_eq!(u32_at(&zip, local + 18) as usize, data.len());
+            assert_eq!(u16_at(&zip, local + 26) as usize, name_len);
+            let body = local + 30 + name_len;
+            assert_eq!(&zip[local + 30..body], name.as_bytes());
+            assert_eq!(&zip[body..body + data.len()], data);
+
+            central += 46 + name_len;
+        }
+        assert_eq!(central, end);
+    }
+}
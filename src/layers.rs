@@ -0,0 +1,1579 @@
+// src/layers.rs
+//
+// The app's layer state, kept free of Yew so the logic reads on its own:
+// the static obstacle map (SOM), the rover with its plans, and the DOBs.
+// `MainApp` holds each in a `use_state` and swaps in updated clones.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::event_log::JourneyEvent;
+use crate::maze::XorShift64;
+use crate::pathfinding::movement::{
+    crosses_thin_wall, first_invalid_step, is_valid_step, thin_wall, ThinWalls,
+    DEFAULT_DIAGONAL_COST,
+};
+use crate::pathfinding::{parse_algorithm, uses_terrain, Connectivity, Coord};
+use crate::rover::{PathResult, Rover};
+use crate::sensor::{
+    update_occupancy, ConversionPolicy, Direction, Sensor, DEFAULT_OCCUPANCY_THRESHOLD,
+};
+use crate::solver::{solve, GridKind, SolveRequest};
+
+/// Extra goals a map may hold on top of the primary one; each costs a
+/// search per replan.
+const MAX_EXTRA_GOALS: usize = 8;
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct SomLayer {
+    pub(crate) original_static_obstacles: HashSet<Coord>,
+    converted_dob_obstacles: HashSet<Coord>,
+    /// Uncertain cells and the probability (0–1) that each is occupied.
+    pub(crate) occupancy: HashMap<Coord, f64>,
+    /// Uncertain cells at or above this probability count as blocked.
+    pub(crate) occupancy_threshold: f64,
+}
+
+impl SomLayer {
+    pub(crate) fn new() -> Self {
+        Self {
+            original_static_obstacles: HashSet::new(),
+            converted_dob_obstacles: HashSet::new(),
+            occupancy: HashMap::new(),
+            occupancy_threshold: DEFAULT_OCCUPANCY_THRESHOLD,
+        }
+    }
+
+    /// Every blocked cell, the boolean map the solvers plan over: walls,
+    /// converted DOBs and uncertain cells at or above the threshold.
+    pub(crate) fn get_complete_obstacle_map(&self) -> Vec<Coord> {
+        let mut blocked: HashSet<Coord> = self
+            .original_static_obstacles
+            .union(&self.converted_dob_obstacles)
+            .cloned()
+            .collect();
+        blocked.extend(self.believed_occupied());
+        blocked.into_iter().collect()
+    }
+
+    /// Uncertain cells currently counted as blocked.
+    fn believed_occupied(&self) -> impl Iterator<Item = Coord> + '_ {
+        self.occupancy
+            .iter()
+            .filter(|&(_, &p)| p >= self.occupancy_threshold)
+            .map(|(&c, _)| c)
+    }
+
+    /// Drop obstacles outside a `width` x `height` grid. Returns whether any were removed.
+    pub(crate) fn prune_to_bounds(&mut self, width: usize, height: usize) -> bool {
+        let count = |som: &Self| {
+            som.original_static_obstacles.len()
+                + som.converted_dob_obstacles.len()
+                + som.occupancy.len()
+        };
+        let before = count(self);
+        let in_bounds = |&(x, y): &Coord| x < width && y < height;
+        self.original_static_obstacles.retain(in_bounds);
+        self.converted_dob_obstacles.retain(in_bounds);
+        self.occupancy.retain(|c, _| in_bounds(c));
+        before != count(self)
+    }
+
+    /// Share of the grid covered by known obstacles, as a percentage.
+    pub(crate) fn density_percent(&self, width: usize, height: usize) -> f64 {
+        let area = (width * height).max(1);
+        let count = self.get_complete_obstacle_map().len();
+        count as f64 / area as f64 * 100.0
+    }
+
+    /// Set the belief that `coord` is occupied, clamped to 0–1; 0 makes it a
+    /// certain free cell again.
+    pub(crate) fn set_occupancy(&mut self, coord: Coord, probability: f64) {
+        let probability = probability.clamp(0.0, 1.0);
+        if probability > 0.0 {
+            self.occupancy.insert(coord, probability);
+        } else {
+            self.occupancy.remove(&coord);
+        }
+    }
+
+    /// One sensor reading of every uncertain cell `in_view`: a cell holding
+    /// a wall or a DOB (`hidden_obstacle`) reads occupied, any other free.
+    /// Returns whether any cell crossed the threshold, which changes the map
+    /// the planner sees.
+    pub(crate) fn observe_occupancy(
+        &mut self,
+        in_view: impl Fn(Coord) -> bool,
+        hidden_obstacle: impl Fn(Coord) -> bool,
+    ) -> bool {
+        let seen: Vec<Coord> = self
+            .occupancy
+            .keys()
+            .copied()
+            .filter(|&c| in_view(c))
+            .collect();
+        let mut crossed = false;
+        for cell in seen {
+            let occupied = self.has_wall(cell) || hidden_obstacle(cell);
+            let before = self.occupancy[&cell];
+            let after = update_occupancy(before, occupied);
+            crossed |= (before >= self.occupancy_threshold) != (after >= self.occupancy_threshold);
+            self.occupancy.insert(cell, after);
+        }
+        crossed
+    }
+
+    pub(crate) fn add_converted_dob(&mut self, coord: Coord) {
+        self.converted_dob_obstacles.insert(coord);
+        debug_log!(
+            "🗺️ SOM Layer 4: Added blocked coordinate {:?} for pathfinding",
+            coord
+        );
+    }
+
+    pub(crate) fn set_initial_obstacles(&mut self, obstacles: HashSet<Coord>) {
+        self.original_static_obstacles = obstacles;
+        debug_log!(
+            "🗺️ SOM Layer 4: Set {} initial obstacles",
+            self.original_static_obstacles.len()
+        );
+    }
+
+    /// Whether the planner treats `coord` as blocked, uncertain cells included.
+    pub(crate) fn is_cell_occupied(&self, coord: Coord) -> bool {
+        self.has_wall(coord)
+            || self
+                .occupancy
+                .get(&coord)
+                .is_some_and(|&p| p >= self.occupancy_threshold)
+    }
+
+    /// A wall or converted DOB, known for certain.
+    pub(crate) fn has_wall(&self, coord: Coord) -> bool {
+        self.original_static_obstacles.contains(&coord)
+            || self.converted_dob_obstacles.contains(&coord)
+    }
+
+    pub(crate) fn clear_converted_dob_obstacles(&mut self) {
+        self.converted_dob_obstacles.clear();
+    }
+}
+
+// Why a planning attempt produced no plan, so a bad map can be told apart from a boxed-in rover
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum PlanError {
+    GoalBlocked,
+    NoPath,
+    BudgetExceeded(usize),
+    // The user stopped the search; nothing was learned about the map
+    Cancelled,
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::GoalBlocked => write!(f, "the goal cell is blocked"),
+            PlanError::NoPath => write!(f, "no path reaches the goal"),
+            PlanError::BudgetExceeded(expanded) => {
+                write!(f, "the search gave up after {} expansions", expanded)
+            }
+            PlanError::Cancelled => write!(f, "the search was cancelled"),
+        }
+    }
+}
+
+// Which method produced a plan; only `Solver` paths come from the chosen algorithm
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum PlanOrigin {
+    Solver,
+    // The solver returned nothing, so the rover walks greedily toward the goal
+    GreedyFallback,
+    // No obstacles at all, so the solver was skipped
+    DirectLine,
+}
+
+impl PlanOrigin {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            PlanOrigin::Solver => "solver",
+            PlanOrigin::GreedyFallback => "greedy fallback",
+            PlanOrigin::DirectLine => "direct line",
+        }
+    }
+}
+
+// Why the rover couldn't take its next planned step
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum StepError {
+    PathTooShort,
+    // The plan starts somewhere other than the rover. `adopt_plan` rules this
+    // out, so hitting it means a plan was edited behind the layer's back
+    Desync { planned_from: Coord },
+    IllegalStep(Coord),
+}
+
+// Why a DOB couldn't go on a cell
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum PlacementError {
+    Occupied,      // A wall, converted DOB or believed-occupied cell
+    Protected,     // The rover, start or a goal
+    AlreadyPlaced, // An amber DOB is there already
+}
+
+impl PlacementError {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            PlacementError::Occupied => "cell is already blocked",
+            PlacementError::Protected => "rover, start and goals stay clear",
+            PlacementError::AlreadyPlaced => "a DOB is already there",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub(crate) struct RoverLayer {
+    pub(crate) current_position: Coord,
+    pub(crate) goal_position: Coord,
+    pub(crate) start_position: Coord,
+    pub(crate) traveled_path: Vec<Coord>,
+    pub(crate) planned_path: Vec<Coord>,
+    pub(crate) algorithm: String,
+    pub(crate) allow_diagonals: bool, // Shared setting for every planner but Theta*
+    pub(crate) diagonal_cost: f64,    // Cost of one diagonal step (every planner but Theta*)
+    pub(crate) turn_penalty: f64,     // Cost per direction change (A* / Dijkstra / D*-Lite)
+    pub(crate) sensor_range: Option<Sensor>, // Sensor-limited planning: only obstacles in range are known
+    pub(crate) no_revisit: bool, // Coverage mode: cells already traveled count as blocked
+    pub(crate) heading: Direction, // Way the last move went; aims a forward-cone sensor
+    is_journey_active: bool,
+    pub(crate) last_result: Option<PathResult>, // Latest solver run (None for shortcut paths)
+    plan_origin: Option<PlanOrigin>, // What produced `planned_path` (None when there is none)
+    pub(crate) cost_field: Option<HashMap<Coord, f64>>, // Per-cell costs from the last search (not Theta*)
+    pub(crate) goals: Vec<Coord>, // Extra goals (Shift+click); the rover visits all, nearest first
+    pub(crate) reached_goals: Vec<Coord>, // Goals visited this journey, in order
+    skipped_goals: Vec<Coord>,    // Goals given up on this journey as unreachable
+    pub(crate) terrain_costs: HashMap<Coord, f64>, // Entry cost per weighted cell (A* / Dijkstra / Greedy); absent = 1.0
+    pub(crate) thin_walls: ThinWalls, // Blocked borders between cells, keyed by `thin_wall`
+    pub(crate) reroute_indices: Vec<usize>, // Traveled-path index where each reroute's plan took over
+}
+
+impl RoverLayer {
+    pub(crate) fn new(start: Coord, goal: Coord) -> Self {
+        Self {
+            current_position: start,
+            goal_position: goal,
+            start_position: start,
+            traveled_path: vec![start],
+            planned_path: Vec::new(),
+            algorithm: "A*".to_string(),
+            allow_diagonals: false,
+            diagonal_cost: DEFAULT_DIAGONAL_COST,
+            turn_penalty: 0.0,
+            sensor_range: None,
+            no_revisit: false,
+            heading: Direction::default(),
+            is_journey_active: false,
+            last_result: None,
+            plan_origin: None,
+            cost_field: None,
+            goals: Vec::new(),
+            reached_goals: Vec::new(),
+            skipped_goals: Vec::new(),
+            terrain_costs: HashMap::new(),
+            thin_walls: ThinWalls::new(),
+            reroute_indices: Vec::new(),
+        }
+    }
+
+    /// Obstacles the planner may use. With sensor-limited planning that is only
+    /// what the sensor sees from the current cell; everything else looks free.
+    /// With no-revisit on, the rover's own trail is added on top, since the
+    /// sensor has nothing to do with where it has already been.
+    fn known_obstacles(&self, obstacle_map: Vec<Coord>) -> Vec<Coord> {
+        let mut known: Vec<Coord> = match self.sensor_range {
+            Some(sensor) => obstacle_map
+                .into_iter()
+                .filter(|&c| sensor.detects(self.current_position, c))
+                .collect(),
+            None => obstacle_map,
+        };
+        if self.no_revisit {
+            known.extend(
+                self.traveled_path
+                    .iter()
+                    .copied()
+                    .filter(|&c| c != self.current_position),
+            );
+        }
+        known
+    }
+
+    /// Goals still to visit: the primary goal plus any extra ones, minus those
+    /// already reached or skipped this journey.
+    pub(crate) fn pending_goals(&self) -> Vec<Coord> {
+        let mut pending: Vec<Coord> = Vec::new();
+        for goal in std::iter::once(self.goal_position).chain(self.goals.iter().copied()) {
+            if !pending.contains(&goal)
+                && !self.reached_goals.contains(&goal)
+                && !self.skipped_goals.contains(&goal)
+            {
+                pending.push(goal);
+            }
+        }
+        pending
+    }
+
+    fn is_goal(&self, coord: Coord) -> bool {
+        coord == self.goal_position || self.goals.contains(&coord)
+    }
+
+    /// `planner` persists across calls so incremental algorithms can reuse
+    /// their previous search when only a few cells changed. With several goals
+    /// pending, the search runs to each and the cheapest plan wins; goals with
+    /// no real path are left for later. Only the goal `planner` last searched
+    /// for is planned on it; the others get a scratch copy, and the winner's
+    /// copy takes its place, so the kept search always follows the active goal.
+    pub(crate) fn compute_path_from_som(
+        &mut self,
+        obstacle_map: Vec<Coord>,
+        planner: &mut Rover,
+    ) -> Result<PlanOrigin, PlanError> {
+        let obstacle_map = self.known_obstacles(obstacle_map);
+        let goals = self.pending_goals();
+        if goals.len() <= 1 {
+            let goal = goals.first().copied().unwrap_or(self.goal_position);
+            return self.plan_to_goal(goal, &obstacle_map, planner);
+        }
+
+        let active_goal = planner.state.goal;
+        let mut best: Option<(f64, RoverLayer, PlanOrigin, Option<Rover>)> = None;
+        let mut first_failure = None;
+        for goal in goals {
+            let mut attempt = self.clone();
+            let mut scratch = (goal != active_goal).then(|| planner.clone_for_goal(goal));
+            let leg_planner = match scratch.as_mut() {
+                Some(scratch) => scratch,
+                None => &mut *planner,
+            };
+            let origin = match attempt.plan_to_goal(goal, &obstacle_map, leg_planner) {
+                Err(PlanError::Cancelled) => {
+                    self.clear_plan();
+                    return Err(PlanError::Cancelled);
+                }
+                Ok(origin @ (PlanOrigin::Solver | PlanOrigin::DirectLine)) => origin,
+                outcome => {
+                    debug_log!("🎯 Goal {:?} unreachable for now - skipping it", goal);
+                    first_failure.get_or_insert(outcome.err().unwrap_or(PlanError::NoPath));
+                    continue;
+                }
+            };
+            let cost = attempt
+                .last_result
+                .as_ref()
+                .map_or(attempt.planned_path.len() as f64, |r| r.cost);
+            if best
+                .as_ref()
+                .is_none_or(|(best_cost, ..)| cost < *best_cost)
+            {
+                best = Some((cost, attempt, origin, scratch));
+            }
+        }
+
+        match best {
+            Some((_, attempt, origin, scratch)) => {
+                if let Some(scratch) = scratch {
+                    *planner = scratch;
+                }
+                *self = attempt;
+                Ok(origin)
+            }
+            None => {
+                self.clear_plan();
+                Err(first_failure.unwrap_or(PlanError::NoPath))
+            }
+        }
+    }
+
+    fn clear_plan(&mut self) {
+        self.planned_path.clear();
+        self.planned_path.shrink_to_fit();
+        self.last_result = None;
+        self.plan_origin = None;
+        self.cost_field = None;
+    }
+
+    /// Drop the plan if a static obstacle now sits on it or on a corner it
+    /// cuts, or a thin wall crosses it, e.g. a wall drawn over it during
+    /// setup. Returns whether it was dropped.
+    pub(crate) fn drop_plan_if_blocked(&mut self, som_layer: &SomLayer) -> bool {
+        let blocked = first_invalid_step(
+            &self.planned_path,
+            self.connectivity(),
+            &self.thin_walls,
+            |c| som_layer.is_cell_occupied(c),
+        )
+        .is_some();
+        if blocked {
+            self.clear_plan();
+        }
+        blocked
+    }
+
+    fn plan_to_goal(
+        &mut self,
+        goal: Coord,
+        obstacle_map: &[Coord],
+        planner: &mut Rover,
+    ) -> Result<PlanOrigin, PlanError> {
+        debug_log!(
+            "🤖 Rover Layer 3: Computing COMPLETELY NEW planned path from {:?} to {:?} using {}",
+            self.current_position,
+            goal,
+            self.algorithm
+        );
+        debug_log!(
+            "🗺️ Using {} SOM obstacles (NO amber DOBs included)",
+            obstacle_map.len()
+        );
+        debug_log!(
+            "📍 Traveled path UNCHANGED: {} steps | Planning NEW path",
+            self.traveled_path.len()
+        );
+
+        self.clear_plan();
+        debug_log!("🔥 CLEARED planned path (traveled path untouched)");
+
+        if obstacle_map.contains(&goal) {
+            debug_log!("❌ Goal is directly blocked by SOM obstacle");
+            return Err(PlanError::GoalBlocked);
+        }
+
+        // A straight line ignores terrain and thin walls, so it's only a shortcut on flat, open ground
+        if obstacle_map.is_empty() && self.terrain_costs.is_empty() && self.thin_walls.is_empty() {
+            let simple_path =
+                Self::create_simple_direct_path(self.current_position, goal, self.connectivity());
+            if !simple_path.is_empty() {
+                debug_log!(
+                    "NEW planned path (simple direct) - {} steps | Traveled: {} unchanged",
+                    simple_path.len(),
+                    self.traveled_path.len()
+                );
+                return self.adopt_plan(simple_path, PlanOrigin::DirectLine);
+            }
+        }
+
+        planner.set_position(self.current_position);
+        planner.set_goal(goal);
+        planner.set_obstacles(obstacle_map.to_vec());
+        planner.set_algorithm(&self.algorithm);
+        planner.set_allow_diagonals(self.allow_diagonals);
+        planner.set_diagonal_cost(self.diagonal_cost);
+        planner.set_turn_penalty(self.turn_penalty);
+        // Costs are checked on entry in `on_terrain_cost_change`; planners that
+        // ignore terrain get none rather than keeping the last ones
+        let terrain_costs = if uses_terrain(&planner.state.algorithm) {
+            self.terrain_costs.iter().map(|(&c, &v)| (c, v)).collect()
+        } else {
+            Vec::new()
+        };
+        let _ = planner.set_terrain_costs(terrain_costs);
+        planner.set_thin_walls(self.thin_walls.iter().copied());
+
+        let result = planner.compute_path_detailed();
+        debug_log!(
+            "🔎 {} expanded {} nodes in {:.1}ms",
+            self.algorithm,
+            result.nodes_expanded,
+            result.elapsed_ms
+        );
+        let mut new_path = result.path.clone();
+        let budget_exceeded = result.budget_exceeded.then_some(result.nodes_expanded);
+        let cancelled = result.cancelled;
+        self.last_result = Some(result);
+        self.cost_field = planner.cost_field();
+
+        if cancelled {
+            debug_log!("Rover Layer 3: Search cancelled");
+            return Err(PlanError::Cancelled);
+        }
+
+        if let Some(expanded) = budget_exceeded {
+            debug_log!(
+                "Rover Layer 3: Search budget exceeded after {} expansions",
+                expanded
+            );
+            return Err(PlanError::BudgetExceeded(expanded));
+        }
+
+        // Don't trust the solver blindly: a broken path would only fail mid-journey
+        let (width, height) = (planner.width, planner.height);
+        let blocked: HashSet<Coord> = obstacle_map.iter().copied().collect();
+        if new_path
+            .first()
+            .is_some_and(|&first| first != self.current_position)
+        {
+            debug_log!(
+                "Rover Layer 3: Rejected {} path - starts at {:?}, rover is at {:?}",
+                self.algorithm,
+                new_path[0],
+                self.current_position
+            );
+            new_path.clear();
+        } else if let Some((from, to)) =
+            first_invalid_step(&new_path, self.connectivity(), &self.thin_walls, |c| {
+                c.0 >= width || c.1 >= height || blocked.contains(&c)
+            })
+        {
+            debug_log!(
+                "Rover Layer 3: Rejected {} path - {:?} -> {:?} is not a legal {:?} step",
+                self.algorithm,
+                from,
+                to,
+                self.connectivity()
+            );
+            new_path.clear();
+        }
+
+        if new_path.is_empty() {
+            let fallback_path = Self::create_greedy_path(
+                self.current_position,
+                goal,
+                obstacle_map,
+                &self.thin_walls,
+                (planner.width, planner.height),
+            );
+            if !fallback_path.is_empty() {
+                debug_log!(
+                    "NEW planned path (fallback) - {} steps | Traveled: {} unchanged",
+                    fallback_path.len(),
+                    self.traveled_path.len()
+                );
+                return self.adopt_plan(fallback_path, PlanOrigin::GreedyFallback);
+            }
+
+            debug_log!("Rover Layer 3: All pathfinding methods failed");
+            return Err(PlanError::NoPath);
+        }
+
+        debug_log!(
+            "NEW planned path COMPLETE - {} steps: {:?} -> {:?} | Traveled: {} unchanged",
+            new_path.len(),
+            new_path.first().unwrap_or(&(0, 0)),
+            new_path.last().unwrap_or(&(0, 0)),
+            self.traveled_path.len()
+        );
+        self.adopt_plan(new_path, PlanOrigin::Solver)
+    }
+
+    /// The one place a plan is installed. Every plan starts on the rover's own
+    /// cell, so `planned_path[0] == current_position` and the next step is
+    /// always `[0] -> [1]`; a path that starts anywhere else is rejected.
+    fn adopt_plan(
+        &mut self,
+        path: Vec<Coord>,
+        origin: PlanOrigin,
+    ) -> Result<PlanOrigin, PlanError> {
+        if path.first() != Some(&self.current_position) {
+            debug_log!(
+                "Rover Layer 3: Rejected {} plan - starts at {:?}, rover is at {:?}",
+                origin.label(),
+                path.first(),
+                self.current_position
+            );
+            return Err(PlanError::NoPath);
+        }
+        self.planned_path = path;
+        self.plan_origin = Some(origin);
+        Ok(origin)
+    }
+
+    fn create_simple_direct_path(
+        start: Coord,
+        goal: Coord,
+        connectivity: Connectivity,
+    ) -> Vec<Coord> {
+        let mut path = vec![start];
+        let mut current = start;
+
+        while current != goal {
+            let (cx, cy) = current;
+            let (gx, gy) = goal;
+
+            let next_x = if cx < gx {
+                cx + 1
+            } else if cx > gx {
+                cx - 1
+            } else {
+                cx
+            };
+            let next_y = if cy < gy {
+                cy + 1
+            } else if cy > gy {
+                cy - 1
+            } else {
+                cy
+            };
+
+            current = if connectivity.allows_diagonals() || next_x == cx || next_y == cy {
+                (next_x, next_y)
+            } else if cx.abs_diff(gx) >= cy.abs_diff(gy) {
+                // 4-connected: close the larger gap first, one axis per step
+                (next_x, cy)
+            } else {
+                (cx, next_y)
+            };
+            path.push(current);
+
+            if path.len() > 1000 {
+                break;
+            }
+        }
+
+        path
+    }
+
+    fn create_greedy_path(
+        start: Coord,
+        goal: Coord,
+        obstacles: &[Coord],
+        thin_walls: &ThinWalls,
+        (width, height): (usize, usize),
+    ) -> Vec<Coord> {
+        use std::collections::HashSet;
+
+        let obstacle_set: HashSet<Coord> = obstacles.iter().cloned().collect();
+        let mut path = vec![start];
+        let mut current = start;
+
+        for _ in 0..1000 {
+            if current == goal {
+                break;
+            }
+
+            let (cx, cy) = current;
+            let (gx, gy) = goal;
+
+            let mut best_next = current;
+            let mut best_distance = f64::INFINITY;
+
+            for (dx, dy) in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
+                let next_x = cx as i32 + dx;
+                let next_y = cy as i32 + dy;
+
+                if (0..width as i32).contains(&next_x) && (0..height as i32).contains(&next_y) {
+                    let next_coord = (next_x as usize, next_y as usize);
+                    if !obstacle_set.contains(&next_coord)
+                        && !crosses_thin_wall(current, next_coord, thin_walls)
+                    {
+                        let distance = ((next_x as f64 - gx as f64).powi(2)
+                            + (next_y as f64 - gy as f64).powi(2))
+                        .sqrt();
+                        if distance < best_distance {
+                            best_distance = distance;
+                            best_next = next_coord;
+                        }
+                    }
+                }
+            }
+
+            if best_next == current {
+                for (dx, dy) in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
+                    let next_x = cx as i32 + dx;
+                    let next_y = cy as i32 + dy;
+
+                    if (0..width as i32).contains(&next_x) && (0..height as i32).contains(&next_y) {
+                        let next_coord = (next_x as usize, next_y as usize);
+                        if !obstacle_set.contains(&next_coord)
+                            && !crosses_thin_wall(current, next_coord, thin_walls)
+                        {
+                            best_next = next_coord;
+                            break;
+                        }
+                    }
+                }
+            }
+
+            if best_next == current {
+                break;
+            }
+
+            current = best_next;
+            path.push(current);
+        }
+
+        if current == goal {
+            path
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub(crate) fn execute_movement_step(&mut self, som_layer: &SomLayer) -> Result<(), StepError> {
+        if self.planned_path.len() < 2 {
+            debug_log!(
+                "Cannot move - planned path too short: {}",
+                self.planned_path.len()
+            );
+            return Err(StepError::PathTooShort);
+        }
+
+        let current_step = self.planned_path[0];
+        if current_step != self.current_position {
+            debug_log!(
+                "PATH DESYNC: Expected current position {:?}, but planned path starts at {:?}",
+                self.current_position,
+                current_step
+            );
+            return Err(StepError::Desync {
+                planned_from: current_step,
+            });
+        }
+
+        let next_position = self.planned_path[1];
+
+        let connectivity = self.connectivity();
+        if !is_valid_step(
+            self.current_position,
+            next_position,
+            connectivity,
+            &self.thin_walls,
+            |c| som_layer.is_cell_occupied(c),
+        ) {
+            debug_log!(
+                "INVALID STEP: From {:?} to {:?} - not a legal {:?} move",
+                self.current_position,
+                next_position,
+                connectivity
+            );
+            return Err(StepError::IllegalStep(next_position));
+        }
+
+        let old_position = self.current_position;
+        self.current_position = next_position;
+        self.heading = Direction::between(old_position, next_position).unwrap_or(self.heading);
+
+        // CRITICAL SEPARATION:
+        // 1. Add new position to TRAVELED PATH (historical, immutable, only grows)
+        self.traveled_path.push(next_position);
+        debug_log!(
+            "📍 TRAVELED PATH: Added {:?} (total traveled: {})",
+            next_position,
+            self.traveled_path.len()
+        );
+
+        // 2. Remove completed step from PLANNED PATH (current plan, shrinks as we move)
+        self.planned_path.remove(0);
+        debug_log!(
+            "🗺️ PLANNED PATH: Removed completed step (remaining planned: {})",
+            self.planned_path.len()
+        );
+
+        debug_log!(
+            "MOVED: {:?} -> {:?} | Traveled: {} | Planned: {}",
+            old_position,
+            self.current_position,
+            self.traveled_path.len(),
+            self.planned_path.len()
+        );
+
+        if self.planned_path.len() >= 2 {
+            debug_log!(
+                "🗺️ Next planned steps: {:?} -> {:?}",
+                self.planned_path[0],
+                self.planned_path[1]
+            );
+        } else if self.planned_path.len() == 1 {
+            debug_log!("🏁 Final step in planned path: {:?}", self.planned_path[0]);
+        }
+
+        Ok(())
+    }
+
+    /// Length of what's left of the current plan; diagonal steps count √2.
+    pub(crate) fn remaining_planned_distance(&self) -> f64 {
+        path_length(&self.planned_path)
+    }
+
+    /// Shortest route over `som_layer` as it stands now, from the journey
+    /// start through the goals reached, in the order reached, to the current
+    /// cell. Found with full knowledge of the map, it's what the journey
+    /// would have been without learning obstacles on the way. Thin walls
+    /// count too. `None` if a leg has no path.
+    pub(crate) fn optimal_route(
+        &self,
+        som_layer: &SomLayer,
+        (width, height): (usize, usize),
+    ) -> Option<Vec<Coord>> {
+        let obstacles = som_layer.get_complete_obstacle_map();
+        let thin_walls: Vec<(Coord, Coord)> = self.thin_walls.iter().copied().collect();
+        let preset = if self.connectivity().allows_diagonals() {
+            "A* (8-dir)"
+        } else {
+            "A* (4-dir)"
+        };
+
+        let mut stops = vec![self.journey_start()];
+        stops.extend(self.reached_goals.iter().copied());
+        stops.push(self.current_position);
+        stops.dedup();
+        let mut route = vec![self.journey_start()];
+        for leg in stops.windows(2) {
+            let response = solve(&SolveRequest {
+                width,
+                height,
+                obstacles: obstacles.clone(),
+                start: leg[0],
+                goal: leg[1],
+                algorithm: preset.to_string(),
+                allow_diagonals: false,
+                diagonal_cost: self.diagonal_cost,
+                turn_penalty: 0.0,
+                terrain_costs: Vec::new(),
+                thin_walls: thin_walls.clone(),
+                obstacle_mask: None,
+                record_trace: false,
+                grid_kind: GridKind::Square,
+            });
+            if response.path.is_empty() {
+                return None;
+            }
+            route.extend(response.path.into_iter().skip(1));
+        }
+        Some(route)
+    }
+
+    pub(crate) fn connectivity(&self) -> Connectivity {
+        Connectivity::for_algorithm(&self.algorithm, self.allow_diagonals)
+    }
+
+    pub(crate) fn nodes_expanded(&self) -> usize {
+        self.last_result.as_ref().map_or(0, |r| r.nodes_expanded)
+    }
+
+    /// What produced the current plan: the algorithm's name, or the fallback used instead.
+    pub(crate) fn plan_origin_label(&self) -> Option<String> {
+        self.plan_origin.map(|origin| match origin {
+            PlanOrigin::Solver => self.algorithm.clone(),
+            fallback => fallback.label().to_string(),
+        })
+    }
+
+    /// Where this journey began (the first traveled cell).
+    pub(crate) fn journey_start(&self) -> Coord {
+        self.traveled_path
+            .first()
+            .copied()
+            .unwrap_or(self.start_position)
+    }
+
+    /// True once the rover stands on the last goal still pending.
+    pub(crate) fn has_reached_goal(&self) -> bool {
+        self.pending_goals()
+            .iter()
+            .all(|&goal| goal == self.current_position)
+    }
+
+    /// True when the rover stands on a pending goal with others still to visit.
+    pub(crate) fn is_at_waypoint_goal(&self) -> bool {
+        self.pending_goals().contains(&self.current_position) && !self.has_reached_goal()
+    }
+
+    /// Cells a DOB may never occupy: start, goals and the rover itself.
+    pub(crate) fn is_protected(&self, coord: Coord) -> bool {
+        coord == self.start_position || self.is_goal(coord) || coord == self.current_position
+    }
+
+    /// Tick off the goal the rover stands on and plan to the nearest one left.
+    /// If none is reachable they are all skipped and this goal ends the journey.
+    pub(crate) fn advance_to_next_goal(
+        &mut self,
+        obstacle_map: Vec<Coord>,
+        planner: &mut Rover,
+    ) -> Vec<JourneyEvent> {
+        let here = self.current_position;
+        self.reached_goals.push(here);
+        if self.compute_path_from_som(obstacle_map, planner).is_ok() {
+            return vec![JourneyEvent::GoalReached { at: here }];
+        }
+        // Leave this goal pending so the usual arrival check finishes here
+        self.reached_goals.pop();
+        let skipped: Vec<Coord> = self
+            .pending_goals()
+            .into_iter()
+            .filter(|&goal| goal != here)
+            .collect();
+        debug_log!(
+            "🎯 No path to the remaining goals {:?} - skipping them",
+            skipped
+        );
+        self.skipped_goals.extend(&skipped);
+        skipped
+            .into_iter()
+            .map(|goal| JourneyEvent::GoalSkipped { goal })
+            .collect()
+    }
+
+    /// Add an extra goal at `cell`, or remove the one already there. Returns
+    /// false if the cell can't take a goal (start, primary goal or the cap).
+    pub(crate) fn toggle_goal(&mut self, cell: Coord) -> bool {
+        if let Some(index) = self.goals.iter().position(|&g| g == cell) {
+            self.goals.remove(index);
+        } else if cell == self.start_position
+            || cell == self.goal_position
+            || self.goals.len() >= MAX_EXTRA_GOALS
+        {
+            return false;
+        } else {
+            self.goals.push(cell);
+        }
+        self.planned_path.clear();
+        true
+    }
+
+    /// Put a thin wall on the border between neighbors `a` and `b`, or take
+    /// away the one there. Returns whether a wall now stands.
+    pub(crate) fn toggle_thin_wall(&mut self, a: Coord, b: Coord) -> bool {
+        let wall = thin_wall(a, b);
+        let added = !self.thin_walls.remove(&wall);
+        if added {
+            self.thin_walls.insert(wall);
+        }
+        added
+    }
+
+    /// Switch to a dropdown preset; presets set `allow_diagonals` too.
+    pub(crate) fn set_algorithm(&mut self, algo: &str) {
+        let Some((algorithm, connectivity)) = parse_algorithm(algo) else {
+            return;
+        };
+        self.algorithm = algorithm.to_string();
+        if let Some(connectivity) = connectivity {
+            self.allow_diagonals = connectivity.allows_diagonals();
+        }
+        self.planned_path.clear();
+    }
+
+    pub(crate) fn set_goal(&mut self, new_goal: Coord) {
+        self.goal_position = new_goal;
+        self.goals.retain(|&goal| goal != new_goal);
+        self.planned_path.clear();
+    }
+
+    /// Mark where the traveled path switches to a new plan. Reroutes with no
+    /// step in between share one index.
+    pub(crate) fn note_reroute(&mut self) {
+        let index = self.traveled_path.len() - 1;
+        if self.reroute_indices.last() != Some(&index) {
+            self.reroute_indices.push(index);
+        }
+    }
+
+    /// Undo the last step: back up one traveled cell and un-reach a goal that
+    /// step reached. DOBs the step converted stay converted, since the sensor
+    /// has seen them and the map only ever learns. Returns the cell backed out
+    /// of, or `None` at the journey start.
+    pub(crate) fn step_back(&mut self) -> Option<Coord> {
+        if self.traveled_path.len() < 2 {
+            return None;
+        }
+        let left = self.traveled_path.pop()?;
+        self.current_position = *self.traveled_path.last()?;
+        self.heading = Direction::of_last_step(&self.traveled_path).unwrap_or_default();
+        let len = self.traveled_path.len();
+        self.reroute_indices.retain(|&index| index < len);
+        if self.reached_goals.last() == Some(&left) {
+            self.reached_goals.pop();
+        }
+        Some(left)
+    }
+
+    pub(crate) fn reset_to_start(&mut self, start: Coord) {
+        self.start_position = start;
+        self.current_position = start;
+        self.traveled_path = vec![start];
+        self.heading = Direction::default();
+        self.reroute_indices.clear();
+        self.planned_path.clear();
+        self.goals.retain(|&goal| goal != start);
+        self.reached_goals.clear();
+        self.skipped_goals.clear();
+        self.is_journey_active = false;
+    }
+}
+
+/// Sum of step lengths along `path`; diagonal steps count √2.
+pub(crate) fn path_length(path: &[Coord]) -> f64 {
+    path.windows(2)
+        .map(|w| {
+            let dx = w[0].0.abs_diff(w[1].0) as f64;
+            let dy = w[0].1.abs_diff(w[1].1) as f64;
+            (dx * dx + dy * dy).sqrt()
+        })
+        .sum()
+}
+
+// LAYER 1: DOB LAYER - OWNS: All DOB states and display classifications
+#[derive(Clone, PartialEq)]
+pub(crate) struct DobLayer {
+    pub(crate) amber_dobs: Vec<Coord>, // OWNED: Active dynamic obstacles (yellow display)
+    pub(crate) blue_converted_dobs: HashSet<Coord>, // OWNED: Converted obstacles (blue display)
+    pub(crate) sensor: Sensor,         // Setting: survives clear_all
+    pub(crate) policy: ConversionPolicy, // Setting: which detected DOBs convert
+    pub(crate) wander: bool,           // Setting: amber DOBs take a random step every cycle
+    pub(crate) wander_seed: u64, // Setting: replays the same wandering when the journey restarts
+    wander_rng: XorShift64,
+}
+
+impl DobLayer {
+    pub(crate) fn new() -> Self {
+        Self {
+            amber_dobs: Vec::new(),
+            blue_converted_dobs: HashSet::new(),
+            sensor: Sensor::default(),
+            policy: ConversionPolicy::default(),
+            wander: false,
+            wander_seed: 0,
+            wander_rng: XorShift64::new(0),
+        }
+    }
+
+    /// Convert the amber DOBs the sensor detects from `rover_position`, facing
+    /// `heading`, that the conversion policy lets through, given the plan
+    /// being followed.
+    pub(crate) fn check_proximity_and_convert(
+        &mut self,
+        rover_position: Coord,
+        heading: Direction,
+        planned_path: &[Coord],
+    ) -> Vec<Coord> {
+        let mut converted_coords = Vec::new();
+        let mut remaining_amber = Vec::new();
+
+        for &dob_coord in &self.amber_dobs {
+            if self
+                .sensor
+                .detects_facing(rover_position, heading, dob_coord)
+                && self.policy.converts(dob_coord, planned_path)
+            {
+                self.blue_converted_dobs.insert(dob_coord);
+                converted_coords.push(dob_coord);
+                debug_log!("🟡→🔵 DOB Layer 1: Converted DOB {:?}", dob_coord);
+            } else {
+                remaining_amber.push(dob_coord);
+            }
+        }
+
+        self.amber_dobs = remaining_amber;
+        converted_coords
+    }
+
+    /// Restart the wandering sequence from `wander_seed`.
+    pub(crate) fn reseed_wander(&mut self) {
+        self.wander_rng = XorShift64::new(self.wander_seed);
+    }
+
+    /// Move every amber DOB one random orthogonal step. A DOB never enters a
+    /// wall, another DOB, the rover, the start or a goal, and stays put when
+    /// boxed in. Returns whether any moved.
+    pub(crate) fn wander_step(
+        &mut self,
+        som_layer: &SomLayer,
+        rover_layer: &RoverLayer,
+        (width, height): (usize, usize),
+    ) -> bool {
+        let mut moved = false;
+        for i in 0..self.amber_dobs.len() {
+            let (x, y) = self.amber_dobs[i];
+            let options: Vec<Coord> = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                .into_iter()
+                .filter_map(|(dx, dy): (isize, isize)| {
+                    Some((x.checked_add_signed(dx)?, y.checked_add_signed(dy)?))
+                })
+                .filter(|&(nx, ny)| nx < width && ny < height)
+                .filter(|&c| {
+                    !som_layer.is_cell_occupied(c)
+                        && !rover_layer.is_protected(c)
+                        && !self.amber_dobs.contains(&c)
+                })
+                .collect();
+            if options.is_empty() {
+                continue;
+            }
+            self.amber_dobs[i] = options[self.wander_rng.below(options.len())];
+            moved = true;
+        }
+        moved
+    }
+
+    pub(crate) fn add_dob(
+        &mut self,
+        coord: Coord,
+        som_layer: &SomLayer,
+        rover_layer: &RoverLayer,
+    ) -> Result<(), PlacementError> {
+        Self::check_placeable(coord, som_layer, rover_layer)?;
+        if self.amber_dobs.contains(&coord) {
+            return Err(PlacementError::AlreadyPlaced);
+        }
+        self.amber_dobs.push(coord);
+        Ok(())
+    }
+
+    /// Whether a DOB may go on `coord` at all, ignoring the DOBs already placed.
+    fn check_placeable(
+        coord: Coord,
+        som_layer: &SomLayer,
+        rover_layer: &RoverLayer,
+    ) -> Result<(), PlacementError> {
+        if som_layer.is_cell_occupied(coord) {
+            Err(PlacementError::Occupied)
+        } else if rover_layer.is_protected(coord) {
+            Err(PlacementError::Protected)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn get_amber_dobs_for_display(&self) -> Vec<Coord> {
+        self.amber_dobs.clone()
+    }
+
+    pub(crate) fn get_blue_dobs_for_display(&self) -> HashSet<Coord> {
+        self.blue_converted_dobs.clone()
+    }
+
+    pub(crate) fn clear_all(&mut self) {
+        self.amber_dobs.clear();
+        self.blue_converted_dobs.clear();
+    }
+
+    /// Drop DOBs outside a `width` x `height` grid. Returns whether any were removed.
+    pub(crate) fn prune_to_bounds(&mut self, width: usize, height: usize) -> bool {
+        let before = self.amber_dobs.len() + self.blue_converted_dobs.len();
+        let in_bounds = |&(x, y): &Coord| x < width && y < height;
+        self.amber_dobs.retain(in_bounds);
+        self.blue_converted_dobs.retain(in_bounds);
+        before != self.amber_dobs.len() + self.blue_converted_dobs.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::{build_planner, PlannerOptions};
+
+    fn set(cells: &[Coord]) -> HashSet<Coord> {
+        cells.iter().copied().collect()
+    }
+
+    #[test]
+    fn obstacle_map_is_walls_converted_dobs_and_believed_cells() {
+        let mut som = SomLayer::new();
+        som.set_initial_obstacles(set(&[(1, 1), (2, 2)]));
+        som.add_converted_dob((2, 2));
+        som.add_converted_dob((3, 3));
+        som.set_occupancy((4, 4), 0.9);
+        som.set_occupancy((5, 5), 0.2);
+
+        let map: HashSet<Coord> = som.get_complete_obstacle_map().into_iter().collect();
+        assert_eq!(map, set(&[(1, 1), (2, 2), (3, 3), (4, 4)]));
+
+        som.clear_converted_dob_obstacles();
+        assert!(!som.get_complete_obstacle_map().contains(&(3, 3)));
+    }
+
+    #[test]
+    fn dobs_convert_within_the_sensor_range_only() {
+        let mut dobs = DobLayer::new();
+        dobs.amber_dobs = vec![(7, 5), (6, 6), (7, 7), (9, 5)];
+
+        let converted = dobs.check_proximity_and_convert((5, 5), Direction::default(), &[]);
+        assert_eq!(converted, vec![(7, 5), (6, 6)]);
+        assert_eq!(dobs.amber_dobs, vec![(7, 7), (9, 5)]);
+        assert_eq!(dobs.blue_converted_dobs, set(&[(7, 5), (6, 6)]));
+    }
+
+    #[test]
+    fn wandering_is_repeatable_and_keeps_off_walls_and_the_rover() {
+        let mut som = SomLayer::new();
+        som.set_initial_obstacles(set(&[(2, 1), (2, 2), (2, 3), (4, 4)]));
+        let mut rover = RoverLayer::new((0, 0), (5, 5));
+        rover.goals = vec![(5, 0)];
+        rover.current_position = (1, 1);
+        let start = vec![(1, 2), (3, 2), (4, 1), (0, 5)];
+
+        let walk = |seed: u64| {
+            let mut dobs = DobLayer::new();
+            dobs.wander_seed = seed;
+            dobs.reseed_wander();
+            dobs.amber_dobs = start.clone();
+            let mut trail = vec![dobs.amber_dobs.clone()];
+            for _ in 0..60 {
+                dobs.wander_step(&som, &rover, (6, 6));
+                trail.push(dobs.amber_dobs.clone());
+            }
+            trail
+        };
+
+        let trail = walk(7);
+        assert_eq!(trail, walk(7));
+        assert_ne!(trail, walk(8));
+        for (before, after) in trail.iter().zip(&trail[1..]) {
+            for (&(x0, y0), &(x, y)) in before.iter().zip(after) {
+                assert!(x0.abs_diff(x) + y0.abs_diff(y) <= 1);
+                assert!(x < 6 && y < 6);
+                assert!(!som.is_cell_occupied((x, y)), "{:?}", (x, y));
+                assert!(!rover.is_protected((x, y)), "{:?}", (x, y));
+            }
+            let distinct: HashSet<&Coord> = after.iter().collect();
+            assert_eq!(distinct.len(), after.len());
+        }
+        // Reseeding replays the walk from the top
+        let mut dobs = DobLayer::new();
+        dobs.wander_seed = 7;
+        dobs.reseed_wander();
+        dobs.amber_dobs = vec![(0, 5)];
+        dobs.wander_step(&som, &rover, (6, 6));
+        dobs.wander_step(&som, &rover, (6, 6));
+        let first = dobs.amber_dobs.clone();
+        dobs.reseed_wander();
+        dobs.amber_dobs = vec![(0, 5)];
+        dobs.wander_step(&som, &rover, (6, 6));
+        dobs.wander_step(&som, &rover, (6, 6));
+        assert_eq!(dobs.amber_dobs, first);
+    }
+
+    #[test]
+    fn only_permanent_on_detect_converts_dobs_off_the_path() {
+        // Heading north; (6, 4) is beside the plan, (7, 5) and (4, 6) are not
+        let planned_path = [(5, 4), (5, 3), (5, 2)];
+        let amber = vec![(6, 4), (7, 5), (4, 6)];
+
+        let mut dobs = DobLayer::new();
+        dobs.policy = ConversionPolicy::PermanentOnDetect;
+        dobs.amber_dobs = amber.clone();
+        let converted = dobs.check_proximity_and_convert((5, 5), Direction::North, &planned_path);
+        assert_eq!(converted, amber);
+        assert!(dobs.amber_dobs.is_empty());
+
+        let mut dobs = DobLayer::new();
+        dobs.policy = ConversionPolicy::AdjacentBlockOnly;
+        dobs.amber_dobs = amber;
+        let converted = dobs.check_proximity_and_convert((5, 5), Direction::North, &planned_path);
+        assert_eq!(converted, vec![(6, 4)]);
+        assert_eq!(dobs.amber_dobs, vec![(7, 5), (4, 6)]);
+        assert_eq!(dobs.blue_converted_dobs, set(&[(6, 4)]));
+    }
+
+    #[test]
+    fn a_desynced_plan_is_refused_without_moving() {
+        let som = SomLayer::new();
+        let mut rover = RoverLayer::new((2, 2), (5, 2));
+        rover.planned_path = vec![(3, 2), (4, 2), (5, 2)];
+
+        assert_eq!(
+            rover.execute_movement_step(&som),
+            Err(StepError::Desync {
+                planned_from: (3, 2)
+            })
+        );
+        assert_eq!(rover.current_position, (2, 2));
+        assert_eq!(rover.traveled_path, vec![(2, 2)]);
+    }
+
+    #[test]
+    fn a_fresh_plan_routes_around_the_som() {
+        let mut som = SomLayer::new();
+        som.set_initial_obstacles(set(&[(3, 0), (3, 1), (3, 2)]));
+        let mut rover = RoverLayer::new((0, 0), (6, 0));
+        let mut planner = Rover::new(10, 10);
+
+        let origin = rover.compute_path_from_som(som.get_complete_obstacle_map(), &mut planner);
+        assert_eq!(origin, Ok(PlanOrigin::Solver));
+        assert_eq!(rover.planned_path.first(), Some(&(0, 0)));
+        assert_eq!(rover.planned_path.last(), Some(&(6, 0)));
+        // Down under the wall and back up: 6 across, 3 down, 3 up
+        assert_eq!(rover.planned_path.len(), 13);
+
+        while rover.current_position != (6, 0) {
+            rover.execute_movement_step(&som).unwrap();
+        }
+        assert_eq!(rover.traveled_path.len(), 13);
+    }
+
+    #[test]
+    fn the_kept_planner_follows_the_nearest_goal() {
+        let mut som = SomLayer::new();
+        som.set_initial_obstacles(set(&[(9, 0)]));
+        let mut rover = RoverLayer::new((0, 0), (3, 0));
+        rover.algorithm = "D*-Lite".to_string();
+        rover.goals = vec![(0, 9)];
+        let mut planner = Rover::new(10, 10);
+
+        let plan = |rover: &mut RoverLayer, planner: &mut Rover| {
+            rover.compute_path_from_som(som.get_complete_obstacle_map(), planner)
+        };
+        assert_eq!(plan(&mut rover, &mut planner), Ok(PlanOrigin::Solver));
+        assert_eq!(rover.planned_path.last(), Some(&(3, 0)));
+        assert_eq!(planner.state.goal, (3, 0));
+
+        // Nothing changed, so D*-Lite answers from the search it kept
+        assert_eq!(plan(&mut rover, &mut planner), Ok(PlanOrigin::Solver));
+        assert_eq!(rover.planned_path.last(), Some(&(3, 0)));
+        assert_eq!(rover.nodes_expanded(), 0);
+    }
+
+    #[test]
+    fn dobs_stay_off_the_rover_start_and_goals() {
+        let mut som = SomLayer::new();
+        som.set_initial_obstacles(set(&[(3, 3)]));
+        let mut rover = RoverLayer::new((0, 0), (5, 5));
+        rover.goals = vec![(7, 7)];
+        rover.current_position = (1, 0);
+        let mut dobs = DobLayer::new();
+
+        for cell in [(0, 0), (1, 0), (5, 5), (7, 7)] {
+            assert_eq!(
+                dobs.add_dob(cell, &som, &rover),
+                Err(PlacementError::Protected)
+            );
+        }
+        assert_eq!(
+            dobs.add_dob((3, 3), &som, &rover),
+            Err(PlacementError::Occupied)
+        );
+        assert!(dobs.amber_dobs.is_empty());
+
+        assert_eq!(dobs.add_dob((4, 4), &som, &rover), Ok(()));
+        assert_eq!(
+            dobs.add_dob((4, 4), &som, &rover),
+            Err(PlacementError::AlreadyPlaced)
+        );
+        assert_eq!(dobs.amber_dobs, vec![(4, 4)]);
+    }
+
+    #[test]
+    fn the_selected_algorithm_plans_the_next_path() {
+        let mut som = SomLayer::new();
+        som.set_initial_obstacles(set(&[(4, 3), (4, 4), (4, 5)]));
+        let mut rover = RoverLayer::new((0, 4), (9, 4));
+        let mut planner = Rover::new(10, 10);
+
+        for (preset, algorithm) in [
+            ("A* (8-dir)", "A*"),
+            ("Dijkstra (4-dir)", "Dijkstra"),
+            ("D*-Lite (4-dir)", "D*-Lite"),
+        ] {
+            rover.set_algorithm(preset);
+            let origin = rover.compute_path_from_som(som.get_complete_obstacle_map(), &mut planner);
+            assert_eq!(origin, Ok(PlanOrigin::Solver), "{}", preset);
+            assert_eq!(planner.state.algorithm, algorithm);
+
+            // The same search run directly expands exactly as many cells
+            let options = PlannerOptions::new(rover.connectivity());
+            let mut direct =
+                build_planner(algorithm, planner.build_grid(), (0, 4), (9, 4), &options);
+            direct.compute_path((0, 4), (9, 4));
+            assert_eq!(
+                rover.nodes_expanded(),
+                direct.nodes_expanded(),
+                "{}",
+                preset
+            );
+        }
+    }
+
+    #[test]
+    fn shrinking_the_grid_drops_what_falls_outside() {
+        let mut som = SomLayer::new();
+        som.set_initial_obstacles(set(&[(2, 2), (8, 8)]));
+        som.add_converted_dob((9, 1));
+        som.set_occupancy((7, 4), 0.9);
+        let mut dobs = DobLayer::new();
+        dobs.amber_dobs = vec![(1, 1), (6, 1)];
+        dobs.blue_converted_dobs = set(&[(3, 3), (1, 7)]);
+
+        assert!(som.prune_to_bounds(5, 5));
+        assert!(dobs.prune_to_bounds(5, 5));
+        assert_eq!(som.get_complete_obstacle_map(), vec![(2, 2)]);
+        assert_eq!(dobs.amber_dobs, vec![(1, 1)]);
+        assert_eq!(dobs.blue_converted_dobs, set(&[(3, 3)]));
+
+        // Nothing left to drop
+        assert!(!som.prune_to_bounds(5, 5));
+        assert!(!dobs.prune_to_bounds(5, 5));
+    }
+
+    #[test]
+    fn a_desynced_plan_is_replaced_by_a_fresh_one() {
+        let som = SomLayer::new();
+        let mut rover = RoverLayer::new((2, 2), (5, 2));
+        let mut planner = Rover::new(10, 10);
+        rover.planned_path = vec![(3, 2), (4, 2), (5, 2)];
+        assert!(matches!(
+            rover.execute_movement_step(&som),
+            Err(StepError::Desync { .. })
+        ));
+
+        rover
+            .compute_path_from_som(som.get_complete_obstacle_map(), &mut planner)
+            .unwrap();
+        assert_eq!(rover.planned_path.first(), Some(&(2, 2)));
+        assert_eq!(rover.execute_movement_step(&som), Ok(()));
+        assert_eq!(rover.current_position, (3, 2));
+    }
+
+    #[test]
+    fn a_plan_with_a_gap_is_never_walked() {
+        let som = SomLayer::new();
+        let mut rover = RoverLayer::new((2, 2), (6, 2));
+        rover.planned_path = vec![(2, 2), (4, 2), (5, 2), (6, 2)];
+        assert_eq!(
+            rover.execute_movement_step(&som),
+            Err(StepError::IllegalStep((4, 2)))
+        );
+        assert_eq!(rover.current_position, (2, 2));
+
+        // A gap further along drops the whole plan before the rover sets off
+        rover.planned_path = vec![(2, 2), (3, 2), (5, 2), (6, 2)];
+        assert!(rover.drop_plan_if_blocked(&som));
+        assert!(rover.planned_path.is_empty());
+
+        rover.planned_path = vec![(2, 2), (3, 2), (4, 2), (5, 2), (6, 2)];
+        assert!(!rover.drop_plan_if_blocked(&som));
+    }
+
+    #[test]
+    fn every_adopted_plan_starts_on_the_rover() {
+        let mut som = SomLayer::new();
+        let mut rover = RoverLayer::new((0, 0), (6, 0));
+        let mut planner = Rover::new(10, 10);
+        rover.planned_path = vec![(0, 0), (1, 0)];
+        rover.execute_movement_step(&som).unwrap();
+
+        let origin = rover.compute_path_from_som(som.get_complete_obstacle_map(), &mut planner);
+        assert_eq!(origin, Ok(PlanOrigin::DirectLine));
+        assert_eq!(rover.planned_path.first(), Some(&(1, 0)));
+
+        som.set_initial_obstacles(set(&[(3, 0), (3, 1)]));
+        let origin = rover.compute_path_from_som(som.get_complete_obstacle_map(), &mut planner);
+        assert_eq!(origin, Ok(PlanOrigin::Solver));
+        assert_eq!(rover.planned_path.first(), Some(&(1, 0)));
+
+        rover.goals = vec![(1, 5)];
+        let origin = rover.compute_path_from_som(som.get_complete_obstacle_map(), &mut planner);
+        assert_eq!(origin, Ok(PlanOrigin::Solver));
+        assert_eq!(rover.planned_path.first(), Some(&(1, 0)));
+
+        let greedy = RoverLayer::create_greedy_path(
+            (1, 0),
+            (1, 5),
+            &som.get_complete_obstacle_map(),
+            &rover.thin_walls,
+            (10, 10),
+        );
+        assert_eq!(
+            rover.adopt_plan(greedy, PlanOrigin::GreedyFallback),
+            Ok(PlanOrigin::GreedyFallback)
+        );
+        assert_eq!(rover.planned_path.first(), Some(&(1, 0)));
+
+        // A plan from anywhere else is refused and the current one kept
+        assert_eq!(
+            rover.adopt_plan(vec![(0, 0), (1, 0)], PlanOrigin::GreedyFallback),
+            Err(PlanError::NoPath)
+        );
+        assert_eq!(rover.planned_path.first(), Some(&(1, 0)));
+    }
+
+    #[test]
+    fn an_uncertain_cell_blocks_planning_only_from_the_threshold() {
+        let mut som = SomLayer::new();
+        let mut rover = RoverLayer::new((0, 0), (4, 0));
+        let mut planner = Rover::new(10, 10);
+
+        som.set_occupancy((2, 0), 0.4);
+        assert!(!som.is_cell_occupied((2, 0)));
+        rover
+            .compute_path_from_som(som.get_complete_obstacle_map(), &mut planner)
+            .unwrap();
+        assert!(rover.planned_path.contains(&(2, 0)));
+
+        som.set_occupancy((2, 0), 0.6);
+        assert!(som.is_cell_occupied((2, 0)));
+        assert_eq!(
+            rover.compute_path_from_som(som.get_complete_obstacle_map(), &mut planner),
+            Ok(PlanOrigin::Solver)
+        );
+        assert!(!rover.planned_path.contains(&(2, 0)));
+        assert_eq!(rover.planned_path.last(), Some(&(4, 0)));
+
+        // A stricter threshold lets the same belief through again
+        som.occupancy_threshold = 0.8;
+        rover
+            .compute_path_from_som(som.get_complete_obstacle_map(), &mut planner)
+            .unwrap();
+        assert!(rover.planned_path.contains(&(2, 0)));
+    }
+
+    #[test]
+    fn a_belief_crossing_the_threshold_calls_for_a_replan() {
+        let mut som = SomLayer::new();
+        let mut rover = RoverLayer::new((0, 0), (4, 0));
+        let mut planner = Rover::new(10, 10);
+        som.set_occupancy((2, 0), 0.2);
+        som.set_occupancy((8, 8), 0.2);
+        rover
+            .compute_path_from_som(som.get_complete_obstacle_map(), &mut planner)
+            .unwrap();
+        assert!(rover.planned_path.contains(&(2, 0)));
+
+        // Only cells in view are read; (2, 0) hides a DOB
+        let in_view = |c: Coord| c.0 < 5 && c.1 < 5;
+        let dob = |c: Coord| c == (2, 0);
+        assert!(!som.observe_occupancy(in_view, dob));
+        assert!(som.observe_occupancy(in_view, dob));
+        assert!(som.occupancy[&(2, 0)] >= som.occupancy_threshold);
+        assert_eq!(som.occupancy[&(8, 8)], 0.2);
+
+        rover
+            .compute_path_from_som(som.get_complete_obstacle_map(), &mut planner)
+            .unwrap();
+        assert!(!rover.planned_path.contains(&(2, 0)));
+
+        // The DOB leaves: readings of a free cell bring the belief back down
+        let crossings: Vec<bool> = (0..3)
+            .map(|_| som.observe_occupancy(in_view, |_| false))
+            .collect();
+        assert_eq!(crossings.iter().filter(|&&c| c).count(), 1);
+        assert!(!som.is_cell_occupied((2, 0)));
+    }
+
+    #[test]
+    fn allowing_diagonals_turns_the_staircase_into_a_diagonal() {
+        // An obstacle off the route keeps the plan on the solver, not the straight-line shortcut
+        let mut som = SomLayer::new();
+        som.set_initial_obstacles(set(&[(9, 0)]));
+        let mut rover = RoverLayer::new((0, 0), (6, 6));
+        rover.algorithm = "D*-Lite".to_string();
+        let mut planner = Rover::new(10, 10);
+        let diagonal_steps = |path: &[Coord]| {
+            path.windows(2)
+                .filter(|w| w[0].0 != w[1].0 && w[0].1 != w[1].1)
+                .count()
+        };
+
+        let plan = |rover: &mut RoverLayer, planner: &mut Rover| {
+            rover.compute_path_from_som(som.get_complete_obstacle_map(), planner)
+        };
+        assert_eq!(plan(&mut rover, &mut planner), Ok(PlanOrigin::Solver));
+        assert_eq!(diagonal_steps(&rover.planned_path), 0);
+        assert_eq!(rover.planned_path.len(), 13);
+
+        // Same kept planner: its cached 4-way neighbors must not survive the switch
+        rover.allow_diagonals = true;
+        assert_eq!(plan(&mut rover, &mut planner), Ok(PlanOrigin::Solver));
+        assert_eq!(diagonal_steps(&rover.planned_path), 6);
+        assert_eq!(rover.planned_path.len(), 7);
+        assert_eq!(rover.planned_path.last(), Some(&(6, 6)));
+    }
+}
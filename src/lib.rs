@@ -1,41 +1,260 @@
 // src/lib.rs
 
+//! Scout's grid planners as a plain Rust library, plus the wasm entry points
+//! behind the web demo.
+//!
+//! Every planner implements [`Pathfinder`], so they can be driven directly
+//! and fed map changes between searches:
+//!
+//! ```
+//! use scout::{AStar, Pathfinder};
+//!
+//! let grid = vec![vec![false; 10]; 10]; // grid[x][y], true = blocked
+//! let mut astar = AStar::new(grid);
+//! assert_eq!(astar.compute_path((0, 0), (9, 9)).unwrap().len(), 19);
+//!
+//! astar.update_obstacle((0, 1), true);
+//! let path = astar.compute_path((0, 0), (9, 9)).unwrap();
+//! assert!(!path.contains(&(0, 1)));
+//! ```
+//!
+//! For a one-off route, [`solve`] picks the planner by name.
+
 #![allow(deprecated)]
 
 use wasm_bindgen::prelude::*;
 use yew::Renderer;
 
+/// Console logging that only runs in debug builds, so release builds stay quiet.
+/// Native builds (tests, the bins) have no console to log to and skip it.
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        if cfg!(all(debug_assertions, target_arch = "wasm32")) {
+            web_sys::console::log_1(&format!($($arg)*).into());
+        }
+    };
+}
+
 mod components;
-mod pathfinding;
+mod event_log;
+mod frames;
+mod layers;
+mod maze;
+mod palette;
+pub mod pathfinding;
+mod prefs;
 mod rover;
+mod sensor;
+mod serialize;
+mod solver;
+mod url_params;
 
 use components::MainApp;
+use event_log::EventLog;
+use pathfinding::movement::{densify, DEFAULT_DIAGONAL_COST};
+use pathfinding::{build_planner, parse_algorithm, PlannerOptions};
+use solver::{GridKind, ScenarioFixture, SolveRequest};
+
+pub use pathfinding::{
+    AStar, Connectivity, Coord, DStarLite, FieldDStar, Pathfinder, ThetaStar, ALGORITHMS,
+    ALGORITHM_PRESETS,
+};
+
+/// Plan a route on `grid[x][y]` (`true` = blocked) with the planner named by
+/// `algorithm`: a bare name from [`ALGORITHMS`], which moves 4 ways (Theta*
+/// any-angle), or a preset from [`ALGORITHM_PRESETS`] such as `"A* (8-dir)"`.
+///
+/// Runs natively, with none of the UI or browser clock. Any-angle waypoints
+/// (Field D*, Theta*) are filled in to unit steps, so the path always runs
+/// cell by cell from `start` to `goal`. `Ok(None)` means there is no path, or
+/// the search gave up at its expansion budget; `Err` describes bad input.
+///
+/// ```
+/// let mut grid = vec![vec![false; 5]; 5];
+/// for y in 0..4 {
+///     grid[2][y] = true; // a wall with a gap at the bottom
+/// }
+///
+/// let path = scout::solve(&grid, (0, 0), (4, 0), "A*").unwrap().unwrap();
+/// assert_eq!(path.first(), Some(&(0, 0)));
+/// assert_eq!(path.last(), Some(&(4, 0)));
+/// assert!(path.contains(&(2, 4)));
+///
+/// grid[2][4] = true;
+/// assert_eq!(scout::solve(&grid, (0, 0), (4, 0), "Theta*"), Ok(None));
+/// assert!(scout::solve(&grid, (0, 0), (4, 0), "BFS").is_err());
+///
+/// let ragged = vec![vec![false; 5], vec![false; 4]];
+/// assert!(scout::solve(&ragged, (0, 0), (1, 1), "A*").is_err());
+/// ```
+pub fn solve(
+    grid: &[Vec<bool>],
+    start: Coord,
+    goal: Coord,
+    algorithm: &str,
+) -> Result<Option<Vec<Coord>>, String> {
+    let (name, connectivity) =
+        parse_algorithm(algorithm).ok_or_else(|| format!("unknown algorithm {:?}", algorithm))?;
+    let connectivity = connectivity.unwrap_or_else(|| Connectivity::for_algorithm(name, false));
+    let (width, height) = (grid.len(), grid.first().map_or(0, Vec::len));
+    if width == 0 || height == 0 {
+        return Err("grid is empty".to_string());
+    }
+    if grid.iter().any(|column| column.len() != height) {
+        return Err("grid columns differ in length".to_string());
+    }
+    for (what, (x, y)) in [("start", start), ("goal", goal)] {
+        if x >= width || y >= height {
+            return Err(format!(
+                "{} ({}, {}) is outside the {}x{} grid",
+                what, x, y, width, height
+            ));
+        }
+    }
+
+    let options = PlannerOptions::new(connectivity);
+    let mut planner = build_planner(name, grid.to_vec(), start, goal, &options);
+    Ok(planner
+        .compute_path(start, goal)
+        .map(|waypoints| densify(&waypoints)))
+}
 
 #[wasm_bindgen]
 pub fn greet(name: &str) -> String {
     format!("Hello, {}! Scout Pathfinder is working.", name)
 }
 
+/// Dump the current journey's event log as a JSON array (for bug reports).
+#[wasm_bindgen]
+pub fn dump_event_log() -> String {
+    EventLog::published_json()
+}
+
+/// Compute a path without the UI, using the same `Rover` plumbing as the app.
+///
+/// `obstacles_json` is an array of `[x, y]` cells; `start` and `goal` are a
+/// single `[x, y]` each. Returns the path as a JSON array of `[x, y]` cells,
+/// or `[]` when no path exists.
+///
+/// `grid_kind` is `"square"` (the default when omitted) or `"hex"`, which reads
+/// cells as odd-r offset `[col, row]` hexes and takes "A*" or "Dijkstra".
+#[wasm_bindgen]
+pub fn find_path(
+    width: usize,
+    height: usize,
+    obstacles_json: &str,
+    start: &str,
+    goal: &str,
+    algorithm: &str,
+    grid_kind: Option<String>,
+) -> Result<String, JsValue> {
+    let parse_err =
+        |what: &str, e: serde_json::Error| JsValue::from_str(&format!("{}: {}", what, e));
+    let obstacles: Vec<Coord> =
+        serde_json::from_str(obstacles_json).map_err(|e| parse_err("obstacles_json", e))?;
+    let start: Coord = serde_json::from_str(start).map_err(|e| parse_err("start", e))?;
+    let goal: Coord = serde_json::from_str(goal).map_err(|e| parse_err("goal", e))?;
+    let grid_kind = match grid_kind.as_deref() {
+        None => GridKind::Square,
+        Some(name) => GridKind::from_name(name).ok_or_else(|| {
+            JsValue::from_str(&format!(
+                "grid_kind: expected \"square\" or \"hex\", got {:?}",
+                name
+            ))
+        })?,
+    };
+
+    let request = SolveRequest {
+        width,
+        height,
+        obstacles,
+        start,
+        goal,
+        algorithm: algorithm.to_string(),
+        allow_diagonals: false,
+        diagonal_cost: DEFAULT_DIAGONAL_COST,
+        turn_penalty: 0.0,
+        terrain_costs: Vec::new(),
+        thin_walls: Vec::new(),
+        obstacle_mask: None,
+        record_trace: false,
+        grid_kind,
+    };
+    request.validate().map_err(|e| JsValue::from_str(&e))?;
+    let response = solver::solve(&request);
+
+    serde_json::to_string(&response.path).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Pack an `[[x, y], ...]` obstacle list into the compact `obstacle_mask`
+/// string a `SolveRequest` also accepts (see `serialize.rs`).
+#[wasm_bindgen]
+pub fn encode_obstacles(
+    width: usize,
+    height: usize,
+    obstacles_json: &str,
+) -> Result<String, JsValue> {
+    let obstacles: Vec<Coord> = serde_json::from_str(obstacles_json)
+        .map_err(|e| JsValue::from_str(&format!("obstacles_json: {}", e)))?;
+    let mut grid = vec![vec![false; height]; width];
+    for (x, y) in obstacles {
+        if x < width && y < height {
+            grid[x][y] = true;
+        }
+    }
+    Ok(serialize::encode_grid(&grid))
+}
+
+/// The maze `seed` carves on a `width` x `height` grid, as an `obstacle_mask`
+/// string (see `serialize.rs`). The same arguments always give the same mask.
+#[wasm_bindgen]
+pub fn generate_maze_mask(width: usize, height: usize, seed: u64) -> String {
+    serialize::encode_grid(&maze::generate_maze(width, height, seed, &[]).grid)
+}
+
+/// Worker entry point: one `SolveRequest` JSON in, one `SolveResponse` JSON
+/// out. See docs/ARCHITECTURE.md for the message protocol.
+#[wasm_bindgen]
+pub fn solve_json(request: &str) -> Result<String, JsValue> {
+    let request: SolveRequest =
+        serde_json::from_str(request).map_err(|e| JsValue::from_str(&format!("request: {}", e)))?;
+    request.validate().map_err(|e| JsValue::from_str(&e))?;
+    let response = solver::solve(&request);
+
+    serde_json::to_string(&response).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Run a regression fixture (see `fixtures/scenarios/`) through every
+/// algorithm it lists. Returns a JSON array of mismatches; `[]` means it passed.
+#[wasm_bindgen]
+pub fn check_scenario_json(fixture: &str) -> Result<String, JsValue> {
+    let fixture: ScenarioFixture =
+        serde_json::from_str(fixture).map_err(|e| JsValue::from_str(&format!("fixture: {}", e)))?;
+    let mismatches = solver::check_fixture(&fixture).map_err(|e| JsValue::from_str(&e))?;
+
+    serde_json::to_string(&mismatches).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
 #[wasm_bindgen]
 pub fn run_app() {
     console_error_panic_hook::set_once();
 
-    web_sys::console::log_1(&"Scout Pathfinder starting initialization...".into());
+    debug_log!("Scout Pathfinder starting initialization...");
 
     let window = web_sys::window().expect("no global `window` exists");
     let document = window.document().expect("no global `document` exists");
 
-    web_sys::console::log_1(&"Window and document obtained".into());
+    debug_log!("Window and document obtained");
 
     let app_element = document
         .get_element_by_id("app")
         .expect("could not find element with id 'app'");
 
-    web_sys::console::log_1(&"App element found".into());
+    debug_log!("App element found");
 
-    web_sys::console::log_1(&"🔧 Attempting to mount Yew application...".into());
+    debug_log!("🔧 Attempting to mount Yew application...");
 
     Renderer::<MainApp>::with_root(app_element).render();
 
-    web_sys::console::log_1(&"Scout Pathfinder initialized successfully!".into());
+    debug_log!("Scout Pathfinder initialized successfully!");
 }
@@ -0,0 +1,139 @@
+// src/maze.rs
+//
+// Seeded maze generation. The same seed and dimensions always carve the same
+// maze, so a seed in a bug report or shared link rebuilds the exact map. The
+// PRNG is a tiny in-crate xorshift so the output never depends on
+// `Math.random` or a dependency's algorithm changing between versions.
+
+use crate::pathfinding::Coord;
+
+/// xorshift64* seeded through splitmix64, so nearby seeds still diverge.
+/// Also drives wandering DOBs, for the same reproducibility.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        // An all-zero state would only ever produce zeros
+        Self {
+            state: if z == 0 { 0x9E37_79B9_7F4A_7C15 } else { z },
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform-enough index in `0..n` for shuffling a handful of neighbors.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// A generated maze and the seed that reproduces it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Maze {
+    pub seed: u64,
+    pub grid: Vec<Vec<bool>>, // grid[x][y], true = wall
+}
+
+impl Maze {
+    pub fn walls(&self) -> impl Iterator<Item = Coord> + '_ {
+        self.grid.iter().enumerate().flat_map(|(x, column)| {
+            column
+                .iter()
+                .enumerate()
+                .filter(|&(_, &wall)| wall)
+                .map(move |(y, _)| (x, y))
+        })
+    }
+}
+
+/// A seed for when the user didn't pick one, shown afterwards so the maze
+/// can still be reproduced.
+pub fn fresh_seed() -> u64 {
+    // Date::now is milliseconds; scramble it so seeds a moment apart look unrelated
+    XorShift64::new(js_sys::Date::now() as u64).next_u64() % 1_000_000_000
+}
+
+/// Carve a perfect maze (recursive backtracker) with corridors on odd cells.
+/// `keep_open` cells (start, goal) are cleared and joined to the nearest
+/// corridor so they're never sealed into a wall.
+pub fn generate_maze(width: usize, height: usize, seed: u64, keep_open: &[Coord]) -> Maze {
+    let mut grid = vec![vec![true; height]; width];
+    let mut rng = XorShift64::new(seed);
+
+    if width >= 3 && height >= 3 {
+        grid[1][1] = false;
+        let mut stack: Vec<Coord> = vec![(1, 1)];
+        while let Some(&(x, y)) = stack.last() {
+            let mut unvisited: Vec<Coord> = [(0, -2), (2, 0), (0, 2), (-2, 0)]
+                .iter()
+                .filter_map(|&(dx, dy)| {
+                    let nx = x.checked_add_signed(dx)?;
+                    let ny = y.checked_add_signed(dy)?;
+                    (nx < width - 1 && ny < height - 1 && grid[nx][ny]).then_some((nx, ny))
+                })
+                .collect();
+            if unvisited.is_empty() {
+                stack.pop();
+                continue;
+            }
+            let (nx, ny) = unvisited.swap_remove(rng.below(unvisited.len()));
+            grid[(x + nx) / 2][(y + ny) / 2] = false;
+            grid[nx][ny] = false;
+            stack.push((nx, ny));
+        }
+    }
+
+    // Nearest corridor index on an axis: odd, and short of the far border
+    let corridor = |v: usize, size: usize| {
+        let last = size - 3 + size % 2;
+        if v % 2 == 1 {
+            v.min(last)
+        } else {
+            v.saturating_sub(1).max(1)
+        }
+    };
+    for &(x, y) in keep_open {
+        if x >= width || y >= height {
+            continue;
+        }
+        grid[x][y] = false;
+        if width < 3 || height < 3 {
+            continue;
+        }
+        let (cx, cy) = (corridor(x, width), corridor(y, height));
+        for column in &mut grid[x.min(cx)..=x.max(cx)] {
+            column[y] = false;
+        }
+        for cell in &mut grid[cx][y.min(cy)..=y.max(cy)] {
+            *cell = false;
+        }
+    }
+
+    Maze { seed, grid }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_seed_always_carves_the_same_maze() {
+        let keep_open = [(0, 0), (30, 20)];
+        let maze = generate_maze(31, 21, 42, &keep_open);
+        assert_eq!(generate_maze(31, 21, 42, &keep_open), maze);
+        assert_ne!(generate_maze(31, 21, 43, &keep_open).grid, maze.grid);
+        assert!(keep_open.iter().all(|&(x, y)| !maze.grid[x][y]));
+    }
+}
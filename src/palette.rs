@@ -0,0 +1,148 @@
+// src/palette.rs
+//
+// Canvas colors per layer. The default blue/amber/turquoise/purple set is
+// hard to tell apart with red-green color blindness, so the user can swap
+// in a deuteranopia-safe set (Okabe–Ito hues) or a high-contrast one.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColorScheme {
+    #[default]
+    Default,
+    /// Okabe–Ito hues, distinguishable with red-green color blindness.
+    Deuteranopia,
+    /// Saturated colors on a plain background, for low vision.
+    HighContrast,
+}
+
+impl ColorScheme {
+    pub const ALL: [ColorScheme; 3] = [
+        ColorScheme::Default,
+        ColorScheme::Deuteranopia,
+        ColorScheme::HighContrast,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ColorScheme::Default => "Default",
+            ColorScheme::Deuteranopia => "Deuteranopia-safe",
+            ColorScheme::HighContrast => "High contrast",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.label() == label)
+    }
+}
+
+/// An `(r, g, b)` color drawn at varying opacity.
+pub type Rgb = (u8, u8, u8);
+
+/// `rgba(...)` string for `rgb` at `alpha`.
+pub fn rgba((r, g, b): Rgb, alpha: f64) -> String {
+    format!("rgba({}, {}, {}, {:.3})", r, g, b, alpha)
+}
+
+/// Every color the canvas draws with, by layer.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Palette {
+    pub background: &'static str,
+    pub grid_line: &'static str,
+    pub obstacle: &'static str,
+    pub amber_dob: &'static str,
+    pub converted_dob: &'static str,
+    /// One shade per plan the rover followed, cycled at each reroute.
+    pub traveled: [&'static str; 4],
+    pub traveled_dot: &'static str,
+    pub planned: &'static str,
+    pub planned_dot: &'static str,
+    /// Full-knowledge shortest route, overlaid once the goal is reached.
+    pub optimal: &'static str,
+    pub preview: Rgb,
+    pub start: &'static str,
+    pub goal: &'static str,
+    pub endpoint_text: &'static str,
+    pub reroute: &'static str,
+    pub reroute_text: &'static str,
+    pub next_step: &'static str,
+    pub selected: &'static str,
+    pub sensor: Rgb,
+    pub terrain: Rgb,
+    pub cost_field: Rgb,
+    pub dead_end: Rgb,
+}
+
+impl Palette {
+    pub fn new(scheme: ColorScheme, is_dark: bool) -> Self {
+        let default = Palette {
+            background: if is_dark { "#0a0a0a" } else { "#fafafa" },
+            grid_line: if is_dark { "#1f1f1f" } else { "#e5e7eb" },
+            obstacle: if is_dark { "#3f3f46" } else { "#52525b" },
+            amber_dob: if is_dark { "#d97706" } else { "#f59e0b" },
+            converted_dob: if is_dark { "#2563eb" } else { "#3b82f6" },
+            traveled: ["#14b8a6", "#0891b2", "#0f766e", "#2dd4bf"],
+            traveled_dot: "#0d9488",
+            planned: "#a855f7",
+            planned_dot: "#9333ea",
+            optimal: "#e11d48",
+            preview: (168, 85, 247),
+            start: "#16a34a",
+            goal: "#dc2626",
+            endpoint_text: "#FFFFFF",
+            reroute: "#f97316",
+            reroute_text: "#FFFFFF",
+            next_step: "#f472b6",
+            selected: "#a855f7",
+            sensor: (251, 146, 60),
+            terrain: (146, 64, 14),
+            cost_field: (14, 165, 233),
+            dead_end: (239, 68, 68),
+        };
+
+        match scheme {
+            ColorScheme::Default => default,
+            ColorScheme::Deuteranopia => Palette {
+                amber_dob: "#E69F00",
+                converted_dob: "#0072B2",
+                traveled: ["#009E73", "#007A5A", "#33B58F", "#005C44"],
+                traveled_dot: "#007A5A",
+                planned: "#CC79A7",
+                planned_dot: "#A8588A",
+                optimal: if is_dark { "#FFFFFF" } else { "#000000" },
+                preview: (204, 121, 167),
+                start: "#56B4E9",
+                goal: "#D55E00",
+                reroute: "#F0E442",
+                reroute_text: "#000000",
+                next_step: if is_dark { "#FFFFFF" } else { "#000000" },
+                selected: "#CC79A7",
+                sensor: (230, 159, 0),
+                terrain: (120, 94, 60),
+                cost_field: (86, 180, 233),
+                dead_end: (213, 94, 0),
+                ..default
+            },
+            ColorScheme::HighContrast => Palette {
+                background: if is_dark { "#000000" } else { "#FFFFFF" },
+                grid_line: if is_dark { "#525252" } else { "#a3a3a3" },
+                obstacle: if is_dark { "#FFFFFF" } else { "#000000" },
+                amber_dob: "#FFB000",
+                converted_dob: if is_dark { "#4D7CFF" } else { "#0000FF" },
+                traveled: ["#00A000", "#007000", "#00D000", "#005000"],
+                traveled_dot: "#007000",
+                planned: "#FF00FF",
+                planned_dot: "#C000C0",
+                optimal: "#00FFFF",
+                preview: (255, 0, 255),
+                start: "#00A000",
+                goal: "#FF0000",
+                reroute: "#FF6600",
+                next_step: "#00E5FF",
+                selected: "#FF00FF",
+                sensor: (255, 102, 0),
+                cost_field: (0, 160, 255),
+                dead_end: (255, 0, 0),
+                ..default
+            },
+        }
+    }
+}
@@ -0,0 +1,204 @@
+// src/pathfinding/analysis.rs
+// ---------------------------
+//
+// Whole-map checks that don't plan a path, for previews before a journey.
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::pathfinding::grid::Grid;
+use crate::pathfinding::movement::{Connectivity, ThinWalls};
+use crate::pathfinding::Coord;
+
+/// Free cells of `grid` (`true` = blocked) with no route to `goal` that
+/// respects `thin_walls`.
+///
+/// Flood-fills outward from the goal over 4-neighbors. Diagonal steps never
+/// cut corners or slip past thin walls, so 8-connected movement reaches
+/// exactly the same cells. A blocked or off-grid goal leaves every free cell
+/// unreachable.
+pub fn unreachable_cells(
+    grid: &[Vec<bool>],
+    thin_walls: &ThinWalls,
+    goal: Coord,
+) -> HashSet<Coord> {
+    let grid = walled_grid(grid, thin_walls);
+    let reached = flood_fill(&grid, goal, Connectivity::Orthogonal);
+    free_cells(&grid).filter(|c| !reached.contains(c)).collect()
+}
+
+/// Share of the free cells in `grid` that `start` can reach, from 0.0 to 1.0.
+/// Anything below 1.0 means walls split the free space into separate pockets.
+/// A blocked or off-grid start, or a grid with no free cells, gives 0.0.
+pub fn reachable_fraction(grid: &[Vec<bool>], thin_walls: &ThinWalls, start: Coord) -> f64 {
+    let grid = walled_grid(grid, thin_walls);
+    let free = free_cells(&grid).count();
+    if free == 0 {
+        return 0.0;
+    }
+    flood_fill(&grid, start, Connectivity::Orthogonal).len() as f64 / free as f64
+}
+
+/// Why `from` may be stuck short of `to`, for the trapped alert.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Isolation {
+    /// Free cells one step from `from` under the movement rules.
+    pub free_neighbors: usize,
+    /// `to` is blocked or lies outside `from`'s connected region.
+    pub separated: bool,
+    /// A wall worth removing when separated: one touching both regions if
+    /// any, else the wall on the edge of `from`'s region closest to `to`.
+    pub wall_hint: Option<Coord>,
+}
+
+/// Diagnose how `from` is cut off from `to` on `grid` (`true` = blocked)
+/// with `thin_walls` in place. Two flood fills, so only call it once
+/// something is actually stuck.
+pub fn isolation(
+    grid: &[Vec<bool>],
+    thin_walls: &ThinWalls,
+    from: Coord,
+    to: Coord,
+    connectivity: Connectivity,
+) -> Isolation {
+    let grid = walled_grid(grid, thin_walls);
+    let free_neighbors = grid.neighbors(from, connectivity).len();
+    let from_region = flood_fill(&grid, from, connectivity);
+    if from_region.contains(&to) {
+        return Isolation {
+            free_neighbors,
+            separated: false,
+            wall_hint: None,
+        };
+    }
+
+    let to_region = flood_fill(&grid, to, connectivity);
+    let edge_walls: Vec<Coord> = (0..grid.width())
+        .flat_map(|x| (0..grid.height()).map(move |y| (x, y)))
+        .filter(|&c| grid.is_blocked(c))
+        .filter(|&c| {
+            grid.adjacent(c, Connectivity::Orthogonal)
+                .iter()
+                .any(|n| from_region.contains(n))
+        })
+        .collect();
+    let manhattan = |a: Coord, b: Coord| a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
+    let bridge = edge_walls
+        .iter()
+        .copied()
+        .filter(|&c| {
+            grid.adjacent(c, Connectivity::Orthogonal)
+                .iter()
+                .any(|n| to_region.contains(n))
+        })
+        .min_by_key(|&c| (manhattan(c, from), c));
+    let wall_hint = bridge.or_else(|| {
+        edge_walls
+            .into_iter()
+            .min_by_key(|&c| (manhattan(c, to), c))
+    });
+
+    Isolation {
+        free_neighbors,
+        separated: true,
+        wall_hint,
+    }
+}
+
+fn walled_grid(grid: &[Vec<bool>], thin_walls: &ThinWalls) -> Grid {
+    let mut grid = Grid::from_columns(grid);
+    grid.set_thin_walls(thin_walls.clone());
+    grid
+}
+
+/// Free cells connected to `from` (itself included) under `connectivity`.
+fn flood_fill(grid: &Grid, from: Coord, connectivity: Connectivity) -> HashSet<Coord> {
+    let mut reached = HashSet::new();
+    let mut frontier = VecDeque::new();
+    if !grid.is_blocked(from) {
+        reached.insert(from);
+        frontier.push_back(from);
+    }
+    while let Some(cell) = frontier.pop_front() {
+        for next in grid.neighbors(cell, connectivity) {
+            if reached.insert(next) {
+                frontier.push_back(next);
+            }
+        }
+    }
+    reached
+}
+
+fn free_cells(grid: &Grid) -> impl Iterator<Item = Coord> + '_ {
+    (0..grid.width())
+        .flat_map(move |x| (0..grid.height()).map(move |y| (x, y)))
+        .filter(|&c| !grid.is_blocked(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::movement::thin_wall;
+
+    #[test]
+    fn thin_walls_seal_off_cells_like_solid_ones() {
+        let grid = vec![vec![false; 4]; 4];
+        let sealed: ThinWalls = [(2, 3), (2, 1), (1, 2), (3, 2)]
+            .into_iter()
+            .map(|side| thin_wall((2, 2), side))
+            .collect();
+
+        assert_eq!(unreachable_cells(&grid, &sealed, (2, 2)).len(), 15);
+        assert_eq!(reachable_fraction(&grid, &sealed, (0, 0)), 15.0 / 16.0);
+        for connectivity in [Connectivity::Orthogonal, Connectivity::Diagonal] {
+            let isolation = isolation(&grid, &sealed, (0, 0), (2, 2), connectivity);
+            assert!(isolation.separated, "{:?}", connectivity);
+        }
+
+        let open = ThinWalls::new();
+        assert!(unreachable_cells(&grid, &open, (2, 2)).is_empty());
+        assert!(!isolation(&grid, &open, (0, 0), (2, 2), Connectivity::Diagonal).separated);
+    }
+
+    #[test]
+    fn a_walled_off_pocket_is_unreachable() {
+        let mut grid = vec![vec![false; 5]; 5];
+        // A full wall down x = 2, and the corner (0, 4) boxed in by two blocks
+        grid[2].fill(true);
+        grid[0][3] = true;
+        grid[1][4] = true;
+
+        let unreachable = unreachable_cells(&grid, &ThinWalls::new(), (0, 0));
+        let mut expected: HashSet<Coord> =
+            (3..5).flat_map(|x| (0..5).map(move |y| (x, y))).collect();
+        expected.insert((0, 4));
+        assert_eq!(unreachable, expected);
+    }
+
+    #[test]
+    fn a_blocked_or_off_grid_goal_leaves_every_free_cell_unreachable() {
+        let mut grid = vec![vec![false; 4]; 4];
+        grid[1][1] = true;
+        let walls = ThinWalls::new();
+
+        assert_eq!(unreachable_cells(&grid, &walls, (1, 1)).len(), 15);
+        assert!(!unreachable_cells(&grid, &walls, (1, 1)).contains(&(1, 1)));
+        assert_eq!(unreachable_cells(&grid, &walls, (9, 9)).len(), 15);
+    }
+
+    #[test]
+    fn reachable_fraction_is_the_share_of_free_cells_in_the_start_region() {
+        let walls = ThinWalls::new();
+        // A wall down x = 1 leaves 4 free cells on the left and 8 on the right
+        let mut split = vec![vec![false; 4]; 4];
+        split[1].fill(true);
+        assert_eq!(reachable_fraction(&split, &walls, (0, 0)), 4.0 / 12.0);
+        assert_eq!(reachable_fraction(&split, &walls, (3, 3)), 8.0 / 12.0);
+
+        // Starting on a wall or off the grid reaches nothing
+        assert_eq!(reachable_fraction(&split, &walls, (1, 2)), 0.0);
+        assert_eq!(reachable_fraction(&split, &walls, (7, 0)), 0.0);
+
+        let solid = vec![vec![true; 3]; 3];
+        assert_eq!(reachable_fraction(&solid, &walls, (0, 0)), 0.0);
+    }
+}
@@ -3,14 +3,28 @@
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 
-use crate::pathfinding::pathfinder_trait::Pathfinder;
+use crate::pathfinding::grid::Grid;
+use crate::pathfinding::movement::{
+    arrival_heading, Connectivity, Heading, ThinWalls, DEFAULT_DIAGONAL_COST,
+    MAX_HEADINGS_PER_CELL, MAX_TURN_PENALTY,
+};
+use crate::pathfinding::pathfinder_trait::{
+    default_max_expansions, trivial_path, CancelFlag, Pathfinder,
+};
+use crate::pathfinding::tie_break::TieBreak;
 
 pub type Coord = (usize, usize);
 
+/// A search state: a cell and the heading it was entered with (`None` unless
+/// turns are penalized).
+type State = (Coord, Heading);
+
 #[derive(Clone, Copy, Eq, PartialEq)]
 struct Node {
     coord: Coord,
+    heading: Heading,
     f_score: usize,
+    tie: u64,
 }
 
 impl Ord for Node {
@@ -18,7 +32,9 @@ impl Ord for Node {
         other
             .f_score
             .cmp(&self.f_score)
+            .then_with(|| other.tie.cmp(&self.tie))
             .then_with(|| self.coord.cmp(&other.coord))
+            .then_with(|| self.heading.cmp(&other.heading))
     }
 }
 
@@ -29,49 +45,172 @@ impl PartialOrd for Node {
 }
 
 pub struct AStar {
-    grid: Vec<Vec<bool>>,
-    width: usize,
-    height: usize,
+    grid: Grid,
+    tie_break: TieBreak,
+    connectivity: Connectivity,
+    diagonal_cost: Option<usize>, // Same integer units; None = diagonals never taken
+    turn_penalty: usize,          // Same integer units as the step costs
+    use_heuristic: bool,          // false = Dijkstra (uniform-cost search)
+    greedy: bool,                 // true = greedy best-first: f = h, g ignored
+    terrain_costs: HashMap<Coord, f64>, // Multiplier for entering a cell; missing = 1.0
+    last_start: Option<Coord>,
+    explored: HashMap<Coord, f64>, // Distance from start of each expanded cell
+    nodes_expanded: usize,
+    trace: Vec<Coord>, // Cells in the order the last search expanded them
+    max_expansions: usize,
+    budget_exceeded: bool,
+    cancel: CancelFlag, // Polled during the search; shared with whoever may cancel it
+    cancelled: bool,
 }
 
 impl AStar {
-    pub fn new(grid: Vec<Vec<bool>>, _start: Coord, _goal: Coord) -> Self {
-        let width = grid.len();
-        let height = if width > 0 { grid[0].len() } else { 0 };
+    // Integer step costs: 1.0 orthogonal, 1000 units
+    const STRAIGHT_COST: usize = 1000;
+    // Dearest single step; a path of these still sums far below usize::MAX
+    const MAX_STEP_COST: usize = u32::MAX as usize;
+
+    /// A* on `grid[x][y]`; start and goal are given per search. Cells missing
+    /// from a short column count as blocked.
+    pub fn new(grid: Vec<Vec<bool>>) -> Self {
+        Self::with_tie_break(grid, TieBreak::default())
+    }
+
+    /// Like `new`, but choose how equal f-scores are ordered.
+    pub fn with_tie_break(grid: Vec<Vec<bool>>, tie_break: TieBreak) -> Self {
+        let grid = Grid::from_columns(&grid);
+        let max_expansions = default_max_expansions(grid.width(), grid.height());
         AStar {
             grid,
-            width,
-            height,
+            tie_break,
+            connectivity: Connectivity::Orthogonal,
+            diagonal_cost: Self::scaled_diagonal_cost(DEFAULT_DIAGONAL_COST),
+            turn_penalty: 0,
+            use_heuristic: true,
+            greedy: false,
+            terrain_costs: HashMap::new(),
+            last_start: None,
+            explored: HashMap::new(),
+            nodes_expanded: 0,
+            trace: Vec::new(),
+            max_expansions,
+            budget_exceeded: false,
+            cancel: CancelFlag::new(),
+            cancelled: false,
         }
     }
 
-    fn heuristic(&self, a: Coord, b: Coord) -> usize {
-        let dx = if a.0 > b.0 { a.0 - b.0 } else { b.0 - a.0 };
-        let dy = if a.1 > b.1 { a.1 - b.1 } else { b.1 - a.1 };
-        dx + dy
+    /// Plan on a 4- or 8-connected grid (4-connected by default).
+    pub fn with_connectivity(mut self, connectivity: Connectivity) -> Self {
+        self.connectivity = connectivity;
+        self
+    }
+
+    /// Cost of one diagonal step (`DEFAULT_DIAGONAL_COST` unless set; values
+    /// below 1.0 or NaN are raised to 1.0). `f64::INFINITY`, or any cost too
+    /// large for the integer step units, leaves only straight moves.
+    pub fn with_diagonal_cost(mut self, cost: f64) -> Self {
+        self.diagonal_cost = Self::scaled_diagonal_cost(cost);
+        self
+    }
+
+    fn scaled_diagonal_cost(cost: f64) -> Option<usize> {
+        let scaled = (cost.max(1.0) * Self::STRAIGHT_COST as f64).round();
+        (scaled < usize::MAX as f64).then_some(scaled as usize)
+    }
+
+    /// Charge `penalty` (in straight-step units, capped at `MAX_TURN_PENALTY`)
+    /// whenever the path changes direction. Headings then become part of the
+    /// search state, so the expansion cap grows with the number of states per cell.
+    pub fn with_turn_penalty(mut self, penalty: f64) -> Self {
+        let penalty = penalty.clamp(0.0, MAX_TURN_PENALTY);
+        self.turn_penalty = (penalty * Self::STRAIGHT_COST as f64).round() as usize;
+        if self.turn_penalty > 0 {
+            self.max_expansions = default_max_expansions(self.grid.width(), self.grid.height())
+                * MAX_HEADINGS_PER_CELL;
+        }
+        self
+    }
+
+    /// Drop the heuristic to run Dijkstra: the same optimal paths, found by
+    /// expanding uniformly outward from the start instead of toward the goal.
+    pub fn with_heuristic(mut self, use_heuristic: bool) -> Self {
+        self.use_heuristic = use_heuristic;
+        self
+    }
+
+    /// Rank the open list by the heuristic alone (greedy best-first search).
+    /// It heads straight for the goal and expands far fewer cells than
+    /// Dijkstra or A*, but the path it returns can be well above the shortest.
+    /// Each cell keeps the parent it was first reached from.
+    pub fn with_greedy(mut self, greedy: bool) -> Self {
+        self.greedy = greedy;
+        self
     }
 
-    fn neighbors(&self, (x, y): Coord) -> Vec<Coord> {
-        let mut result = Vec::with_capacity(4);
+    /// Scale the cost of entering each listed cell. Weights should be at
+    /// least 1.0 so the distance heuristic never overestimates.
+    pub fn with_terrain_costs(mut self, terrain_costs: HashMap<Coord, f64>) -> Self {
+        self.terrain_costs = terrain_costs;
+        self
+    }
+
+    /// Block the listed cell borders (see `movement::ThinWalls`).
+    pub fn with_thin_walls(mut self, thin_walls: ThinWalls) -> Self {
+        self.grid.set_thin_walls(thin_walls);
+        self
+    }
 
-        // Up
-        if y > 0 && x < self.width && (y - 1) < self.height && !self.grid[x][y - 1] {
-            result.push((x, y - 1));
+    fn heuristic(&self, a: Coord, b: Coord) -> usize {
+        if !self.use_heuristic {
+            return 0;
         }
-        // Down
-        if y + 1 < self.height && x < self.width && !self.grid[x][y + 1] {
-            result.push((x, y + 1));
+        let (dx, dy) = (a.0.abs_diff(b.0), a.1.abs_diff(b.1));
+        match self.connectivity {
+            Connectivity::Orthogonal => (dx + dy) * Self::STRAIGHT_COST,
+            Connectivity::Diagonal => {
+                // Past two straight steps a diagonal never shortens the estimate
+                let diagonal = self.diagonal_cost.map_or(2 * Self::STRAIGHT_COST, |cost| {
+                    cost.min(2 * Self::STRAIGHT_COST)
+                });
+                dx.max(dy) * Self::STRAIGHT_COST + dx.min(dy) * (diagonal - Self::STRAIGHT_COST)
+            }
         }
-        // Left
-        if x > 0 && y < self.height && !self.grid[x - 1][y] {
-            result.push((x - 1, y));
+    }
+
+    /// `None` for a diagonal step when diagonals cost ∞.
+    fn step_cost(&self, a: Coord, b: Coord) -> Option<usize> {
+        if a.0 != b.0 && a.1 != b.1 {
+            self.diagonal_cost
+        } else {
+            Some(Self::STRAIGHT_COST)
         }
-        // Right
-        if x + 1 < self.width && y < self.height && !self.grid[x + 1][y] {
-            result.push((x + 1, y));
+    }
+
+    /// `step_cost` scaled by the terrain weight of the cell being entered,
+    /// capped at `MAX_STEP_COST`.
+    fn weighted_step_cost(&self, a: Coord, b: Coord) -> Option<usize> {
+        let cost = self.step_cost(a, b)?;
+        let weighted = match self.terrain_costs.get(&b) {
+            Some(weight) => (cost as f64 * weight).round() as usize,
+            None => cost,
+        };
+        Some(weighted.min(Self::MAX_STEP_COST))
+    }
+
+    /// Heading recorded when stepping `from` → `to`; always `None` when turns are free.
+    fn heading_for(&self, from: Coord, to: Coord) -> Heading {
+        if self.turn_penalty > 0 {
+            arrival_heading(from, to)
+        } else {
+            None
         }
+    }
 
-        result
+    fn turn_cost(&self, heading: Heading, next: Heading) -> usize {
+        match (heading, next) {
+            (Some(h), Some(n)) if h != n => self.turn_penalty,
+            _ => 0,
+        }
     }
 }
 
@@ -79,56 +218,107 @@ impl Pathfinder for AStar {
     type Coord = Coord;
 
     fn compute_path(&mut self, start: Coord, goal: Coord) -> Option<Vec<Coord>> {
+        self.nodes_expanded = 0;
+        self.trace.clear();
+        self.budget_exceeded = false;
+        self.cancelled = false;
+        // A single step since the last plan leaves the rover facing that way
+        let start_heading = self
+            .last_start
+            .and_then(|last| self.heading_for(last, start));
+        self.last_start = Some(start);
+        self.explored.clear();
+        if let Some(answer) = trivial_path(&self.grid, start, goal) {
+            return answer;
+        }
+        let start_state: State = (start, start_heading);
         let mut open_set = BinaryHeap::new();
-        let mut closed_set = HashSet::new();
-        let mut came_from: HashMap<Coord, Coord> = HashMap::new();
-        let mut g_score: HashMap<Coord, usize> = HashMap::new();
-        let mut f_score: HashMap<Coord, usize> = HashMap::new();
+        let mut closed_set: HashSet<State> = HashSet::new();
+        let mut came_from: HashMap<State, State> = HashMap::new();
+        let mut g_score: HashMap<State, usize> = HashMap::new();
 
-        g_score.insert(start, 0);
-        f_score.insert(start, self.heuristic(start, goal));
+        g_score.insert(start_state, 0);
         open_set.push(Node {
             coord: start,
-            f_score: f_score[&start],
+            heading: start_heading,
+            f_score: self.heuristic(start, goal),
+            tie: 0,
         });
 
         while let Some(current_node) = open_set.pop() {
             let current = current_node.coord;
+            let current_state = (current, current_node.heading);
+            // A state pushed again at a lower cost leaves stale copies behind
+            if !closed_set.insert(current_state) {
+                continue;
+            }
 
             if current == goal {
                 // Reconstruct path
                 let mut path = Vec::new();
-                let mut cur = goal;
-                path.push(cur);
+                let mut cur = current_state;
+                path.push(cur.0);
 
                 while let Some(&prev) = came_from.get(&cur) {
                     cur = prev;
-                    path.push(cur);
+                    path.push(cur.0);
                 }
 
                 path.reverse();
                 return Some(path);
             }
 
-            closed_set.insert(current);
+            self.explored
+                .entry(current)
+                .or_insert(g_score[&current_state] as f64 / Self::STRAIGHT_COST as f64);
+            self.nodes_expanded += 1;
+            self.trace.push(current);
+            if self.nodes_expanded > self.max_expansions {
+                self.budget_exceeded = true;
+                return None;
+            }
+            if self.cancel.should_stop(self.nodes_expanded) {
+                self.cancelled = true;
+                return None;
+            }
 
-            for neighbor in self.neighbors(current) {
-                if closed_set.contains(&neighbor) {
+            for neighbor in self.grid.neighbors(current, self.connectivity) {
+                let heading = self.heading_for(current, neighbor);
+                let neighbor_state = (neighbor, heading);
+                if closed_set.contains(&neighbor_state) {
                     continue;
                 }
+                let Some(step_cost) = self.weighted_step_cost(current, neighbor) else {
+                    continue;
+                };
 
-                let tentative_g = g_score.get(&current).unwrap_or(&usize::MAX) + 1;
-                let neighbor_g = *g_score.get(&neighbor).unwrap_or(&usize::MAX);
+                // Saturate: huge terrain weights must not wrap around to cheap
+                let tentative_g = g_score[&current_state]
+                    .saturating_add(step_cost)
+                    .saturating_add(self.turn_cost(current_node.heading, heading));
+                let neighbor_g = *g_score.get(&neighbor_state).unwrap_or(&usize::MAX);
 
-                if tentative_g < neighbor_g {
-                    came_from.insert(neighbor, current);
-                    g_score.insert(neighbor, tentative_g);
-                    let f = tentative_g + self.heuristic(neighbor, goal);
-                    f_score.insert(neighbor, f);
+                // Greedy search never re-parents: a cheaper g can't change its order
+                let improves = if self.greedy {
+                    neighbor_g == usize::MAX
+                } else {
+                    tentative_g < neighbor_g
+                };
+                if improves {
+                    came_from.insert(neighbor_state, current_state);
+                    g_score.insert(neighbor_state, tentative_g);
+                    let h = self.heuristic(neighbor, goal);
+                    let f = if self.greedy {
+                        h
+                    } else {
+                        tentative_g.saturating_add(h)
+                    };
 
                     open_set.push(Node {
                         coord: neighbor,
+                        heading,
                         f_score: f,
+                        tie: self.tie_break.key(neighbor, start, goal),
                     });
                 }
             }
@@ -138,9 +328,118 @@ impl Pathfinder for AStar {
     }
 
     fn update_obstacle(&mut self, coord: Coord, is_blocked: bool) {
-        let (x, y) = coord;
-        if x < self.width && y < self.height {
-            self.grid[x][y] = is_blocked;
+        self.grid.set(coord, is_blocked);
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn search_trace(&self) -> Vec<Coord> {
+        self.trace.clone()
+    }
+
+    fn set_max_expansions(&mut self, max_expansions: usize) {
+        self.max_expansions = max_expansions;
+    }
+
+    fn budget_exceeded(&self) -> bool {
+        self.budget_exceeded
+    }
+
+    fn set_cancel_flag(&mut self, flag: CancelFlag) {
+        self.cancel = flag;
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Distance from the start of every cell the last search expanded, so the
+    /// overlay shows the search footprint.
+    fn cost_field(&self) -> Option<HashMap<Coord, f64>> {
+        Some(self.explored.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::{build_planner, PlannerOptions};
+
+    fn path_cost(path: &[Coord]) -> f64 {
+        path.windows(2)
+            .map(|w| {
+                if w[0].0 != w[1].0 && w[0].1 != w[1].1 {
+                    DEFAULT_DIAGONAL_COST
+                } else {
+                    1.0
+                }
+            })
+            .sum()
+    }
+
+    #[test]
+    fn each_cell_is_expanded_once() {
+        // The goal is walled into its corner, so the search drains every reachable cell
+        let mut grid = vec![vec![false; 15]; 15];
+        for (x, y) in [(13, 13), (13, 14), (14, 13)] {
+            grid[x][y] = true;
+        }
+        for connectivity in [Connectivity::Orthogonal, Connectivity::Diagonal] {
+            let mut astar = AStar::new(grid.clone()).with_connectivity(connectivity);
+            assert_eq!(astar.compute_path((0, 0), (14, 14)), None);
+
+            let trace = astar.search_trace();
+            let unique: HashSet<Coord> = trace.iter().copied().collect();
+            assert_eq!(astar.nodes_expanded(), 15 * 15 - 4, "{:?}", connectivity);
+            assert_eq!(unique.len(), trace.len(), "{:?}", connectivity);
+        }
+    }
+
+    #[test]
+    fn huge_step_costs_never_overflow() {
+        let grid = vec![vec![false; 6]; 6];
+        for cost in [1e300, 1e17, f64::INFINITY] {
+            let mut astar = AStar::new(grid.clone())
+                .with_connectivity(Connectivity::Diagonal)
+                .with_diagonal_cost(cost);
+            let path = astar.compute_path((0, 0), (4, 4)).unwrap();
+            assert_eq!(path.len(), 9, "{}", cost);
         }
+
+        let terrain = HashMap::from([((1, 0), 1e300), ((0, 1), 1e300)]);
+        let mut astar = AStar::new(grid).with_terrain_costs(terrain);
+        assert!(astar.compute_path((0, 0), (4, 4)).is_some());
+    }
+
+    #[test]
+    fn dijkstra_explores_more_than_a_star_and_greedy_far_less() {
+        let grid = vec![vec![false; 30]; 30];
+        let options = PlannerOptions::new(Connectivity::Diagonal);
+        let run = |algorithm: &str| {
+            let mut planner = build_planner(algorithm, grid.clone(), (2, 3), (25, 17), &options);
+            let path = planner.compute_path((2, 3), (25, 17)).unwrap();
+            (path_cost(&path), planner.nodes_expanded())
+        };
+        let (astar_cost, astar_expanded) = run("A*");
+        let (dijkstra_cost, dijkstra_expanded) = run("Dijkstra");
+        let (greedy_cost, greedy_expanded) = run("Greedy Best-First");
+
+        assert!((astar_cost - dijkstra_cost).abs() < 1e-9);
+        assert!(
+            dijkstra_expanded > 4 * astar_expanded,
+            "Dijkstra {} vs A* {}",
+            dijkstra_expanded,
+            astar_expanded
+        );
+        // Greedy never beats the optimal cost, but reaches the goal with a fraction of the work
+        assert!(greedy_cost >= dijkstra_cost - 1e-9);
+        assert!(
+            greedy_expanded * 10 < dijkstra_expanded,
+            "Greedy {} vs Dijkstra {}",
+            greedy_expanded,
+            dijkstra_expanded
+        );
     }
 }
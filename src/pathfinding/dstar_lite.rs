@@ -4,19 +4,35 @@
 // Full D*-Lite implementation on a 2D boolean grid.
 // Constructor: `DStarLite::new(grid, start, goal)`.
 // Adapted from Koenig & Likhachev's original 2002 paper.
+//
+// Search nodes are a cell plus the heading the rover entered it with.
+// Headings only matter when turns cost something (`with_turn_penalty`);
+// otherwise every node carries `None` and the graph is the plain cell grid.
 
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
-
-use crate::pathfinding::pathfinder_trait::Pathfinder;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::pathfinding::grid::Grid;
+use crate::pathfinding::movement::{
+    arrival_heading, turn_cost, Connectivity, Heading, ThinWalls, DEFAULT_DIAGONAL_COST,
+    MAX_HEADINGS_PER_CELL, MAX_TURN_PENALTY,
+};
+use crate::pathfinding::pathfinder_trait::{
+    default_max_expansions, trivial_path, CancelFlag, Pathfinder,
+};
+use crate::pathfinding::tie_break::TieBreak;
 
 /// Shorthand for grid‐cell coordinates.
 pub type Coord = (usize, usize);
 
+/// A search node: a cell and the heading it was entered with.
+type Node = (Coord, Heading);
+
 #[derive(Clone, PartialEq, Eq)]
 struct State {
-    coord: Coord,
+    node: Node,
     k: (i64, i64), // Use i64 to avoid floating point comparison issues
+    tie: u64,
 }
 
 impl Ord for State {
@@ -32,7 +48,10 @@ impl Ord for State {
         } else if k2a > k2b {
             Ordering::Less
         } else {
-            other.coord.cmp(&self.coord)
+            other
+                .tie
+                .cmp(&self.tie)
+                .then_with(|| other.node.cmp(&self.node))
         }
     }
 }
@@ -44,46 +63,62 @@ impl PartialOrd for State {
 }
 
 pub struct DStarLite {
-    grid: Vec<Vec<bool>>,
-    width: usize,
-    height: usize,
-    start: Coord,
+    grid: Grid,
+    start: Node,
     goal: Coord,
 
-    g: HashMap<Coord, f64>,
-    rhs: HashMap<Coord, f64>,
+    g: HashMap<Node, f64>,
+    rhs: HashMap<Node, f64>,
     km: f64,
     open_list: BinaryHeap<State>,
     neighbors_cache: HashMap<Coord, Vec<Coord>>,
     last_start: Coord,
+    tie_break: TieBreak,
+    connectivity: Connectivity,
+    diagonal_cost: f64,
+    turn_penalty: f64,
+    nodes_expanded: usize,
+    trace: Vec<Coord>,     // Cells in the order the last search expanded them
+    vertex_updates: usize, // Nodes refreshed by obstacle changes since construction
+    max_expansions: usize,
+    budget_exceeded: bool,
+    cancel: CancelFlag, // Polled during the search; shared with whoever may cancel it
+    cancelled: bool,
 }
 
 impl DStarLite {
-    const INF_COST: f64 = std::f64::INFINITY;
+    const INF_COST: f64 = f64::INFINITY;
 
     /// Create a new D*-Lite on `grid`, with given `start` and `goal`.
+    #[allow(dead_code)]
     pub fn new(grid: Vec<Vec<bool>>, start: Coord, goal: Coord) -> Self {
-        let width = grid.len();
-        let height = if width > 0 { grid[0].len() } else { 0 };
+        Self::with_tie_break(grid, start, goal, TieBreak::default())
+    }
+
+    /// Like `new`, but choose how equal keys are ordered in the open list.
+    pub fn with_tie_break(
+        grid: Vec<Vec<bool>>,
+        start: Coord,
+        goal: Coord,
+        tie_break: TieBreak,
+    ) -> Self {
+        let grid = Grid::from_columns(&grid);
+        let max_expansions = default_max_expansions(grid.width(), grid.height());
         let mut g = HashMap::new();
         let mut rhs = HashMap::new();
 
         // Initialize all nodes' g and rhs to ∞
-        for x in 0..width {
-            for y in 0..height {
-                let c = (x, y);
+        for x in 0..grid.width() {
+            for y in 0..grid.height() {
+                let c = ((x, y), None);
                 rhs.insert(c, Self::INF_COST);
                 g.insert(c, Self::INF_COST);
             }
         }
-        // The goal's rhs = 0
-        rhs.insert(goal, 0.0);
 
         let mut planner = DStarLite {
             grid,
-            width,
-            height,
-            start,
+            start: (start, None),
             goal,
             g,
             rhs,
@@ -91,163 +126,335 @@ impl DStarLite {
             open_list: BinaryHeap::new(),
             neighbors_cache: HashMap::new(),
             last_start: start,
+            tie_break,
+            connectivity: Connectivity::Orthogonal,
+            diagonal_cost: DEFAULT_DIAGONAL_COST,
+            turn_penalty: 0.0,
+            nodes_expanded: 0,
+            trace: Vec::new(),
+            vertex_updates: 0,
+            max_expansions,
+            budget_exceeded: false,
+            cancel: CancelFlag::new(),
+            cancelled: false,
         };
 
-        // Build neighbors cache
-        planner.build_neighbors_cache();
+        planner.seed_search();
+        planner
+    }
+
+    /// Plan on a 4- or 8-connected grid (4-connected by default). The
+    /// neighbor cache and the goal's key depend on it, so both are rebuilt.
+    pub fn with_connectivity(mut self, connectivity: Connectivity) -> Self {
+        if connectivity != self.connectivity {
+            self.connectivity = connectivity;
+            self.open_list.clear();
+            self.seed_search();
+        }
+        self
+    }
 
-        // Push the goal into open list with its key
-        let goal_key = planner.calculate_key(goal);
-        planner.open_list.push(State {
-            coord: goal,
-            k: goal_key,
-        });
+    /// Cost of one diagonal step (`DEFAULT_DIAGONAL_COST` unless set; values
+    /// below 1.0 or NaN are raised to 1.0). `f64::INFINITY` leaves only
+    /// straight moves. It shapes the goal's key, so the search is reseeded.
+    pub fn with_diagonal_cost(mut self, cost: f64) -> Self {
+        let cost = cost.max(1.0);
+        if cost != self.diagonal_cost {
+            self.diagonal_cost = cost;
+            self.open_list.clear();
+            self.seed_search();
+        }
+        self
+    }
 
-        planner
+    /// Charge `penalty` (in straight-step units, capped at `MAX_TURN_PENALTY`)
+    /// whenever the path changes direction. Headings then become part of the
+    /// search, so the expansion cap grows with the number of nodes per cell.
+    pub fn with_turn_penalty(mut self, penalty: f64) -> Self {
+        let penalty = penalty.clamp(0.0, MAX_TURN_PENALTY);
+        if penalty != self.turn_penalty {
+            self.turn_penalty = penalty;
+            if penalty > 0.0 {
+                self.max_expansions = default_max_expansions(self.grid.width(), self.grid.height())
+                    * MAX_HEADINGS_PER_CELL;
+            }
+            self.open_list.clear();
+            self.seed_search();
+        }
+        self
+    }
+
+    /// Block the listed cell borders (see `movement::ThinWalls`). They live in
+    /// the neighbor cache, so it is rebuilt.
+    pub fn with_thin_walls(mut self, thin_walls: ThinWalls) -> Self {
+        if &thin_walls != self.grid.thin_walls() {
+            self.grid.set_thin_walls(thin_walls);
+            self.open_list.clear();
+            self.seed_search();
+        }
+        self
+    }
+
+    /// Nodes whose rhs obstacle changes have recomputed since this planner
+    /// was built. Batching neighboring changes in `update_obstacles` keeps
+    /// it down.
+    pub fn vertex_updates(&self) -> usize {
+        self.vertex_updates
+    }
+
+    /// Build the neighbor cache and queue every goal node, ready for the first search.
+    fn seed_search(&mut self) {
+        self.build_neighbors_cache();
+
+        // Arriving at the goal with any heading finishes the path
+        for node in self.entry_nodes(self.goal) {
+            self.rhs.insert(node, 0.0);
+            let k = self.calculate_key(node);
+            let tie = self.tie_key(node);
+            self.open_list.push(State { node, k, tie });
+        }
+    }
+
+    /// Forget every g and rhs value and queue the goal afresh: they all hold
+    /// distances to the previous goal, so none can be reused.
+    fn reset_search(&mut self) {
+        for value in self.g.values_mut().chain(self.rhs.values_mut()) {
+            *value = Self::INF_COST;
+        }
+        self.km = 0.0;
+        self.open_list.clear();
+        self.seed_search();
     }
 
     /// Precompute all free‐cell neighbors for quick access
     fn build_neighbors_cache(&mut self) {
-        for x in 0..self.width {
-            for y in 0..self.height {
-                let c = (x, y);
-                if x < self.width && y < self.height && !self.grid[x][y] {
-                    let mut nbrs = Vec::new();
-                    // Up
-                    if y > 0 && y.saturating_sub(1) < self.height && !self.grid[x][y - 1] {
-                        nbrs.push((x, y - 1));
-                    }
-                    // Down
-                    if y + 1 < self.height && !self.grid[x][y + 1] {
-                        nbrs.push((x, y + 1));
-                    }
-                    // Left
-                    if x > 0 && x.saturating_sub(1) < self.width && !self.grid[x - 1][y] {
-                        nbrs.push((x - 1, y));
-                    }
-                    // Right
-                    if x + 1 < self.width && !self.grid[x + 1][y] {
-                        nbrs.push((x + 1, y));
-                    }
-                    self.neighbors_cache.insert(c, nbrs);
-                }
+        for x in 0..self.grid.width() {
+            for y in 0..self.grid.height() {
+                self.refresh_neighbors((x, y));
             }
         }
     }
 
-    /// Heuristic: Manhattan distance (converted to f64)
+    /// Recompute the cached free neighbors of `c`. Blocked cells have no
+    /// entry; a freed cell gets one back here, so it is no longer isolated.
+    fn refresh_neighbors(&mut self, c: Coord) {
+        if self.grid.is_blocked(c) {
+            self.neighbors_cache.remove(&c);
+            return;
+        }
+        let nbrs = self.grid.neighbors(c, self.connectivity);
+        self.neighbors_cache.insert(c, nbrs);
+    }
+
+    /// Heading recorded when stepping `from` → `to`; always `None` when turns are free.
+    fn heading_for(&self, from: Coord, to: Coord) -> Heading {
+        if self.turn_penalty > 0.0 {
+            arrival_heading(from, to)
+        } else {
+            None
+        }
+    }
+
+    /// Nodes of cell `c` worth searching: the heading-less one, one per free
+    /// neighbor it can be entered from, and the rover's own start node.
+    fn entry_nodes(&self, c: Coord) -> Vec<Node> {
+        let mut nodes = vec![(c, None)];
+        if self.turn_penalty > 0.0 {
+            if let Some(nbrs) = self.neighbors_cache.get(&c) {
+                nodes.extend(nbrs.iter().map(|&p| (c, arrival_heading(p, c))));
+            }
+            if c == self.start.0 && !nodes.contains(&self.start) {
+                nodes.push(self.start);
+            }
+        }
+        nodes
+    }
+
+    /// Every node `c` could have, reachable right now or not (for obstacle updates).
+    fn all_nodes(&self, c: Coord) -> Vec<Node> {
+        let mut nodes = vec![(c, None)];
+        if self.turn_penalty > 0.0 {
+            nodes.extend(self.connectivity.offsets().iter().map(|&o| (c, Some(o))));
+        }
+        nodes
+    }
+
+    /// Heuristic: Manhattan distance, or octile when diagonals are allowed
     fn heuristic(&self, a: Coord, b: Coord) -> f64 {
-        let dx = (a.0 as i32 - b.0 as i32).abs() as f64;
-        let dy = (a.1 as i32 - b.1 as i32).abs() as f64;
-        dx + dy
+        self.connectivity.heuristic(a, b, self.diagonal_cost)
     }
 
-    /// Cost of moving from `u` to `v`: 1.0 if adjacent, ∞ otherwise
-    fn cost(&self, u: Coord, v: Coord) -> f64 {
-        if let Some(nbrs) = self.neighbors_cache.get(&u) {
-            if nbrs.contains(&v) {
-                1.0
-            } else {
-                Self::INF_COST
+    /// Cost of moving from `u` to `v`: 1.0 (`diagonal_cost` diagonally) plus
+    /// any turn penalty if adjacent, ∞ otherwise
+    fn cost(&self, (from, heading): Node, (to, _): Node) -> f64 {
+        match self.neighbors_cache.get(&from) {
+            Some(nbrs) if nbrs.contains(&to) => {
+                let step = (to.0 as i32 - from.0 as i32, to.1 as i32 - from.1 as i32);
+                let length = if step.0 != 0 && step.1 != 0 {
+                    self.diagonal_cost
+                } else {
+                    1.0
+                };
+                length + turn_cost(heading, step, self.turn_penalty)
             }
+            _ => Self::INF_COST,
+        }
+    }
+
+    /// Key value for an unreachable node; sorts after every finite key.
+    const INF_KEY: i64 = i64::MAX;
+
+    /// Fixed-point key component. Infinite or NaN costs map to `INF_KEY`
+    /// explicitly rather than relying on the saturating `f64 as i64` cast.
+    fn scaled_key(value: f64) -> i64 {
+        if value.is_finite() {
+            (value * 1000.0) as i64
         } else {
-            Self::INF_COST
+            Self::INF_KEY
         }
     }
 
     /// Compute Key(u) = (min(g[u],rhs[u]) + h(u,s_start) + km, min(g[u],rhs[u]))
-    fn calculate_key(&self, u: Coord) -> (i64, i64) {
+    fn calculate_key(&self, u: Node) -> (i64, i64) {
         let g_u = *self.g.get(&u).unwrap_or(&Self::INF_COST);
         let rhs_u = *self.rhs.get(&u).unwrap_or(&Self::INF_COST);
-        let h = self.heuristic(u, self.start);
-        let key1 = ((g_u.min(rhs_u) + h + self.km) * 1000.0) as i64;
-        let key2 = (g_u.min(rhs_u) * 1000.0) as i64;
+        let best = g_u.min(rhs_u);
+        if !best.is_finite() {
+            return (Self::INF_KEY, Self::INF_KEY);
+        }
+        let h = self.heuristic(u.0, self.start.0);
+        let key1 = Self::scaled_key(best + h + self.km);
+        let key2 = Self::scaled_key(best);
+        debug_assert!(
+            key1 >= key2,
+            "D*-Lite key {:?} is out of order",
+            (key1, key2)
+        );
         (key1, key2)
     }
 
-    /// Compute rhs(u) = min_{s' ∈ neighbors(u)} [g(s') + cost(u,s')]
-    fn compute_rhs(&self, u: Coord) -> f64 {
-        if u == self.goal {
+    /// Secondary ordering for nodes whose keys are equal.
+    fn tie_key(&self, u: Node) -> u64 {
+        self.tie_break.key(u.0, self.start.0, self.goal)
+    }
+
+    /// Nodes one step on from `u`.
+    fn successors(&self, (c, _): Node) -> Vec<Node> {
+        self.neighbors_cache
+            .get(&c)
+            .map(|nbrs| nbrs.iter().map(|&n| (n, self.heading_for(c, n))).collect())
+            .unwrap_or_default()
+    }
+
+    /// Compute rhs(u) = min_{s' ∈ successors(u)} [g(s') + cost(u,s')]
+    fn compute_rhs(&self, u: Node) -> f64 {
+        if u.0 == self.goal {
             return 0.0;
         }
         let mut min_rhs = Self::INF_COST;
-        if let Some(nbrs) = self.neighbors_cache.get(&u) {
-            for &s_prime in nbrs {
-                let g_sp = *self.g.get(&s_prime).unwrap_or(&Self::INF_COST);
-                let c = self.cost(u, s_prime);
-                let tentative = g_sp + c;
-                if tentative < min_rhs {
-                    min_rhs = tentative;
-                }
+        for s_prime in self.successors(u) {
+            let g_sp = *self.g.get(&s_prime).unwrap_or(&Self::INF_COST);
+            let c = self.cost(u, s_prime);
+            let tentative = g_sp + c;
+            if tentative < min_rhs {
+                min_rhs = tentative;
             }
         }
         min_rhs
     }
 
-    /// Returns all predecessors of `u` (i.e. its neighbors)
-    fn predecessors(&self, u: Coord) -> Vec<Coord> {
-        self.neighbors_cache.get(&u).cloned().unwrap_or_default()
+    /// Returns all predecessors of `u`: nodes of the neighbors that step into it with its heading
+    fn predecessors(&self, (c, heading): Node) -> Vec<Node> {
+        let Some(nbrs) = self.neighbors_cache.get(&c) else {
+            return Vec::new();
+        };
+        nbrs.iter()
+            .filter(|&&p| self.heading_for(p, c) == heading)
+            .flat_map(|&p| self.entry_nodes(p))
+            .collect()
+    }
+
+    /// Recompute rhs(u) and requeue `u` if that left it inconsistent.
+    fn refresh_node(&mut self, u: Node) {
+        let rhs_u = self.compute_rhs(u);
+        self.rhs.insert(u, rhs_u);
+        self.update_vertex(u);
+    }
+
+    /// g == rhs, treating two infinities as equal (their difference is NaN).
+    fn is_consistent(g: f64, rhs: f64) -> bool {
+        g == rhs || (rhs - g).abs() <= f64::EPSILON
     }
 
     /// Update a single vertex `u` in the open list
-    fn update_vertex(&mut self, u: Coord) {
+    fn update_vertex(&mut self, u: Node) {
         let rhs_u = *self.rhs.get(&u).unwrap_or(&Self::INF_COST);
         let g_u = *self.g.get(&u).unwrap_or(&Self::INF_COST);
 
         // We do "lazy" removal by simply re‐inserting with a new key if needed.
-        if (rhs_u - g_u).abs() > f64::EPSILON {
+        if !Self::is_consistent(g_u, rhs_u) {
             let k = self.calculate_key(u);
-            self.open_list.push(State { coord: u, k });
+            let tie = self.tie_key(u);
+            self.open_list.push(State { node: u, k, tie });
         }
         // If rhs == g, then it is "consistent," and we do nothing.
     }
 
     /// Main D*-Lite loop: repeatedly pop from open_list until top key ≥ key(start)
     fn compute_shortest_path(&mut self) {
-        loop {
-            if let Some(top) = self.open_list.peek() {
-                let k_old = top.k;
-                let k_start = self.calculate_key(self.start);
-                let rhs_start = *self.rhs.get(&self.start).unwrap_or(&Self::INF_COST);
-                let g_start = *self.g.get(&self.start).unwrap_or(&Self::INF_COST);
-
-                if k_old > k_start && (rhs_start - g_start).abs() <= f64::EPSILON {
-                    break;
-                }
-            } else {
+        while let Some(top) = self.open_list.peek() {
+            let k_old = top.k;
+            let k_start = self.calculate_key(self.start);
+            let rhs_start = *self.rhs.get(&self.start).unwrap_or(&Self::INF_COST);
+            let g_start = *self.g.get(&self.start).unwrap_or(&Self::INF_COST);
+
+            if k_old > k_start && Self::is_consistent(g_start, rhs_start) {
+                break;
+            }
+            // Stop before popping so the queue is intact for the next call
+            if self.cancel.should_stop(self.nodes_expanded) {
+                self.cancelled = true;
+                break;
+            }
+            if self.nodes_expanded >= self.max_expansions {
+                self.budget_exceeded = true;
                 break;
             }
 
             let state_u = self.open_list.pop().unwrap();
-            let u = state_u.coord;
+            let u = state_u.node;
             let k_old = state_u.k;
             let k_new = self.calculate_key(u);
             let g_u = *self.g.get(&u).unwrap_or(&Self::INF_COST);
             let rhs_u = *self.rhs.get(&u).unwrap_or(&Self::INF_COST);
 
+            // Stale duplicate from lazy removal: already consistent
+            if g_u == rhs_u {
+                continue;
+            }
+            self.nodes_expanded += 1;
+            self.trace.push(u.0);
+
             if k_old < k_new {
                 // Reinsert with up‐to‐date key
-                self.open_list.push(State { coord: u, k: k_new });
+                let tie = self.tie_key(u);
+                self.open_list.push(State {
+                    node: u,
+                    k: k_new,
+                    tie,
+                });
             } else if g_u > rhs_u {
                 // Overconsistent => set g[u] = rhs[u], update predecessors
                 self.g.insert(u, rhs_u);
-                let predecessors = self.predecessors(u);
-                for p in predecessors {
-                    let rhs_p = self.compute_rhs(p);
-                    self.rhs.insert(p, rhs_p);
-                    self.update_vertex(p);
+                for p in self.predecessors(u) {
+                    self.refresh_node(p);
                 }
             } else {
                 // Underconsistent => set g[u] = ∞, update u and predecessors
                 self.g.insert(u, Self::INF_COST);
-                let rhs_u2 = self.compute_rhs(u);
-                self.rhs.insert(u, rhs_u2);
-                self.update_vertex(u);
-                let predecessors = self.predecessors(u);
-                for p in predecessors {
-                    let rhs_p = self.compute_rhs(p);
-                    self.rhs.insert(p, rhs_p);
-                    self.update_vertex(p);
+                self.refresh_node(u);
+                for p in self.predecessors(u) {
+                    self.refresh_node(p);
                 }
             }
         }
@@ -261,25 +468,30 @@ impl DStarLite {
         if rhs_start == Self::INF_COST {
             return None;
         }
-        path.push(current);
-        while current != self.goal {
-            // Choose neighbor with min (g(neighbor)+cost(current,neighbor))
+        path.push(current.0);
+        while current.0 != self.goal {
+            // Choose successor with min (g(successor)+cost(current,successor))
+            // Equal values fall back to the tie-break key so the path is stable
             let mut min_val = Self::INF_COST;
-            let mut next_cell = None;
-            if let Some(nbrs) = self.neighbors_cache.get(&current) {
-                for &nbr in nbrs {
-                    let g_n = *self.g.get(&nbr).unwrap_or(&Self::INF_COST);
-                    let c = self.cost(current, nbr);
-                    let val = g_n + c;
-                    if val < min_val {
-                        min_val = val;
-                        next_cell = Some(nbr);
-                    }
+            let mut min_tie = u64::MAX;
+            let mut next_node = None;
+            for nbr in self.successors(current) {
+                let g_n = *self.g.get(&nbr).unwrap_or(&Self::INF_COST);
+                let c = self.cost(current, nbr);
+                let val = g_n + c;
+                if val == Self::INF_COST {
+                    continue;
+                }
+                let tie = self.tie_key(nbr);
+                if val < min_val || (val == min_val && tie < min_tie) {
+                    min_val = val;
+                    min_tie = tie;
+                    next_node = Some(nbr);
                 }
             }
-            if let Some(nx) = next_cell {
+            if let Some(nx) = next_node {
                 current = nx;
-                path.push(current);
+                path.push(current.0);
             } else {
                 return None;
             }
@@ -292,50 +504,222 @@ impl Pathfinder for DStarLite {
     type Coord = Coord;
 
     fn compute_path(&mut self, start: Coord, goal: Coord) -> Option<Vec<Coord>> {
+        self.nodes_expanded = 0;
+        self.trace.clear();
+        self.budget_exceeded = false;
+        self.cancelled = false;
+        if let Some(answer) = trivial_path(&self.grid, start, goal) {
+            return answer;
+        }
         if start != self.last_start {
             self.km += self.heuristic(self.last_start, start);
+            // A single step since the last plan leaves the rover facing that way
+            let heading = self.heading_for(self.last_start, start);
             self.last_start = start;
             // For each predecessor of old start
             let old_s = self.start;
-            let preds = self
-                .neighbors_cache
-                .get(&old_s)
-                .cloned()
-                .unwrap_or_default();
-            for nbr in preds {
-                let rhs_n = self.compute_rhs(nbr);
-                self.rhs.insert(nbr, rhs_n);
-                self.update_vertex(nbr);
+            for nbr in self.predecessors(old_s) {
+                self.refresh_node(nbr);
             }
+            self.start = (start, heading);
+            self.refresh_node(self.start);
         }
 
-        self.start = start;
-        self.goal = goal;
-        self.rhs.insert(self.goal, 0.0);
-
-        let goal_key = self.calculate_key(self.goal);
-        self.open_list.push(State {
-            coord: self.goal,
-            k: goal_key,
-        });
+        if goal != self.goal {
+            self.goal = goal;
+            self.reset_search();
+        }
+        for node in self.entry_nodes(self.goal) {
+            self.rhs.insert(node, 0.0);
+            self.update_vertex(node);
+        }
 
         self.compute_shortest_path();
+        if self.budget_exceeded || self.cancelled {
+            return None;
+        }
         self.reconstruct_path()
     }
 
     fn update_obstacle(&mut self, coord: Coord, is_blocked: bool) {
-        let (x, y) = coord;
-        if x < self.width && y < self.height {
-            self.grid[x][y] = is_blocked;
-        }
-        let preds = self.predecessors(coord);
-        for nbr in preds {
-            let rhs_n = self.compute_rhs(nbr);
-            self.rhs.insert(nbr, rhs_n);
-            self.update_vertex(nbr);
-        }
-        let rhs_c = self.compute_rhs(coord);
-        self.rhs.insert(coord, rhs_c);
-        self.update_vertex(coord);
+        self.update_obstacles(&[(coord, is_blocked)]);
+    }
+
+    /// Flip every cell first, then refresh each affected node once, so
+    /// neighboring changes (a wall of DOBs) don't recompute shared cells repeatedly.
+    fn update_obstacles(&mut self, changes: &[(Coord, bool)]) {
+        let mut affected: HashSet<Coord> = HashSet::new();
+        for &(coord, is_blocked) in changes {
+            if !self.grid.set(coord, is_blocked) {
+                continue;
+            }
+            affected.insert(coord);
+            affected.extend(self.grid.adjacent(coord, self.connectivity));
+        }
+
+        // Edge costs around the changed cells moved, so refresh the cached neighbors
+        // first: a freed cell and each neighbor must list the other again before
+        // their rhs values can drop
+        for &c in &affected {
+            self.refresh_neighbors(c);
+        }
+        for c in affected {
+            for node in self.all_nodes(c) {
+                self.refresh_node(node);
+                self.vertex_updates += 1;
+            }
+        }
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn search_trace(&self) -> Vec<Coord> {
+        self.trace.clone()
+    }
+
+    fn set_max_expansions(&mut self, max_expansions: usize) {
+        self.max_expansions = max_expansions;
+    }
+
+    fn budget_exceeded(&self) -> bool {
+        self.budget_exceeded
+    }
+
+    fn set_cancel_flag(&mut self, flag: CancelFlag) {
+        self.cancel = flag;
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Distance-to-goal (`g`) of every cell reached so far, over its best heading.
+    fn cost_field(&self) -> Option<HashMap<Coord, f64>> {
+        let mut field: HashMap<Coord, f64> = HashMap::new();
+        for (&(c, _), &g) in self.g.iter().filter(|(_, g)| g.is_finite()) {
+            let best = field.entry(c).or_insert(g);
+            *best = best.min(g);
+        }
+        Some(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_unreachable_goal_stops_at_the_budget() {
+        // The start is walled in, so the backward search would drain the whole grid
+        let mut grid = vec![vec![false; 200]; 200];
+        for (x, y) in [(1, 0), (1, 1), (0, 1)] {
+            grid[x][y] = true;
+        }
+        let mut dstar = DStarLite::new(grid, (0, 0), (199, 199));
+        dstar.set_max_expansions(5_000);
+
+        assert_eq!(dstar.compute_path((0, 0), (199, 199)), None);
+        assert!(dstar.budget_exceeded());
+        assert!(dstar.nodes_expanded() <= 5_000);
+    }
+
+    #[test]
+    fn a_replan_after_a_budget_stop_finds_the_shortest_path() {
+        // In a one-cell corridor the frontier is a single node; losing it loses the search
+        let grid = vec![vec![false; 1]; 20];
+        for budget in 1..19 {
+            let mut dstar = DStarLite::new(grid.clone(), (0, 0), (19, 0));
+            dstar.set_max_expansions(budget);
+            assert_eq!(dstar.compute_path((0, 0), (19, 0)), None);
+            assert!(dstar.budget_exceeded());
+
+            dstar.set_max_expansions(usize::MAX);
+            let path = dstar.compute_path((0, 0), (19, 0));
+            assert_eq!(path.map(|p| p.len()), Some(20), "budget {}", budget);
+        }
+    }
+
+    #[test]
+    fn a_new_goal_is_planned_from_scratch() {
+        let grid = vec![vec![false; 10]; 10];
+        let mut dstar = DStarLite::new(grid, (0, 0), (9, 9));
+        assert_eq!(
+            dstar.compute_path((0, 0), (9, 9)).map(|p| p.len()),
+            Some(19)
+        );
+
+        let path = dstar.compute_path((0, 0), (0, 5)).unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(path.last(), Some(&(0, 5)));
+
+        // The old goal no longer counts as a destination
+        let path = dstar.compute_path((9, 8), (0, 5)).unwrap();
+        assert_eq!(path.len(), 13);
+        assert_eq!(path.last(), Some(&(0, 5)));
+    }
+
+    #[test]
+    fn infinite_costs_get_the_sentinel_key() {
+        assert_eq!(DStarLite::scaled_key(f64::INFINITY), DStarLite::INF_KEY);
+        assert_eq!(DStarLite::scaled_key(f64::NAN), DStarLite::INF_KEY);
+        assert_eq!(DStarLite::scaled_key(1.5), 1500);
+    }
+
+    #[test]
+    fn a_walled_in_goal_ends_the_search() {
+        let mut grid = vec![vec![false; 30]; 30];
+        for (x, y) in [(14, 15), (16, 15), (15, 14), (15, 16)] {
+            grid[x][y] = true;
+        }
+        let mut dstar = DStarLite::new(grid, (0, 0), (15, 15));
+        assert_eq!(dstar.compute_path((0, 0), (15, 15)), None);
+        assert!(!dstar.budget_exceeded());
+        assert!(dstar.nodes_expanded() <= 1);
+
+        // Opening one side lets the same planner through
+        dstar.update_obstacle((14, 15), false);
+        let path = dstar.compute_path((0, 0), (15, 15)).unwrap();
+        assert_eq!(path.len(), 31);
+        assert_eq!(path[path.len() - 2], (14, 15));
+    }
+
+    #[test]
+    fn freeing_a_cell_reopens_the_shorter_path() {
+        // A wall across x = 5 with a single gap at the bottom
+        let mut grid = vec![vec![false; 10]; 10];
+        grid[5][..9].fill(true);
+        let mut dstar = DStarLite::new(grid, (0, 0), (9, 0));
+        let detour = dstar.compute_path((0, 0), (9, 0)).unwrap();
+        assert_eq!(detour.len(), 28);
+
+        dstar.update_obstacle((5, 0), false);
+        let path = dstar.compute_path((0, 0), (9, 0)).unwrap();
+        assert_eq!(path.len(), 10);
+        assert!(path.contains(&(5, 0)));
+    }
+
+    #[test]
+    fn a_batch_of_changes_refreshes_fewer_vertices() {
+        let grid = vec![vec![false; 12]; 12];
+        // A wall of DOBs: neighboring cells share most of their neighbors
+        let wall: Vec<(Coord, bool)> = (2..9).map(|y| ((5, y), true)).collect();
+
+        let mut batched = DStarLite::new(grid.clone(), (0, 5), (11, 5));
+        let mut one_by_one = DStarLite::new(grid, (0, 5), (11, 5));
+        batched.compute_path((0, 5), (11, 5)).unwrap();
+        one_by_one.compute_path((0, 5), (11, 5)).unwrap();
+
+        batched.update_obstacles(&wall);
+        for &(cell, blocked) in &wall {
+            one_by_one.update_obstacle(cell, blocked);
+        }
+        let path = batched.compute_path((0, 5), (11, 5));
+        assert_eq!(path, one_by_one.compute_path((0, 5), (11, 5)));
+        assert!(path.is_some_and(|p| p.len() == 20));
+        // Each cell plus its 4 neighbors, against the 7 cells plus the 16 beside the wall
+        assert_eq!(one_by_one.vertex_updates(), 7 * 5);
+        assert_eq!(batched.vertex_updates(), 7 + 16);
     }
 }
@@ -1,13 +1,22 @@
 // src/pathfinding/field_dstar.rs
 // ------------------------------
 //
-// A complete Field D* ("F‐D*") implementation on a 2D boolean grid.
+// Field D* ("F‐D*") on a 2D boolean grid.
 // Constructor: `FieldDStar::new(grid: Vec<Vec<bool>>, start: Coord, goal: Coord)`.
+// Diagonal steps cost √2 unless `with_diagonal_cost` says otherwise.
+//
+// There is no incremental repair: every search starts from scratch, like A*.
+// With `with_full_replan(false)` a call whose start, goal and grid match the
+// previous one returns the cached path instead of searching again.
 
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
-use crate::pathfinding::pathfinder_trait::Pathfinder;
+use crate::pathfinding::grid::Grid;
+use crate::pathfinding::movement::{Connectivity, ThinWalls, DEFAULT_DIAGONAL_COST};
+use crate::pathfinding::pathfinder_trait::{
+    default_max_expansions, trivial_path, CancelFlag, Pathfinder,
+};
 
 /// Shorthand for grid‐cell coordinates.
 pub type Coord = (usize, usize);
@@ -35,88 +44,111 @@ impl PartialOrd for FDState {
 }
 
 pub struct FieldDStar {
-    grid: Vec<Vec<bool>>,
-    width: usize,
-    height: usize,
+    grid: Grid,
     start: Coord,
     goal: Coord,
 
     g: HashMap<Coord, f64>,
     parent: HashMap<Coord, Coord>,
     open_list: BinaryHeap<FDState>,
+    closed: HashSet<Coord>, // Cells the current search has expanded
+    connectivity: Connectivity,
+    diagonal_cost: f64, // Orthogonal steps cost 1.0
+    nodes_expanded: usize,
+    trace: Vec<Coord>, // Cells in the order the last search expanded them
+    max_expansions: usize,
+    budget_exceeded: bool,
+    cancel: CancelFlag, // Polled during the search; shared with whoever may cancel it
+    cancelled: bool,
+
+    /// false = reuse the last path when nothing changed.
+    full_replan: bool,
+    /// Start, goal and result of the last search; cleared when the grid changes.
+    last_search: Option<(Coord, Coord, Option<Vec<Coord>>)>,
 }
 
 impl FieldDStar {
-    const INF: f64 = std::f64::INFINITY;
+    const INF: f64 = f64::INFINITY;
 
     /// Create a new Field D* on `grid`, with `start` and `goal`.
     pub fn new(grid: Vec<Vec<bool>>, start: Coord, goal: Coord) -> Self {
-        let width = grid.len();
-        let height = if width > 0 { grid[0].len() } else { 0 };
-        let mut g = HashMap::new();
-        let parent = HashMap::new();
-
-        for x in 0..width {
-            for y in 0..height {
-                g.insert((x, y), Self::INF);
-            }
-        }
-        g.insert(start, 0.0);
-
-        let mut fds = FieldDStar {
+        let grid = Grid::from_columns(&grid);
+        let max_expansions = default_max_expansions(grid.width(), grid.height());
+        // Search state is built by `compute_path`; unvisited cells read as INF
+        FieldDStar {
             grid,
-            width,
-            height,
             start,
             goal,
-            g,
-            parent,
+            g: HashMap::new(),
+            parent: HashMap::new(),
             open_list: BinaryHeap::new(),
-        };
+            closed: HashSet::new(),
+            connectivity: Connectivity::Diagonal,
+            diagonal_cost: DEFAULT_DIAGONAL_COST,
+            nodes_expanded: 0,
+            trace: Vec::new(),
+            max_expansions,
+            budget_exceeded: false,
+            cancel: CancelFlag::new(),
+            cancelled: false,
+            full_replan: true,
+            last_search: None,
+        }
+    }
 
-        let f0 = (fds.heuristic(start, goal) * 1000.0) as i64;
-        fds.open_list.push(FDState {
-            coord: start,
-            f: f0,
-        });
-        fds
+    /// Plan on a 4- or 8-connected grid (8-connected by default).
+    pub fn with_connectivity(mut self, connectivity: Connectivity) -> Self {
+        self.connectivity = connectivity;
+        self.last_search = None;
+        self
+    }
+
+    /// Cost of one diagonal step (√2 by default; orthogonal steps cost 1.0).
+    /// Raising it steers paths onto straight moves: from 2.0 up a diagonal is
+    /// never cheaper than the two straight steps around it, and
+    /// `f64::INFINITY` leaves only 4-connected paths while neighbors are still
+    /// generated 8 ways. Values below 1.0 (or NaN) are raised to 1.0.
+    pub fn with_diagonal_cost(mut self, cost: f64) -> Self {
+        self.diagonal_cost = cost.max(1.0);
+        self.last_search = None;
+        self
+    }
+
+    /// `true` (the default) searches from scratch on every call. `false`
+    /// skips the search when start, goal and grid are unchanged since the
+    /// last call and returns that path again.
+    pub fn with_full_replan(mut self, full_replan: bool) -> Self {
+        self.full_replan = full_replan;
+        self
+    }
+
+    /// Block the listed cell borders (see `movement::ThinWalls`).
+    pub fn with_thin_walls(mut self, thin_walls: ThinWalls) -> Self {
+        self.grid.set_thin_walls(thin_walls);
+        self.last_search = None;
+        self
     }
 
-    /// Heuristic: Euclidean distance between two coords.
+    /// Heuristic: Euclidean distance between two coords. Diagonals cheaper
+    /// than √2 would make that overestimate, so those use the octile distance
+    /// at the configured diagonal cost instead.
     fn heuristic(&self, a: Coord, b: Coord) -> f64 {
         let dx = (a.0 as f64) - (b.0 as f64);
         let dy = (a.1 as f64) - (b.1 as f64);
-        (dx * dx + dy * dy).sqrt()
-    }
-
-    /// Return up to 8 neighbors (including diagonals) that are free.
-    fn neighbors(&self, (x, y): Coord) -> Vec<Coord> {
-        let mut result = Vec::new();
-        for dy in -1i32..=1 {
-            for dx in -1i32..=1 {
-                if dx == 0 && dy == 0 {
-                    continue;
-                }
-                let nx = x as i32 + dx;
-                let ny = y as i32 + dy;
-                if nx >= 0 && nx < self.width as i32 && ny >= 0 && ny < self.height as i32 {
-                    let cx = nx as usize;
-                    let cy = ny as usize;
-                    if cx < self.width && cy < self.height && !self.grid[cx][cy] {
-                        result.push((cx, cy));
-                    }
-                }
-            }
+        if self.diagonal_cost >= std::f64::consts::SQRT_2 {
+            (dx * dx + dy * dy).sqrt()
+        } else {
+            let (dx, dy) = (dx.abs(), dy.abs());
+            dx.max(dy) + (self.diagonal_cost - 1.0) * dx.min(dy)
         }
-        result
     }
 
-    /// Cost between `a` and `b`: 1.0 for orthogonal, √2 for diagonal.
+    /// Cost between `a` and `b`: 1.0 for orthogonal, `diagonal_cost` for diagonal.
     fn edge_cost(&self, a: Coord, b: Coord) -> f64 {
         let dx = (a.0 as i32 - b.0 as i32).abs();
         let dy = (a.1 as i32 - b.1 as i32).abs();
         if dx == 1 && dy == 1 {
-            std::f64::consts::SQRT_2
+            self.diagonal_cost
         } else {
             1.0
         }
@@ -125,7 +157,10 @@ impl FieldDStar {
     /// "Expand" a node `u`: relax all neighbors via true field cost.
     fn expand(&mut self, u: Coord) {
         let g_u = *self.g.get(&u).unwrap_or(&Self::INF);
-        for &nbr in &self.neighbors(u) {
+        for nbr in self.grid.neighbors(u, self.connectivity) {
+            if self.closed.contains(&nbr) {
+                continue;
+            }
             let c = self.edge_cost(u, nbr);
             let tentative = g_u + c;
             let g_n = *self.g.get(&nbr).unwrap_or(&Self::INF);
@@ -146,18 +181,82 @@ impl Pathfinder for FieldDStar {
     type Coord = Coord;
 
     fn compute_path(&mut self, start: Coord, goal: Coord) -> Option<Vec<Coord>> {
-        self.start = start;
-        self.goal = goal;
+        self.nodes_expanded = 0;
+        self.trace.clear();
+        if !self.full_replan {
+            if let Some((last_start, last_goal, path)) = &self.last_search {
+                if (*last_start, *last_goal) == (start, goal) {
+                    // `budget_exceeded` still describes the cached result
+                    return path.clone();
+                }
+            }
+        }
+        let path = self.search(start, goal);
+        // A cancelled search says nothing about the map, so don't reuse it
+        if !self.cancelled {
+            self.last_search = Some((start, goal, path.clone()));
+        }
+        path
+    }
+
+    fn update_obstacle(&mut self, coord: Coord, is_blocked: bool) {
+        if self.grid.set(coord, is_blocked) {
+            // No incremental repair: the next call searches from scratch
+            self.last_search = None;
+        }
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn search_trace(&self) -> Vec<Coord> {
+        self.trace.clone()
+    }
+
+    fn set_max_expansions(&mut self, max_expansions: usize) {
+        self.max_expansions = max_expansions;
+        self.last_search = None;
+    }
+
+    fn budget_exceeded(&self) -> bool {
+        self.budget_exceeded
+    }
+
+    fn set_cancel_flag(&mut self, flag: CancelFlag) {
+        self.cancel = flag;
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Distance-from-start (`g`) of every cell reached by the last search.
+    fn cost_field(&self) -> Option<HashMap<Coord, f64>> {
+        Some(
+            self.g
+                .iter()
+                .filter(|(_, g)| g.is_finite())
+                .map(|(&c, &g)| (c, g))
+                .collect(),
+        )
+    }
+}
 
+impl FieldDStar {
+    /// One search from scratch over the current grid.
+    fn search(&mut self, start: Coord, goal: Coord) -> Option<Vec<Coord>> {
+        self.budget_exceeded = false;
+        self.cancelled = false;
         self.g.clear();
         self.parent.clear();
         self.open_list.clear();
-
-        for y in 0..self.height {
-            for x in 0..self.width {
-                self.g.insert((x, y), Self::INF);
-            }
+        self.closed.clear();
+        if let Some(answer) = trivial_path(&self.grid, start, goal) {
+            return answer;
         }
+        self.start = start;
+        self.goal = goal;
         self.g.insert(start, 0.0);
 
         let f0 = (self.heuristic(start, goal) * 1000.0) as i64;
@@ -167,6 +266,10 @@ impl Pathfinder for FieldDStar {
         });
 
         while let Some(FDState { coord: u, f: _ }) = self.open_list.pop() {
+            // A cell pushed again at a lower g leaves stale copies behind
+            if !self.closed.insert(u) {
+                continue;
+            }
             if u == goal {
                 let mut path = Vec::new();
                 let mut current = goal;
@@ -183,16 +286,59 @@ impl Pathfinder for FieldDStar {
                 return Some(path);
             }
             self.expand(u);
+            self.nodes_expanded += 1;
+            self.trace.push(u);
+            if self.nodes_expanded > self.max_expansions {
+                self.budget_exceeded = true;
+                return None;
+            }
+            if self.cancel.should_stop(self.nodes_expanded) {
+                self.cancelled = true;
+                return None;
+            }
         }
 
         None
     }
+}
 
-    fn update_obstacle(&mut self, coord: Coord, is_blocked: bool) {
-        let (x, y) = coord;
-        if x < self.width && y < self.height {
-            self.grid[x][y] = is_blocked;
-        }
-        // No incremental repair—will be replanned from scratch next call.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_unchanged_map_reuses_the_last_search_until_something_changes() {
+        let grid = vec![vec![false; 8]; 8];
+        let mut planner = FieldDStar::new(grid.clone(), (0, 0), (7, 7)).with_full_replan(false);
+
+        let path = planner.compute_path((0, 0), (7, 7)).unwrap();
+        assert!(planner.nodes_expanded() > 0);
+        assert_eq!(planner.compute_path((0, 0), (7, 7)), Some(path.clone()));
+        assert_eq!(planner.nodes_expanded(), 0);
+
+        // Re-blocking nothing keeps the cache; a real change drops it
+        planner.update_obstacle((3, 5), false);
+        planner.compute_path((0, 0), (7, 7));
+        assert_eq!(planner.nodes_expanded(), 0);
+        planner.update_obstacle(path[3], true);
+        let detour = planner.compute_path((0, 0), (7, 7)).unwrap();
+        assert!(planner.nodes_expanded() > 0);
+        assert!(!detour.contains(&path[3]));
+        planner.compute_path((0, 0), (7, 7));
+        assert_eq!(planner.nodes_expanded(), 0);
+
+        // A new budget can change the answer, so it searches again too
+        planner.set_max_expansions(1);
+        assert_eq!(planner.compute_path((0, 0), (7, 7)), None);
+        assert!(planner.budget_exceeded());
+        planner.set_max_expansions(usize::MAX);
+        assert_eq!(planner.compute_path((0, 0), (7, 7)), Some(detour));
+        assert!(planner.nodes_expanded() > 0);
+
+        // A full replanner never reuses a search
+        let mut full = FieldDStar::new(grid, (0, 0), (7, 7));
+        full.compute_path((0, 0), (7, 7));
+        full.compute_path((0, 0), (7, 7));
+        assert!(full.nodes_expanded() > 0);
     }
 }
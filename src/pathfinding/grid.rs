@@ -0,0 +1,125 @@
+// src/pathfinding/grid.rs
+// -----------------------
+//
+// The occupancy grid the planners search: one flat `Vec<bool>` (true =
+// blocked) plus the bounds and neighbor rules every planner used to
+// re-implement on its own `Vec<Vec<bool>>`. Optional thin walls block the
+// border between two cells without taking up either of them.
+
+use crate::pathfinding::movement::{crosses_thin_wall, cuts_corner, Connectivity, ThinWalls};
+use crate::pathfinding::Coord;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    /// Row-major: cell `(x, y)` lives at `y * width + x`.
+    blocked: Vec<bool>,
+    thin_walls: ThinWalls,
+}
+
+impl Grid {
+    /// An all-free grid.
+    pub fn new(width: usize, height: usize) -> Self {
+        Grid {
+            width,
+            height,
+            blocked: vec![false; width * height],
+            thin_walls: ThinWalls::new(),
+        }
+    }
+
+    /// Convert the `grid[x][y]` columns the rest of the app uses. The height
+    /// comes from the first column; cells a short column lacks count as blocked.
+    pub fn from_columns(columns: &[Vec<bool>]) -> Self {
+        let width = columns.len();
+        let height = columns.first().map_or(0, Vec::len);
+        let mut grid = Grid::new(width, height);
+        for y in 0..height {
+            for (x, column) in columns.iter().enumerate() {
+                grid.blocked[y * width + x] = column.get(y).copied().unwrap_or(true);
+            }
+        }
+        grid
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, (x, y): Coord) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// Out-of-bounds cells count as blocked.
+    pub fn is_blocked(&self, (x, y): Coord) -> bool {
+        !self.in_bounds((x, y)) || self.blocked[y * self.width + x]
+    }
+
+    /// Mark `c` blocked or free. Returns whether the cell changed; writes
+    /// outside the grid are ignored.
+    pub fn set(&mut self, (x, y): Coord, blocked: bool) -> bool {
+        if !self.in_bounds((x, y)) {
+            return false;
+        }
+        let cell = &mut self.blocked[y * self.width + x];
+        let changed = *cell != blocked;
+        *cell = blocked;
+        changed
+    }
+
+    /// Replace the thin walls; see `movement::crosses_thin_wall` for how they
+    /// stop diagonal steps.
+    pub fn set_thin_walls(&mut self, thin_walls: ThinWalls) {
+        self.thin_walls = thin_walls;
+    }
+
+    pub fn thin_walls(&self) -> &ThinWalls {
+        &self.thin_walls
+    }
+
+    /// Whether the single step `from` -> `to` crosses a thin wall.
+    pub fn crosses_thin_wall(&self, from: Coord, to: Coord) -> bool {
+        crosses_thin_wall(from, to, &self.thin_walls)
+    }
+
+    /// Free neighbors of `c` including diagonals; a diagonal that would cut a
+    /// blocked corner is skipped.
+    pub fn neighbors8(&self, c: Coord) -> Vec<Coord> {
+        self.neighbors(c, Connectivity::Diagonal)
+    }
+
+    /// Free neighbors of `c` under `connectivity`, orthogonal ones first.
+    /// Steps across a thin wall are left out.
+    pub fn neighbors(&self, c: Coord, connectivity: Connectivity) -> Vec<Coord> {
+        self.adjacent(c, connectivity)
+            .into_iter()
+            .filter(|&next| {
+                !self.is_blocked(next)
+                    && (next.0 == c.0
+                        || next.1 == c.1
+                        || !cuts_corner(c, next, |b| self.is_blocked(b)))
+                    && !self.crosses_thin_wall(c, next)
+            })
+            .collect()
+    }
+
+    /// In-bounds cells one step from `c` under `connectivity`, blocked or not.
+    pub fn adjacent(&self, (x, y): Coord, connectivity: Connectivity) -> Vec<Coord> {
+        connectivity
+            .offsets()
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                let next = (
+                    x.checked_add_signed(dx as isize)?,
+                    y.checked_add_signed(dy as isize)?,
+                );
+                self.in_bounds(next).then_some(next)
+            })
+            .collect()
+    }
+}
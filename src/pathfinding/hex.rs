@@ -0,0 +1,232 @@
+// src/pathfinding/hex.rs
+// ----------------------
+//
+// A* on a hexagonal grid, with its own coordinate type: the `Pathfinder`
+// trait only asks for `Copy + Eq + Hash`, so nothing here is tied to square
+// `(usize, usize)` cells.
+//
+// Cells are pointy-top hexes stored as an "odd-r" offset grid: `(col, row)`
+// like the square planners' `grid[x][y]`, with odd rows shifted half a cell
+// right. Searching happens in axial `(q, r)`, where the six neighbors are
+// fixed offsets and the distance is a one-liner.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::pathfinding::grid::Grid;
+use crate::pathfinding::pathfinder_trait::{default_max_expansions, CancelFlag, Pathfinder};
+use crate::pathfinding::Coord;
+
+/// Axial hex coordinate. `r` is the row; `q` runs along it, skewed so that
+/// every neighbor is one of `DIRECTIONS` away.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+pub struct HexCoord {
+    pub q: i64,
+    pub r: i64,
+}
+
+impl HexCoord {
+    /// The six neighbor offsets, east first, counter-clockwise.
+    pub const DIRECTIONS: [(i64, i64); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+    /// The hex stored at offset cell `(col, row)`.
+    pub fn from_offset((col, row): Coord) -> Self {
+        let (col, row) = (col as i64, row as i64);
+        HexCoord {
+            q: col - (row - (row & 1)) / 2,
+            r: row,
+        }
+    }
+
+    /// Offset cell of this hex; `None` when it falls left of or above the grid.
+    pub fn to_offset(self) -> Option<Coord> {
+        let col = self.q + (self.r - (self.r & 1)) / 2;
+        (col >= 0 && self.r >= 0).then_some((col as usize, self.r as usize))
+    }
+
+    /// Steps between two hexes on an open grid.
+    pub fn distance(self, other: HexCoord) -> usize {
+        let (dq, dr) = (self.q - other.q, self.r - other.r);
+        ((dq.abs() + dr.abs() + (dq + dr).abs()) / 2) as usize
+    }
+
+    pub fn neighbors(self) -> impl Iterator<Item = HexCoord> {
+        Self::DIRECTIONS.iter().map(move |&(dq, dr)| HexCoord {
+            q: self.q + dq,
+            r: self.r + dr,
+        })
+    }
+}
+
+/// Open-list entry: lowest f first, then lowest h (closer to the goal), then
+/// coordinate order so equal entries still pop deterministically.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct HexNode {
+    f: usize,
+    h: usize,
+    coord: HexCoord,
+}
+
+impl Ord for HexNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Reverse((self.f, self.h, self.coord)).cmp(&Reverse((other.f, other.h, other.coord)))
+    }
+}
+
+impl PartialOrd for HexNode {
+    fn partial_cmp(&self, other: &HexNode) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub struct HexAStar {
+    grid: Grid,          // Offset cells, `(col, row)`
+    use_heuristic: bool, // false = Dijkstra
+    nodes_expanded: usize,
+    trace: Vec<HexCoord>, // Hexes in the order the last search expanded them
+    max_expansions: usize,
+    budget_exceeded: bool,
+    cancel: CancelFlag, // Polled during the search; shared with whoever may cancel it
+    cancelled: bool,
+}
+
+impl HexAStar {
+    /// Plan over `grid[col][row]` (true = blocked), read as odd-r hexes.
+    pub fn new(grid: Vec<Vec<bool>>) -> Self {
+        let grid = Grid::from_columns(&grid);
+        let max_expansions = default_max_expansions(grid.width(), grid.height());
+        HexAStar {
+            grid,
+            use_heuristic: true,
+            nodes_expanded: 0,
+            trace: Vec::new(),
+            max_expansions,
+            budget_exceeded: false,
+            cancel: CancelFlag::new(),
+            cancelled: false,
+        }
+    }
+
+    /// Drop the heuristic to run Dijkstra; every step costs the same, so it
+    /// expands in rings around the start.
+    pub fn with_heuristic(mut self, use_heuristic: bool) -> Self {
+        self.use_heuristic = use_heuristic;
+        self
+    }
+
+    /// Off-grid hexes count as blocked.
+    fn is_blocked(&self, hex: HexCoord) -> bool {
+        hex.to_offset()
+            .is_none_or(|cell| self.grid.is_blocked(cell))
+    }
+
+    fn heuristic(&self, a: HexCoord, b: HexCoord) -> usize {
+        if self.use_heuristic {
+            a.distance(b)
+        } else {
+            0
+        }
+    }
+}
+
+impl Pathfinder for HexAStar {
+    type Coord = HexCoord;
+
+    fn compute_path(&mut self, start: HexCoord, goal: HexCoord) -> Option<Vec<HexCoord>> {
+        self.nodes_expanded = 0;
+        self.trace.clear();
+        self.budget_exceeded = false;
+        self.cancelled = false;
+        if self.is_blocked(start) || self.is_blocked(goal) {
+            return None;
+        }
+
+        let mut open_set = BinaryHeap::new();
+        let mut closed: HashSet<HexCoord> = HashSet::new();
+        let mut came_from: HashMap<HexCoord, HexCoord> = HashMap::new();
+        let mut g_score: HashMap<HexCoord, usize> = HashMap::new();
+
+        g_score.insert(start, 0);
+        let h = self.heuristic(start, goal);
+        open_set.push(HexNode {
+            f: h,
+            h,
+            coord: start,
+        });
+
+        while let Some(HexNode { coord: current, .. }) = open_set.pop() {
+            if current == goal {
+                let mut path = vec![current];
+                let mut cur = current;
+                while let Some(&prev) = came_from.get(&cur) {
+                    cur = prev;
+                    path.push(cur);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if !closed.insert(current) {
+                continue;
+            }
+            self.nodes_expanded += 1;
+            self.trace.push(current);
+            if self.nodes_expanded > self.max_expansions {
+                self.budget_exceeded = true;
+                return None;
+            }
+            if self.cancel.should_stop(self.nodes_expanded) {
+                self.cancelled = true;
+                return None;
+            }
+
+            let tentative = g_score[&current] + 1;
+            for neighbor in current.neighbors() {
+                if self.is_blocked(neighbor) || closed.contains(&neighbor) {
+                    continue;
+                }
+                if tentative < *g_score.get(&neighbor).unwrap_or(&usize::MAX) {
+                    came_from.insert(neighbor, current);
+                    g_score.insert(neighbor, tentative);
+                    let h = self.heuristic(neighbor, goal);
+                    open_set.push(HexNode {
+                        f: tentative + h,
+                        h,
+                        coord: neighbor,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn update_obstacle(&mut self, hex: HexCoord, is_blocked: bool) {
+        if let Some(cell) = hex.to_offset() {
+            self.grid.set(cell, is_blocked);
+        }
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn search_trace(&self) -> Vec<HexCoord> {
+        self.trace.clone()
+    }
+
+    fn set_max_expansions(&mut self, max_expansions: usize) {
+        self.max_expansions = max_expansions;
+    }
+
+    fn budget_exceeded(&self) -> bool {
+        self.budget_exceeded
+    }
+
+    fn set_cancel_flag(&mut self, flag: CancelFlag) {
+        self.cancel = flag;
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
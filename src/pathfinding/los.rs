@@ -0,0 +1,95 @@
+// src/pathfinding/los.rs
+// ----------------------
+//
+// Integer Bresenham line of sight, shared by any-angle planning (Theta*),
+// path smoothing and `movement::densify`.
+
+use crate::pathfinding::movement::cuts_corner;
+use crate::pathfinding::Coord;
+
+/// Cells on the integer Bresenham line from `a` to `b`, both ends included.
+/// Consecutive cells are always 8-adjacent.
+pub fn bresenham_cells(a: Coord, b: Coord) -> Vec<Coord> {
+    let (mut x, mut y) = (a.0 as i64, a.1 as i64);
+    let (x1, y1) = (b.0 as i64, b.1 as i64);
+    let dx = (x1 - x).abs();
+    let dy = -(y1 - y).abs();
+    let sx = if x < x1 { 1 } else { -1 };
+    let sy = if y < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+
+    let mut cells = vec![a];
+    while (x, y) != (x1, y1) {
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        cells.push((x as usize, y as usize));
+    }
+    cells
+}
+
+/// Whether every cell on the line from `a` to `b` is free and no diagonal
+/// step squeezes past a blocked corner (so two diagonally adjacent blocked
+/// cells always break sight).
+pub fn line_of_sight(a: Coord, b: Coord, blocked: &dyn Fn(Coord) -> bool) -> bool {
+    let cells = bresenham_cells(a, b);
+    cells.iter().all(|&c| !blocked(c))
+        && cells
+            .windows(2)
+            .all(|w| w[0].0 == w[1].0 || w[0].1 == w[1].1 || !cuts_corner(w[0], w[1], blocked))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn straight_lines_cover_every_cell_between() {
+        assert_eq!(
+            bresenham_cells((1, 2), (4, 2)),
+            vec![(1, 2), (2, 2), (3, 2), (4, 2)]
+        );
+        assert_eq!(
+            bresenham_cells((3, 3), (3, 0)),
+            vec![(3, 3), (3, 2), (3, 1), (3, 0)]
+        );
+        assert_eq!(bresenham_cells((2, 2), (2, 2)), vec![(2, 2)]);
+    }
+
+    #[test]
+    fn shallow_and_steep_lines_step_one_cell_at_a_time() {
+        let shallow = bresenham_cells((0, 0), (6, 2));
+        assert_eq!(shallow.len(), 7);
+        let steep = bresenham_cells((0, 0), (2, 6));
+        assert_eq!(steep.len(), 7);
+        // Mirrored endpoints walk the transposed cells
+        assert_eq!(
+            steep,
+            shallow.iter().map(|&(x, y)| (y, x)).collect::<Vec<_>>()
+        );
+        for line in [shallow, steep, bresenham_cells((6, 5), (0, 1))] {
+            assert!(line
+                .windows(2)
+                .all(|w| w[0].0.abs_diff(w[1].0) <= 1 && w[0].1.abs_diff(w[1].1) <= 1));
+        }
+    }
+
+    #[test]
+    fn sight_is_broken_by_a_blocked_cell_or_corner() {
+        let open = |_: Coord| false;
+        assert!(line_of_sight((0, 0), (5, 3), &open));
+        assert!(!line_of_sight((0, 0), (5, 0), &|c| c == (3, 0)));
+
+        // Two diagonally adjacent walls leave no gap to see through
+        let corner = |c: Coord| c == (1, 0) || c == (0, 1);
+        assert!(!line_of_sight((0, 0), (1, 1), &corner));
+        assert!(!line_of_sight((0, 0), (3, 3), &corner));
+        assert!(line_of_sight((0, 0), (3, 0), &|c| c == (0, 1)));
+    }
+}
@@ -1,15 +1,370 @@
 // src/pathfinding/mod.rs
 
+pub mod analysis;
 pub mod astar;
 pub mod dstar_lite;
 pub mod field_dstar;
+pub mod grid;
+pub mod hex;
+pub mod los;
+pub mod movement;
 pub mod pathfinder_trait;
+pub mod theta_star;
+pub mod tie_break;
+
+use std::collections::HashMap;
+
+use movement::{ThinWalls, DEFAULT_DIAGONAL_COST};
 
 // Re-export the types so others can write, e.g. `use crate::pathfinding::AStar;`
 pub use astar::AStar;
 pub use dstar_lite::DStarLite;
 pub use field_dstar::FieldDStar;
+pub use movement::Connectivity;
 pub use pathfinder_trait::Pathfinder;
+pub use theta_star::ThetaStar;
+pub use tie_break::TieBreak;
+
+/// Algorithm names accepted by `Rover::set_algorithm`.
+pub const ALGORITHMS: [&str; 6] = [
+    "D*-Lite",
+    "A*",
+    "Dijkstra",
+    "Greedy Best-First",
+    "Field D*",
+    "Theta*",
+];
+
+/// Algorithm plus movement in one name, as offered in the dropdown. A bare
+/// name from `ALGORITHMS` instead follows the separate `allow_diagonals` flag.
+pub const ALGORITHM_PRESETS: [&str; 11] = [
+    "D*-Lite (4-dir)",
+    "D*-Lite (8-dir)",
+    "A* (4-dir)",
+    "A* (8-dir)",
+    "Dijkstra (4-dir)",
+    "Dijkstra (8-dir)",
+    "Greedy Best-First (4-dir)",
+    "Greedy Best-First (8-dir)",
+    "Field D* (4-dir)",
+    "Field D* (8-dir)",
+    "Theta* (any-angle)",
+];
+
+/// Split a preset or bare name into the algorithm and, for presets, the
+/// connectivity it names. `None` if it is neither.
+pub fn parse_algorithm(name: &str) -> Option<(&'static str, Option<Connectivity>)> {
+    let (base, movement) = match name.trim().split_once(" (") {
+        Some((base, movement)) => (base, Some(movement.strip_suffix(')')?)),
+        None => (name.trim(), None),
+    };
+    let algorithm = ALGORITHMS.into_iter().find(|&a| a == base)?;
+    let connectivity = match (algorithm, movement) {
+        (_, None) => None,
+        ("Theta*", Some("any-angle" | "8-dir")) => Some(Connectivity::Diagonal),
+        ("Theta*", Some(_)) => return None,
+        (_, Some("4-dir")) => Some(Connectivity::Orthogonal),
+        (_, Some("8-dir")) => Some(Connectivity::Diagonal),
+        (_, Some(_)) => return None,
+    };
+    Some((algorithm, connectivity))
+}
+
+/// Whether `algorithm` plans with terrain costs. The others treat every free
+/// cell as cost 1.0, so weights given to them would only skew the reported cost.
+pub fn uses_terrain(algorithm: &str) -> bool {
+    matches!(algorithm, "A*" | "Dijkstra" | "Greedy Best-First")
+}
+
+/// Everything besides the map and endpoints that shapes a planner. Each
+/// algorithm takes the settings it supports and ignores the rest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlannerOptions {
+    pub connectivity: Connectivity,
+    pub tie_break: TieBreak,
+    /// Cost of one diagonal step (every 8-connected planner but Theta*, whose
+    /// any-angle segments are always Euclidean).
+    pub diagonal_cost: f64,
+    /// Cost per change of direction (A*, Dijkstra and D*-Lite).
+    pub turn_penalty: f64,
+    /// Entry cost per weighted cell (A*, Dijkstra and Greedy Best-First); absent = 1.0.
+    pub terrain_costs: HashMap<Coord, f64>,
+    /// Blocked cell borders (every square-grid planner).
+    pub thin_walls: ThinWalls,
+}
+
+impl PlannerOptions {
+    /// Plain `connectivity` moves: cross-product tie-breaking, √2 diagonals,
+    /// no turn penalty, terrain or thin walls.
+    pub fn new(connectivity: Connectivity) -> Self {
+        Self {
+            connectivity,
+            tie_break: TieBreak::CrossProduct,
+            diagonal_cost: DEFAULT_DIAGONAL_COST,
+            turn_penalty: 0.0,
+            terrain_costs: HashMap::new(),
+            thin_walls: ThinWalls::new(),
+        }
+    }
+}
+
+/// The planner for `algorithm`, a name from `ALGORITHMS` (anything else gets
+/// D*-Lite, the app's default), on `grid[x][y]` from `start` to `goal`.
+/// The grid is taken as given; `crate::solve` rejects ragged ones, and cells
+/// missing from a short column count as blocked.
+pub fn build_planner(
+    algorithm: &str,
+    grid: Vec<Vec<bool>>,
+    start: Coord,
+    goal: Coord,
+    options: &PlannerOptions,
+) -> Box<dyn Pathfinder<Coord = Coord>> {
+    let PlannerOptions {
+        connectivity,
+        tie_break,
+        diagonal_cost,
+        turn_penalty,
+        ..
+    } = *options;
+    let (terrain_costs, thin_walls) = (options.terrain_costs.clone(), options.thin_walls.clone());
+    match algorithm {
+        "A*" => Box::new(
+            AStar::with_tie_break(grid, tie_break)
+                .with_connectivity(connectivity)
+                .with_diagonal_cost(diagonal_cost)
+                .with_turn_penalty(turn_penalty)
+                .with_terrain_costs(terrain_costs)
+                .with_thin_walls(thin_walls),
+        ),
+        "Dijkstra" => Box::new(
+            AStar::with_tie_break(grid, tie_break)
+                .with_connectivity(connectivity)
+                .with_diagonal_cost(diagonal_cost)
+                .with_turn_penalty(turn_penalty)
+                .with_terrain_costs(terrain_costs)
+                .with_thin_walls(thin_walls)
+                .with_heuristic(false),
+        ),
+        "Greedy Best-First" => Box::new(
+            AStar::with_tie_break(grid, tie_break)
+                .with_connectivity(connectivity)
+                .with_diagonal_cost(diagonal_cost)
+                .with_terrain_costs(terrain_costs)
+                .with_thin_walls(thin_walls)
+                .with_greedy(true),
+        ),
+        "Field D*" => Box::new(
+            FieldDStar::new(grid, start, goal)
+                .with_connectivity(connectivity)
+                .with_diagonal_cost(diagonal_cost)
+                .with_thin_walls(thin_walls)
+                .with_full_replan(false),
+        ),
+        "Theta*" => Box::new(ThetaStar::new(grid).with_thin_walls(thin_walls)),
+        _ => Box::new(
+            DStarLite::with_tie_break(grid, start, goal, tie_break)
+                .with_connectivity(connectivity)
+                .with_diagonal_cost(diagonal_cost)
+                .with_turn_penalty(turn_penalty)
+                .with_thin_walls(thin_walls),
+        ),
+    }
+}
+
+/// Dropdown preset for `algorithm` moving with `connectivity`.
+pub fn preset_name(algorithm: &str, connectivity: Connectivity) -> String {
+    match (algorithm, connectivity) {
+        ("Theta*", _) => "Theta* (any-angle)".to_string(),
+        (algorithm, Connectivity::Orthogonal) => format!("{} (4-dir)", algorithm),
+        (algorithm, Connectivity::Diagonal) => format!("{} (8-dir)", algorithm),
+    }
+}
 
 // A common Coord alias (each algorithm uses `(usize, usize)` for grid coords)
 pub type Coord = (usize, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagonal_steps(path: &[Coord]) -> usize {
+        path.windows(2)
+            .filter(|w| w[0].0 != w[1].0 && w[0].1 != w[1].1)
+            .count()
+    }
+
+    #[test]
+    fn every_8_connected_planner_takes_the_diagonal_cost() {
+        let grid = vec![vec![false; 10]; 10];
+        for algorithm in ["D*-Lite", "A*", "Dijkstra", "Greedy Best-First", "Field D*"] {
+            let mut options = PlannerOptions::new(Connectivity::Diagonal);
+            let path = build_planner(algorithm, grid.clone(), (0, 0), (5, 3), &options)
+                .compute_path((0, 0), (5, 3))
+                .unwrap();
+            assert_eq!(diagonal_steps(&path), 3, "{}", algorithm);
+
+            options.diagonal_cost = f64::INFINITY;
+            let path = build_planner(algorithm, grid.clone(), (0, 0), (5, 3), &options)
+                .compute_path((0, 0), (5, 3))
+                .unwrap();
+            assert_eq!(diagonal_steps(&path), 0, "{}", algorithm);
+            assert_eq!(path.len(), 9, "{}", algorithm);
+        }
+    }
+
+    #[test]
+    fn no_8_connected_planner_slips_between_diagonal_walls() {
+        // A 2x2 checkerboard: the only way across is the cut corner
+        let mut checkerboard = vec![vec![false; 2]; 2];
+        checkerboard[1][0] = true;
+        checkerboard[0][1] = true;
+
+        // A wall along x + y = 4 that only diagonal steps could squeeze through
+        let barrier: Vec<Vec<bool>> = (0..6)
+            .map(|x| (0..6).map(|y| x + y == 4).collect())
+            .collect();
+
+        let options = PlannerOptions::new(Connectivity::Diagonal);
+        for algorithm in ALGORITHMS {
+            let mut planner =
+                build_planner(algorithm, checkerboard.clone(), (0, 0), (1, 1), &options);
+            assert!(
+                planner.compute_path((0, 0), (1, 1)).is_none(),
+                "{}",
+                algorithm
+            );
+
+            let mut planner = build_planner(algorithm, barrier.clone(), (0, 0), (5, 5), &options);
+            assert!(
+                planner.compute_path((0, 0), (5, 5)).is_none(),
+                "{}",
+                algorithm
+            );
+        }
+    }
+
+    #[test]
+    fn a_turn_penalty_cuts_the_number_of_turns() {
+        let grid = vec![vec![false; 8]; 8];
+        for algorithm in ["A*", "D*-Lite"] {
+            let mut turns = Vec::new();
+            for penalty in [0.0, 2.0, 1e300] {
+                let mut options = PlannerOptions::new(Connectivity::Orthogonal);
+                options.turn_penalty = penalty;
+                let path = build_planner(algorithm, grid.clone(), (0, 0), (6, 6), &options)
+                    .compute_path((0, 0), (6, 6))
+                    .unwrap();
+                assert_eq!(path.len(), 13, "{}", algorithm);
+                turns.push(movement::count_turns(&path));
+            }
+            assert!(turns[0] > 1, "{}: {:?}", algorithm, turns);
+            assert_eq!(turns[1..], [1, 1], "{}", algorithm);
+        }
+    }
+
+    #[test]
+    fn no_planner_crosses_a_thin_wall() {
+        let grid = vec![vec![false; 6]; 6];
+        // A fence between x = 2 and x = 3, open only at the bottom row
+        let mut options = PlannerOptions::new(Connectivity::Diagonal);
+        options.thin_walls = (0..5)
+            .map(|y| movement::thin_wall((2, y), (3, y)))
+            .collect();
+        // The goal sealed in on all four sides
+        let mut sealed = PlannerOptions::new(Connectivity::Diagonal);
+        sealed.thin_walls = [(4, 3), (4, 1), (3, 2), (5, 2)]
+            .into_iter()
+            .map(|side| movement::thin_wall((4, 2), side))
+            .collect();
+
+        for algorithm in ALGORITHMS {
+            for connectivity in [Connectivity::Orthogonal, Connectivity::Diagonal] {
+                options.connectivity = connectivity;
+                sealed.connectivity = connectivity;
+                let path = build_planner(algorithm, grid.clone(), (0, 0), (5, 0), &options)
+                    .compute_path((0, 0), (5, 0))
+                    .unwrap();
+                let path = movement::densify(&path);
+                assert!(
+                    path.windows(2).all(|w| !movement::crosses_thin_wall(
+                        w[0],
+                        w[1],
+                        &options.thin_walls
+                    )),
+                    "{} {:?}: {:?}",
+                    algorithm,
+                    connectivity,
+                    path
+                );
+                assert!(path.contains(&(2, 5)), "{} {:?}", algorithm, connectivity);
+
+                let mut planner = build_planner(algorithm, grid.clone(), (0, 0), (4, 2), &sealed);
+                assert_eq!(
+                    planner.compute_path((0, 0), (4, 2)),
+                    None,
+                    "{} {:?}",
+                    algorithm,
+                    connectivity
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn a_walled_in_goal_is_settled_within_the_budget() {
+        // Draining the grid expands every reachable cell, so the budget only
+        // holds if no cell is expanded twice
+        let mut grid = vec![vec![false; 120]; 120];
+        for (x, y) in [(59, 60), (61, 60), (60, 59), (60, 61)] {
+            grid[x][y] = true;
+        }
+        let reachable = 120 * 120 - 5;
+        for algorithm in ["A*", "Field D*"] {
+            for connectivity in [Connectivity::Orthogonal, Connectivity::Diagonal] {
+                let options = PlannerOptions::new(connectivity);
+                let mut planner =
+                    build_planner(algorithm, grid.clone(), (0, 0), (60, 60), &options);
+                planner.set_max_expansions(reachable);
+                assert_eq!(planner.compute_path((0, 0), (60, 60)), None);
+                assert!(
+                    !planner.budget_exceeded(),
+                    "{} {:?}",
+                    algorithm,
+                    connectivity
+                );
+                assert_eq!(
+                    planner.nodes_expanded(),
+                    reachable,
+                    "{} {:?}",
+                    algorithm,
+                    connectivity
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn a_ragged_grid_reads_missing_cells_as_blocked() {
+        // Column 2 only has y = 0, so the straight row y = 3 is cut there
+        let mut grid = vec![vec![false; 5]; 5];
+        grid[2].truncate(1);
+        for algorithm in ALGORITHMS {
+            for connectivity in [Connectivity::Orthogonal, Connectivity::Diagonal] {
+                let options = PlannerOptions::new(connectivity);
+                let mut planner = build_planner(algorithm, grid.clone(), (0, 3), (4, 3), &options);
+                let path = planner.compute_path((0, 3), (4, 3)).unwrap();
+                let path = movement::densify(&path);
+                assert!(
+                    path.iter().all(|&(x, y)| x != 2 || y == 0),
+                    "{}: {:?}",
+                    algorithm,
+                    path
+                );
+
+                // A goal in the missing part is never reached
+                let mut planner = build_planner(algorithm, grid.clone(), (0, 3), (2, 3), &options);
+                assert_eq!(planner.compute_path((0, 3), (2, 3)), None, "{}", algorithm);
+            }
+        }
+    }
+}
@@ -0,0 +1,252 @@
+// src/pathfinding/movement.rs
+// ---------------------------
+//
+// Shared movement rules: which single steps between cells are legal for a
+// given connectivity. Used both by 8-connected planners and by the rover's
+// per-step validation so the two can never disagree.
+
+use std::collections::HashSet;
+
+use crate::pathfinding::los::bresenham_cells;
+use crate::pathfinding::Coord;
+
+/// Blocked transitions between orthogonally adjacent cells ("thin walls"),
+/// each stored as `thin_wall(a, b)` so the order of the pair never matters.
+pub type ThinWalls = HashSet<(Coord, Coord)>;
+
+/// Cost of one diagonal step unless a planner is told otherwise; orthogonal
+/// steps always cost 1.0.
+pub const DEFAULT_DIAGONAL_COST: f64 = std::f64::consts::SQRT_2;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Connectivity {
+    /// 4-connected: up, down, left, right.
+    Orthogonal,
+    /// 8-connected: orthogonal moves plus diagonals.
+    Diagonal,
+}
+
+impl Connectivity {
+    const ORTHOGONAL_OFFSETS: [(i32, i32); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+    const DIAGONAL_OFFSETS: [(i32, i32); 8] = [
+        (0, -1),
+        (0, 1),
+        (-1, 0),
+        (1, 0),
+        (-1, -1),
+        (1, -1),
+        (-1, 1),
+        (1, 1),
+    ];
+
+    /// Connectivity an algorithm plans with under the shared "Allow diagonals"
+    /// setting. Theta* is any-angle, so it always moves diagonally.
+    pub fn for_algorithm(algorithm: &str, allow_diagonals: bool) -> Self {
+        if algorithm == "Theta*" || allow_diagonals {
+            Connectivity::Diagonal
+        } else {
+            Connectivity::Orthogonal
+        }
+    }
+
+    pub fn allows_diagonals(self) -> bool {
+        self == Connectivity::Diagonal
+    }
+
+    /// Single-step offsets, orthogonal ones first.
+    pub fn offsets(self) -> &'static [(i32, i32)] {
+        match self {
+            Connectivity::Orthogonal => &Self::ORTHOGONAL_OFFSETS,
+            Connectivity::Diagonal => &Self::DIAGONAL_OFFSETS,
+        }
+    }
+
+    /// Admissible distance estimate: Manhattan for 4-connected grids, octile
+    /// for 8-connected ones with diagonals costing `diagonal_cost`. From 2.0
+    /// up (including ∞) two straight steps are never dearer than a diagonal,
+    /// so the estimate is Manhattan again.
+    pub fn heuristic(self, a: Coord, b: Coord, diagonal_cost: f64) -> f64 {
+        let dx = a.0.abs_diff(b.0) as f64;
+        let dy = a.1.abs_diff(b.1) as f64;
+        match self {
+            Connectivity::Orthogonal => dx + dy,
+            Connectivity::Diagonal => dx.max(dy) + (diagonal_cost.min(2.0) - 1.0) * dx.min(dy),
+        }
+    }
+}
+
+/// Unit offset of the last step taken; `None` before the first move, or
+/// whenever turns are free and planners don't need to tell headings apart.
+pub type Heading = Option<(i32, i32)>;
+
+/// Largest turn penalty planners take, in straight steps. Anything above it
+/// makes no practical difference and would overflow integer step costs.
+pub const MAX_TURN_PENALTY: f64 = 1000.0;
+
+/// Most search states a cell can have once headings count: none plus 8 directions.
+pub const MAX_HEADINGS_PER_CELL: usize = 9;
+
+/// Heading after stepping from `from` to the adjacent cell `to`; `None` if
+/// the two cells aren't neighbors.
+pub fn arrival_heading(from: Coord, to: Coord) -> Heading {
+    let dx = to.0 as i64 - from.0 as i64;
+    let dy = to.1 as i64 - from.1 as i64;
+    ((dx, dy) != (0, 0) && dx.abs() <= 1 && dy.abs() <= 1).then_some((dx as i32, dy as i32))
+}
+
+/// Number of direction changes along a path of single-cell steps.
+pub fn count_turns(path: &[Coord]) -> usize {
+    let headings: Vec<Heading> = path
+        .windows(2)
+        .map(|w| arrival_heading(w[0], w[1]))
+        .collect();
+    headings.windows(2).filter(|h| h[0] != h[1]).count()
+}
+
+/// Extra cost of leaving along `step` when the last move was along `heading`.
+pub fn turn_cost(heading: Heading, step: (i32, i32), penalty: f64) -> f64 {
+    match heading {
+        Some(h) if h != step => penalty,
+        _ => 0.0,
+    }
+}
+
+/// A diagonal step cuts a corner when either orthogonal cell it passes between is blocked.
+pub fn cuts_corner(from: Coord, to: Coord, is_blocked: impl Fn(Coord) -> bool) -> bool {
+    is_blocked((to.0, from.1)) || is_blocked((from.0, to.1))
+}
+
+/// Key for the thin wall between `a` and `b`: the smaller cell first.
+pub fn thin_wall(a: Coord, b: Coord) -> (Coord, Coord) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Whether a single step from `from` to `to` crosses a thin wall. A diagonal
+/// step is stopped by any of the four walls meeting at the corner it passes,
+/// just as a blocked cell beside it would stop it.
+pub fn crosses_thin_wall(from: Coord, to: Coord, thin_walls: &ThinWalls) -> bool {
+    if thin_walls.is_empty() {
+        return false;
+    }
+    if from.0 == to.0 || from.1 == to.1 {
+        return thin_walls.contains(&thin_wall(from, to));
+    }
+    let (side_a, side_b) = ((to.0, from.1), (from.0, to.1));
+    [(from, side_a), (from, side_b), (side_a, to), (side_b, to)]
+        .iter()
+        .any(|&(a, b)| thin_walls.contains(&thin_wall(a, b)))
+}
+
+/// Whether the rover may move from `from` to `to` in a single step.
+pub fn is_valid_step(
+    from: Coord,
+    to: Coord,
+    connectivity: Connectivity,
+    thin_walls: &ThinWalls,
+    is_blocked: impl Fn(Coord) -> bool,
+) -> bool {
+    if is_blocked(to) || crosses_thin_wall(from, to, thin_walls) {
+        return false;
+    }
+    match (from.0.abs_diff(to.0), from.1.abs_diff(to.1)) {
+        (0, 1) | (1, 0) => true,
+        (1, 1) => connectivity.allows_diagonals() && !cuts_corner(from, to, is_blocked),
+        _ => false,
+    }
+}
+
+/// First consecutive pair in `path` that isn't a legal single step under
+/// `connectivity`, e.g. a gap, a blocked cell, a corner cut or a thin wall.
+/// `None` when the whole path is walkable.
+pub fn first_invalid_step(
+    path: &[Coord],
+    connectivity: Connectivity,
+    thin_walls: &ThinWalls,
+    is_blocked: impl Fn(Coord) -> bool,
+) -> Option<(Coord, Coord)> {
+    path.windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|&(from, to)| !is_valid_step(from, to, connectivity, thin_walls, &is_blocked))
+}
+
+/// Expand sparse waypoints (e.g. from Theta*) into single-cell steps.
+/// Paths that are already dense come back unchanged.
+pub fn densify(waypoints: &[Coord]) -> Vec<Coord> {
+    let mut path: Vec<Coord> = waypoints.first().copied().into_iter().collect();
+    for pair in waypoints.windows(2) {
+        path.extend(bresenham_cells(pair[0], pair[1]).into_iter().skip(1));
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orthogonal_movement_refuses_diagonals_and_jumps() {
+        let open = |_: Coord| false;
+        let walls = ThinWalls::new();
+        let path = [(0, 0), (1, 0), (1, 1), (1, 2)];
+        assert_eq!(
+            first_invalid_step(&path, Connectivity::Orthogonal, &walls, open),
+            None
+        );
+
+        let diagonal = [(0, 0), (1, 1), (2, 2)];
+        assert_eq!(
+            first_invalid_step(&diagonal, Connectivity::Orthogonal, &walls, open),
+            Some(((0, 0), (1, 1)))
+        );
+        assert!(!is_valid_step(
+            (0, 0),
+            (0, 2),
+            Connectivity::Diagonal,
+            &walls,
+            open
+        ));
+        assert!(!is_valid_step(
+            (3, 3),
+            (3, 3),
+            Connectivity::Diagonal,
+            &walls,
+            open
+        ));
+    }
+
+    #[test]
+    fn diagonal_movement_never_cuts_a_blocked_corner() {
+        let walls = ThinWalls::new();
+        let diagonal = [(0, 0), (1, 1), (2, 2)];
+        assert_eq!(
+            first_invalid_step(&diagonal, Connectivity::Diagonal, &walls, |_| false),
+            None
+        );
+
+        // (1, 0) sits beside the (0, 0) → (1, 1) step; (2, 1) beside the next one
+        for blocked in [(1, 0), (0, 1)] {
+            assert!(!is_valid_step(
+                (0, 0),
+                (1, 1),
+                Connectivity::Diagonal,
+                &walls,
+                |c| c == blocked
+            ));
+        }
+        assert_eq!(
+            first_invalid_step(&diagonal, Connectivity::Diagonal, &walls, |c| c == (2, 1)),
+            Some(((1, 1), (2, 2)))
+        );
+        assert!(!is_valid_step(
+            (0, 0),
+            (1, 1),
+            Connectivity::Diagonal,
+            &walls,
+            |c| c == (1, 1)
+        ));
+    }
+}
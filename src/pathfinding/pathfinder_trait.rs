@@ -3,7 +3,13 @@
 //
 // Defines the common interface for A*, D*-Lite and Field D*.
 
+use std::collections::HashMap;
 use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::pathfinding::grid::Grid;
+use crate::pathfinding::Coord;
 
 /// Every pathfinder works on discrete grid coordinates `(usize, usize)`.
 /// This trait requires `Coord: Copy + Eq + Hash`.
@@ -20,4 +26,134 @@ pub trait Pathfinder {
     /// `is_blocked = true` means “place an obstacle at `coord`,”
     /// `is_blocked = false` means “remove obstacle at `coord`.”
     fn update_obstacle(&mut self, coord: Self::Coord, is_blocked: bool);
+
+    /// Apply several `(coord, is_blocked)` changes before the next `compute_path`.
+    /// Planners that can share work across the batch (D*-Lite) override this;
+    /// by default each change goes through `update_obstacle`.
+    fn update_obstacles(&mut self, changes: &[(Self::Coord, bool)]) {
+        for &(coord, is_blocked) in changes {
+            self.update_obstacle(coord, is_blocked);
+        }
+    }
+
+    /// Number of nodes expanded by the most recent `compute_path` call.
+    fn nodes_expanded(&self) -> usize;
+
+    /// Cells in the order the most recent `compute_path` expanded them, one
+    /// per expansion. A cell searched under several headings repeats.
+    fn search_trace(&self) -> Vec<Self::Coord> {
+        Vec::new()
+    }
+
+    /// Cap on expansions per `compute_path`; past it the search gives up with `None`.
+    #[allow(dead_code)]
+    fn set_max_expansions(&mut self, max_expansions: usize);
+
+    /// Whether the last `compute_path` returned `None` because it ran out of budget
+    /// rather than because no path exists.
+    fn budget_exceeded(&self) -> bool;
+
+    /// Share a flag the search polls every `CANCEL_CHECK_INTERVAL` expansions;
+    /// once it is set, `compute_path` gives up with `None`.
+    fn set_cancel_flag(&mut self, flag: CancelFlag);
+
+    /// Whether the last `compute_path` returned `None` because it was cancelled
+    /// rather than because no path exists.
+    fn cancelled(&self) -> bool;
+
+    /// Finite per-cell cost values from the last search, for planners that keep them.
+    fn cost_field(&self) -> Option<HashMap<Self::Coord, f64>> {
+        None
+    }
+}
+
+/// Expansions between looks at the cancel flag, so polling it stays cheap.
+pub const CANCEL_CHECK_INTERVAL: usize = 256;
+
+/// A "stop searching" switch. Clones share one flag, so a copy kept outside
+/// the planner can cancel a search in progress.
+#[derive(Clone, Debug, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Clear the flag before the next search.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Poll point for search loops: true when `expanded` is a multiple of
+    /// `CANCEL_CHECK_INTERVAL` (including 0) and the flag is set.
+    pub fn should_stop(&self, expanded: usize) -> bool {
+        expanded.is_multiple_of(CANCEL_CHECK_INTERVAL) && self.is_cancelled()
+    }
+}
+
+/// Default search budget: a few expansions per cell, so small grids are never
+/// cut short but huge open grids can't freeze the tab.
+pub fn default_max_expansions(width: usize, height: usize) -> usize {
+    width
+        .saturating_mul(height)
+        .saturating_mul(4)
+        .clamp(1_000, 400_000)
+}
+
+/// Inputs every planner answers the same way without searching: an empty
+/// grid, an endpoint that is out of bounds or blocked, or `start == goal`.
+/// Returns `Some(answer)` when the search can be skipped.
+pub fn trivial_path(grid: &Grid, start: Coord, goal: Coord) -> Option<Option<Vec<Coord>>> {
+    if grid.is_blocked(start) || grid.is_blocked(goal) {
+        return Some(None);
+    }
+    if start == goal {
+        return Some(Some(vec![start]));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pathfinding::{build_planner, Connectivity, PlannerOptions, ALGORITHMS};
+
+    #[test]
+    fn degenerate_inputs_are_answered_without_a_search() {
+        let options = PlannerOptions::new(Connectivity::Orthogonal);
+        for algorithm in ALGORITHMS {
+            let plan = |grid: Vec<Vec<bool>>, start, goal| {
+                build_planner(algorithm, grid, start, goal, &options).compute_path(start, goal)
+            };
+            let open = vec![vec![false; 5]; 5];
+            assert_eq!(
+                plan(open.clone(), (2, 2), (2, 2)),
+                Some(vec![(2, 2)]),
+                "{}",
+                algorithm
+            );
+            assert_eq!(
+                plan(vec![vec![false]], (0, 0), (0, 0)),
+                Some(vec![(0, 0)]),
+                "{}",
+                algorithm
+            );
+            assert_eq!(
+                plan(vec![vec![true; 5]; 5], (0, 0), (4, 4)),
+                None,
+                "{}",
+                algorithm
+            );
+            assert_eq!(plan(Vec::new(), (0, 0), (0, 0)), None, "{}", algorithm);
+            assert_eq!(plan(open, (0, 0), (9, 9)), None, "{}", algorithm);
+        }
+    }
 }
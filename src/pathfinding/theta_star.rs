@@ -0,0 +1,285 @@
+// src/pathfinding/theta_star.rs
+// -----------------------------
+//
+// Theta*: A* on an 8-connected grid where a node may take its grandparent as
+// parent whenever the two can see each other, giving any-angle paths.
+// Constructor: `ThetaStar::new(grid)`; start and goal come with each
+// `compute_path` call.
+//
+// `compute_path` returns the sparse waypoints; use `movement::densify` to
+// turn them back into single-cell steps for the rover. Thin walls break sight
+// wherever a step of that dense line would cross one.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::pathfinding::grid::Grid;
+use crate::pathfinding::los::{bresenham_cells, line_of_sight};
+use crate::pathfinding::movement::ThinWalls;
+use crate::pathfinding::pathfinder_trait::{
+    default_max_expansions, trivial_path, CancelFlag, Pathfinder,
+};
+use crate::pathfinding::Coord;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct ThetaState {
+    coord: Coord,
+    f: i64, // Use i64 to avoid floating point comparison issues
+}
+
+impl Ord for ThetaState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // invert because BinaryHeap is max-heap
+        other
+            .f
+            .cmp(&self.f)
+            .then_with(|| self.coord.cmp(&other.coord))
+    }
+}
+
+impl PartialOrd for ThetaState {
+    fn partial_cmp(&self, other: &ThetaState) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+pub struct ThetaStar {
+    grid: Grid,
+    nodes_expanded: usize,
+    trace: Vec<Coord>, // Cells in the order the last search expanded them
+    max_expansions: usize,
+    budget_exceeded: bool,
+    cancel: CancelFlag, // Polled during the search; shared with whoever may cancel it
+    cancelled: bool,
+}
+
+impl ThetaStar {
+    pub fn new(grid: Vec<Vec<bool>>) -> Self {
+        let grid = Grid::from_columns(&grid);
+        let max_expansions = default_max_expansions(grid.width(), grid.height());
+        ThetaStar {
+            grid,
+            nodes_expanded: 0,
+            trace: Vec::new(),
+            max_expansions,
+            budget_exceeded: false,
+            cancel: CancelFlag::new(),
+            cancelled: false,
+        }
+    }
+
+    /// Block the listed cell borders (see `movement::ThinWalls`).
+    pub fn with_thin_walls(mut self, thin_walls: ThinWalls) -> Self {
+        self.grid.set_thin_walls(thin_walls);
+        self
+    }
+
+    /// Line of sight that also stops at thin walls.
+    fn can_see(&self, a: Coord, b: Coord) -> bool {
+        line_of_sight(a, b, &|c| self.grid.is_blocked(c))
+            && (self.grid.thin_walls().is_empty()
+                || bresenham_cells(a, b)
+                    .windows(2)
+                    .all(|w| !self.grid.crosses_thin_wall(w[0], w[1])))
+    }
+
+    /// Euclidean distance; used both as heuristic and as straight-line cost.
+    fn distance(a: Coord, b: Coord) -> f64 {
+        let dx = a.0.abs_diff(b.0) as f64;
+        let dy = a.1.abs_diff(b.1) as f64;
+        (dx * dx + dy * dy).sqrt()
+    }
+}
+
+impl Pathfinder for ThetaStar {
+    type Coord = Coord;
+
+    fn compute_path(&mut self, start: Coord, goal: Coord) -> Option<Vec<Coord>> {
+        self.nodes_expanded = 0;
+        self.trace.clear();
+        self.budget_exceeded = false;
+        self.cancelled = false;
+        if let Some(answer) = trivial_path(&self.grid, start, goal) {
+            return answer;
+        }
+        let mut open_list = BinaryHeap::new();
+        let mut closed = HashSet::new();
+        let mut g: HashMap<Coord, f64> = HashMap::new();
+        let mut parent: HashMap<Coord, Coord> = HashMap::new();
+
+        g.insert(start, 0.0);
+        parent.insert(start, start);
+        open_list.push(ThetaState {
+            coord: start,
+            f: (Self::distance(start, goal) * 1000.0) as i64,
+        });
+
+        while let Some(ThetaState { coord: u, .. }) = open_list.pop() {
+            if u == goal {
+                let mut path = vec![goal];
+                let mut current = goal;
+                while current != start {
+                    current = parent[&current];
+                    path.push(current);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if !closed.insert(u) {
+                continue;
+            }
+            self.nodes_expanded += 1;
+            self.trace.push(u);
+            if self.nodes_expanded > self.max_expansions {
+                self.budget_exceeded = true;
+                return None;
+            }
+            if self.cancel.should_stop(self.nodes_expanded) {
+                self.cancelled = true;
+                return None;
+            }
+
+            let u_parent = parent[&u];
+            for nbr in self.grid.neighbors8(u) {
+                if closed.contains(&nbr) {
+                    continue;
+                }
+
+                // Path 2: skip `u` entirely when its parent can see the neighbor
+                let (via, tentative) = if self.can_see(u_parent, nbr) {
+                    (u_parent, g[&u_parent] + Self::distance(u_parent, nbr))
+                } else {
+                    (u, g[&u] + Self::distance(u, nbr))
+                };
+
+                if tentative < *g.get(&nbr).unwrap_or(&f64::INFINITY) {
+                    g.insert(nbr, tentative);
+                    parent.insert(nbr, via);
+                    let f = (tentative + Self::distance(nbr, goal)) * 1000.0;
+                    open_list.push(ThetaState {
+                        coord: nbr,
+                        f: f as i64,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn update_obstacle(&mut self, coord: Coord, is_blocked: bool) {
+        self.grid.set(coord, is_blocked);
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+
+    fn search_trace(&self) -> Vec<Coord> {
+        self.trace.clone()
+    }
+
+    fn set_max_expansions(&mut self, max_expansions: usize) {
+        self.max_expansions = max_expansions;
+    }
+
+    fn budget_exceeded(&self) -> bool {
+        self.budget_exceeded
+    }
+
+    fn set_cancel_flag(&mut self, flag: CancelFlag) {
+        self.cancel = flag;
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::movement::{densify, first_invalid_step, thin_wall, Connectivity};
+
+    /// Blocked lookup over a column-major grid, with outside cells blocked.
+    fn blocked_in(grid: &[Vec<bool>]) -> impl Fn(Coord) -> bool + '_ {
+        |(x, y)| {
+            grid.get(x)
+                .and_then(|col| col.get(y))
+                .copied()
+                .unwrap_or(true)
+        }
+    }
+
+    #[test]
+    fn waypoints_see_each_other_and_cut_across_open_ground() {
+        let mut grid = vec![vec![false; 12]; 12];
+        // A wall down x = 5, open only at the bottom
+        grid[5][..10].fill(true);
+        let mut planner = ThetaStar::new(grid.clone());
+        let waypoints = planner.compute_path((0, 0), (11, 0)).unwrap();
+
+        assert_eq!(waypoints.first(), Some(&(0, 0)));
+        assert_eq!(waypoints.last(), Some(&(11, 0)));
+        assert!(waypoints
+            .windows(2)
+            .all(|w| line_of_sight(w[0], w[1], &blocked_in(&grid))));
+        // Any-angle: a few turning points, far fewer than the cells walked
+        let dense = densify(&waypoints);
+        assert!(waypoints.len() <= 4, "{:?}", waypoints);
+        assert!(dense.len() > 2 * waypoints.len(), "{:?}", dense);
+    }
+
+    #[test]
+    fn densified_paths_never_cut_corners() {
+        let mut grid = vec![vec![false; 8]; 8];
+        // Two blocks touching only at a corner, and a wall end to round
+        grid[3][3] = true;
+        grid[4][4] = true;
+        grid[2][5..].fill(true);
+        let mut planner = ThetaStar::new(grid.clone());
+
+        for (start, goal) in [((0, 7), (7, 0)), ((0, 0), (7, 7)), ((1, 7), (3, 7))] {
+            let path = densify(&planner.compute_path(start, goal).unwrap());
+            assert_eq!(
+                first_invalid_step(
+                    &path,
+                    Connectivity::Diagonal,
+                    &ThinWalls::new(),
+                    blocked_in(&grid)
+                ),
+                None,
+                "{:?} -> {:?}: {:?}",
+                start,
+                goal,
+                path
+            );
+        }
+        // Squeezing between the diagonal blocks is the only way in, so no path
+        let mut boxed = vec![vec![false; 3]; 3];
+        boxed[1][0] = true;
+        boxed[0][1] = true;
+        assert_eq!(ThetaStar::new(boxed).compute_path((0, 0), (2, 2)), None);
+    }
+
+    #[test]
+    fn densified_paths_respect_thin_walls() {
+        let grid = vec![vec![false; 8]; 8];
+        // A fence between x = 3 and x = 4, open only at the bottom row
+        let walls: ThinWalls = (0..7).map(|y| thin_wall((3, y), (4, y))).collect();
+        let mut planner = ThetaStar::new(grid.clone()).with_thin_walls(walls.clone());
+
+        let path = densify(&planner.compute_path((0, 0), (7, 0)).unwrap());
+        assert_eq!(
+            first_invalid_step(&path, Connectivity::Diagonal, &walls, blocked_in(&grid)),
+            None,
+            "{:?}",
+            path
+        );
+        assert!(
+            path.contains(&(3, 7)) && path.contains(&(4, 7)),
+            "{:?}",
+            path
+        );
+    }
+}
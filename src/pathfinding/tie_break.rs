@@ -0,0 +1,66 @@
+// src/pathfinding/tie_break.rs
+// ----------------------------
+//
+// Secondary ordering for open-list entries whose primary keys are equal.
+
+use crate::pathfinding::Coord;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TieBreak {
+    /// Fall through to coordinate order (historic behavior; paths hug one edge).
+    #[default]
+    Coordinate,
+    /// Prefer cells closest to the straight start→goal line (cross-product heuristic).
+    CrossProduct,
+}
+
+impl TieBreak {
+    /// Smaller is preferred. Always 0 for `Coordinate`.
+    pub fn key(self, cell: Coord, start: Coord, goal: Coord) -> u64 {
+        match self {
+            TieBreak::Coordinate => 0,
+            TieBreak::CrossProduct => {
+                let dx1 = cell.0 as i64 - goal.0 as i64;
+                let dy1 = cell.1 as i64 - goal.1 as i64;
+                let dx2 = start.0 as i64 - goal.0 as i64;
+                let dy2 = start.1 as i64 - goal.1 as i64;
+                (dx1 * dy2 - dx2 * dy1).unsigned_abs()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pathfinding::{AStar, DStarLite, Pathfinder};
+
+    #[test]
+    fn cross_product_keeps_the_path_near_the_diagonal() {
+        let grid = vec![vec![false; 20]; 20];
+        let (start, goal) = ((0, 0), (19, 19));
+        let planners: [Box<dyn Pathfinder<Coord = Coord>>; 2] = [
+            Box::new(AStar::with_tie_break(grid.clone(), TieBreak::CrossProduct)),
+            Box::new(DStarLite::with_tie_break(
+                grid,
+                start,
+                goal,
+                TieBreak::CrossProduct,
+            )),
+        ];
+        for mut planner in planners {
+            let path = planner.compute_path(start, goal).unwrap();
+            assert_eq!(path.len(), 39);
+            // A staircase, not an L along two edges
+            assert!(path.iter().all(|&(x, y)| x.abs_diff(y) <= 1), "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn the_key_is_the_distance_from_the_start_goal_line() {
+        let tie = TieBreak::CrossProduct;
+        assert_eq!(tie.key((5, 5), (0, 0), (10, 10)), 0);
+        assert!(tie.key((5, 4), (0, 0), (10, 10)) < tie.key((5, 0), (0, 0), (10, 10)));
+        assert_eq!(TieBreak::Coordinate.key((5, 0), (0, 0), (10, 10)), 0);
+    }
+}
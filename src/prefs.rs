@@ -0,0 +1,32 @@
+// src/prefs.rs
+//
+// UI preferences kept in `window.localStorage`. Every call quietly does
+// nothing when storage is unavailable (private mode, blocked cookies), so
+// callers just fall back to their defaults.
+
+use std::str::FromStr;
+
+use web_sys::Storage;
+
+pub const DARK_MODE: &str = "scout.dark_mode";
+pub const SPEED: &str = "scout.speed";
+pub const ALGORITHM: &str = "scout.algorithm";
+pub const ALLOW_DIAGONALS: &str = "scout.allow_diagonals";
+pub const FRAME_RATE: &str = "scout.frame_rate";
+pub const WHAT_IF: &str = "scout.what_if";
+pub const PALETTE: &str = "scout.palette";
+
+fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Saved value for `key`, if present and parseable.
+pub fn load<T: FromStr>(key: &str) -> Option<T> {
+    storage()?.get_item(key).ok()??.parse().ok()
+}
+
+pub fn save(key: &str, value: impl ToString) {
+    if let Some(storage) = storage() {
+        let _ = storage.set_item(key, &value.to_string());
+    }
+}
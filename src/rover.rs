@@ -1,20 +1,122 @@
 // src/rover.rs
 
-use crate::pathfinding::{AStar, Coord, DStarLite, FieldDStar, Pathfinder};
-use std::collections::HashSet;
+use crate::pathfinding::movement::{densify, thin_wall, ThinWalls, DEFAULT_DIAGONAL_COST};
+use crate::pathfinding::pathfinder_trait::CancelFlag;
+use crate::pathfinding::{
+    build_planner, parse_algorithm, uses_terrain, Connectivity, Coord, DStarLite, Pathfinder,
+    PlannerOptions, TieBreak,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Slowest cadence (speed 1).
+pub const MAX_STEP_DELAY_MS: u32 = 1000;
+/// Fastest cadence; anything quicker outruns the 50ms render loop.
+pub const MIN_STEP_DELAY_MS: u32 = 40;
+
+/// Milliseconds from `performance.now()`, falling back to the wall clock
+/// where the page has no Performance API.
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or_else(js_sys::Date::now, |performance| performance.now())
+}
+
+/// Native builds (tests, the bins) have no browser clock; the system clock
+/// serves for measuring durations.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}
+
+/// Time between movement cycles. Each speed notch divides the delay by a
+/// constant factor: speed 1 = 1000ms, 5 ≈ 264ms, 10 = 50ms. Speeds past 10
+/// keep the curve going until they hit `MIN_STEP_DELAY_MS`; speed 0 acts as 1.
+pub fn step_delay_ms(speed: u32) -> u32 {
+    let notches = speed.saturating_sub(1) as f64;
+    let delay = MAX_STEP_DELAY_MS as f64 * 0.05f64.powf(notches / 9.0);
+    (delay.round() as u32).max(MIN_STEP_DELAY_MS)
+}
+
+/// Moves per second at `speed`. Only the step delay sets this; the canvas
+/// frame rate changes how smoothly a step is drawn, not how often one happens.
+pub fn cells_per_second(speed: u32) -> f64 {
+    1000.0 / step_delay_ms(speed) as f64
+}
+
+/// Cheapest a terrain cell may be. Plain ground costs 1.0, so anything lower
+/// would let a step cost less than the heuristic assumes.
+pub const MIN_TERRAIN_COST: f64 = 1.0;
+
+/// Reject terrain costs that are below `MIN_TERRAIN_COST` or not finite.
+pub fn validate_terrain_costs(costs: &[(Coord, f64)]) -> Result<(), String> {
+    match costs
+        .iter()
+        .find(|(_, cost)| !cost.is_finite() || *cost < MIN_TERRAIN_COST)
+    {
+        Some(((x, y), cost)) => Err(format!(
+            "terrain cost {} at ({}, {}) must be a finite number >= {}",
+            cost, x, y, MIN_TERRAIN_COST
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Outcome of one solver run, for comparing algorithms on the same map.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PathResult {
+    pub path: Vec<Coord>,
+    /// Sum of step lengths (diagonal steps count the rover's diagonal cost),
+    /// each scaled by the terrain cost of the cell it enters when the planner
+    /// uses terrain; 0.0 when no path.
+    pub cost: f64,
+    pub nodes_expanded: usize,
+    pub elapsed_ms: f64,
+    /// The solver gave up at its expansion cap; an empty path doesn't mean "no route".
+    pub budget_exceeded: bool,
+    /// The search was stopped through the cancel flag; the empty path says nothing about the map.
+    pub cancelled: bool,
+}
+
+impl PathResult {
+    fn path_cost(path: &[Coord], terrain_costs: &HashMap<Coord, f64>, diagonal_cost: f64) -> f64 {
+        path.windows(2)
+            .map(|w| {
+                let length = if w[0].0 != w[1].0 && w[0].1 != w[1].1 {
+                    diagonal_cost
+                } else {
+                    1.0
+                };
+                length * terrain_costs.get(&w[1]).unwrap_or(&1.0)
+            })
+            .sum()
+    }
+}
 
 #[derive(Clone, PartialEq)]
 pub struct RoverState {
     pub pos: Coord,
     pub goal: Coord,
     pub path: Vec<Coord>,
-    pub obstacles: HashSet<Coord>, 
-    pub dynamic_obstacles: Vec<Coord>, 
-    pub converted_obstacles: HashSet<Coord>, 
+    pub obstacles: HashSet<Coord>,
+    pub dynamic_obstacles: Vec<Coord>,
+    pub converted_obstacles: HashSet<Coord>,
     pub algorithm: String,
+    pub tie_break: TieBreak,
+    pub allow_diagonals: bool,
+    /// Cost of one diagonal step (every 8-connected planner but Theta*), in cells.
+    pub diagonal_cost: f64,
+    /// Extra cost per change of direction (A* and D*-Lite only), in cells.
+    pub turn_penalty: f64,
     pub speed: u32,
     pub width: usize,
     pub height: usize,
+    /// Cost multiplier for entering a cell; missing cells cost 1.0 (see `uses_terrain`).
+    pub terrain_costs: HashMap<Coord, f64>,
+    /// Blocked borders between neighboring cells, keyed by `movement::thin_wall`.
+    pub thin_walls: ThinWalls,
 }
 
 pub struct Rover {
@@ -22,12 +124,22 @@ pub struct Rover {
     pathfinder: Box<dyn Pathfinder<Coord = Coord>>,
     pub width: usize,
     pub height: usize,
+    // What the persistent pathfinder was built for; a mismatch forces a rebuild
+    known_grid: Vec<Vec<bool>>,
+    planned_goal: Coord,
+    planned_algorithm: String,
+    planned_connectivity: Connectivity,
+    planned_diagonal_cost: f64,
+    planned_turn_penalty: f64,
+    planned_terrain_costs: HashMap<Coord, f64>,
+    planned_thin_walls: ThinWalls,
+    cancel: CancelFlag, // Handed to every pathfinder this rover builds
 }
 
 impl Rover {
     pub fn new(width: usize, height: usize) -> Self {
         let start = (5, 5);
-        let goal = (width - 5, height - 5);
+        let goal = (width.saturating_sub(5), height.saturating_sub(5));
         let rover_state = RoverState {
             pos: start,
             goal,
@@ -36,71 +148,249 @@ impl Rover {
             dynamic_obstacles: Vec::new(),
             converted_obstacles: HashSet::new(),
             algorithm: "D*-Lite".into(),
+            tie_break: TieBreak::CrossProduct,
+            allow_diagonals: false,
+            diagonal_cost: DEFAULT_DIAGONAL_COST,
+            turn_penalty: 0.0,
             speed: 5,
             width,
             height,
+            terrain_costs: HashMap::new(),
+            thin_walls: ThinWalls::new(),
         };
 
         let grid = vec![vec![false; height]; width];
-        let pf: Box<dyn Pathfinder<Coord = Coord>> = Box::new(DStarLite::new(grid, start, goal));
+        let cancel = CancelFlag::new();
+        let mut pf: Box<dyn Pathfinder<Coord = Coord>> = Box::new(DStarLite::with_tie_break(
+            grid.clone(),
+            start,
+            goal,
+            rover_state.tie_break,
+        ));
+        pf.set_cancel_flag(cancel.clone());
 
         Rover {
+            planned_algorithm: rover_state.algorithm.clone(),
             state: rover_state,
             pathfinder: pf,
             width,
             height,
+            known_grid: grid,
+            planned_goal: goal,
+            planned_connectivity: Connectivity::Orthogonal,
+            planned_diagonal_cost: DEFAULT_DIAGONAL_COST,
+            planned_turn_penalty: 0.0,
+            planned_terrain_costs: HashMap::new(),
+            planned_thin_walls: ThinWalls::new(),
+            cancel,
         }
     }
 
+    #[allow(dead_code)]
     pub fn clone(&self) -> Self {
+        self.clone_for_goal(self.state.goal)
+    }
+
+    /// Like `clone`, but headed for `goal` with a pathfinder built for it.
+    /// A one-off search on the copy leaves this rover's pathfinder and its
+    /// reusable search alone; the two share the cancel flag.
+    pub fn clone_for_goal(&self, goal: Coord) -> Self {
         let grid = self.build_grid();
-        let pf: Box<dyn Pathfinder<Coord = Coord>> = match self.state.algorithm.as_str() {
-            "A*" => Box::new(AStar::new(grid, self.state.pos, self.state.goal)),
-            "D*-Lite" => Box::new(DStarLite::new(grid, self.state.pos, self.state.goal)),
-            "Field D*" => Box::new(FieldDStar::new(grid, self.state.pos, self.state.goal)),
-            _ => Box::new(DStarLite::new(grid, self.state.pos, self.state.goal)),
-        };
+        let pf = self.build_pathfinder(grid.clone(), goal);
 
         Rover {
-            state: self.state.clone(),
+            state: RoverState {
+                goal,
+                ..self.state.clone()
+            },
             pathfinder: pf,
             width: self.width,
             height: self.height,
+            known_grid: grid,
+            planned_goal: goal,
+            planned_algorithm: self.state.algorithm.clone(),
+            planned_connectivity: self.connectivity(),
+            planned_diagonal_cost: self.state.diagonal_cost,
+            planned_turn_penalty: self.state.turn_penalty,
+            planned_terrain_costs: self.state.terrain_costs.clone(),
+            planned_thin_walls: self.state.thin_walls.clone(),
+            cancel: self.cancel.clone(),
         }
     }
 
+    #[allow(dead_code)]
     pub fn clone_state(&self) -> RoverState {
         self.state.clone()
     }
 
+    /// Takes a bare name from `ALGORITHMS` or a preset such as "A* (8-dir)",
+    /// which also sets `allow_diagonals`. Unknown names fall back to
+    /// 4-connected D*-Lite.
     pub fn set_algorithm(&mut self, algo: &str) {
-        let grid = self.build_grid();
-        self.state.algorithm = algo.to_string();
+        let (algorithm, connectivity) =
+            parse_algorithm(algo).unwrap_or(("D*-Lite", Some(Connectivity::Orthogonal)));
+        self.state.algorithm = algorithm.to_string();
+        if let Some(connectivity) = connectivity {
+            self.state.allow_diagonals = connectivity.allows_diagonals();
+        }
+    }
+
+    /// Shared "Allow diagonals" setting, applied to whichever algorithm plans next.
+    pub fn set_allow_diagonals(&mut self, allow: bool) {
+        self.state.allow_diagonals = allow;
+    }
+
+    /// Cost of one diagonal step; values below 1.0 (or NaN) are raised to 1.0.
+    pub fn set_diagonal_cost(&mut self, cost: f64) {
+        self.state.diagonal_cost = cost.max(1.0);
+    }
+
+    pub fn set_turn_penalty(&mut self, penalty: f64) {
+        self.state.turn_penalty = penalty;
+    }
 
-        self.pathfinder = match algo {
-            "A*" => Box::new(AStar::new(grid, self.state.pos, self.state.goal)),
-            "D*-Lite" => Box::new(DStarLite::new(grid, self.state.pos, self.state.goal)),
-            "Field D*" => Box::new(FieldDStar::new(grid, self.state.pos, self.state.goal)),
-            _ => Box::new(DStarLite::new(grid, self.state.pos, self.state.goal)),
+    /// Replace the weighted terrain: entering `coord` costs `cost` times a
+    /// normal step. Costs below `MIN_TERRAIN_COST`, or any weights at all
+    /// while the algorithm ignores terrain (see `uses_terrain`), are rejected
+    /// and leave the current terrain untouched, so set the algorithm first.
+    pub fn set_terrain_costs(&mut self, costs: Vec<(Coord, f64)>) -> Result<(), String> {
+        validate_terrain_costs(&costs)?;
+        if !costs.is_empty() && !uses_terrain(&self.state.algorithm) {
+            return Err(format!(
+                "{} ignores terrain costs; use A*, Dijkstra or Greedy Best-First",
+                self.state.algorithm
+            ));
+        }
+        self.state.terrain_costs = costs
+            .into_iter()
+            .filter(|&((x, y), cost)| x < self.width && y < self.height && cost > MIN_TERRAIN_COST)
+            .collect();
+        Ok(())
+    }
+
+    /// Replace the thin walls. Each pair must be orthogonal neighbors inside
+    /// the grid; anything else is dropped. Every square planner honors them.
+    pub fn set_thin_walls(&mut self, thin_walls: impl IntoIterator<Item = (Coord, Coord)>) {
+        let (width, height) = (self.width, self.height);
+        self.state.thin_walls = thin_walls
+            .into_iter()
+            .filter(|&(a, b)| {
+                a.0.abs_diff(b.0) + a.1.abs_diff(b.1) == 1
+                    && [a, b].iter().all(|&(x, y)| x < width && y < height)
+            })
+            .map(|(a, b)| thin_wall(a, b))
+            .collect();
+    }
+
+    /// Connectivity the active algorithm plans (and the rover moves) with.
+    pub fn connectivity(&self) -> Connectivity {
+        Connectivity::for_algorithm(&self.state.algorithm, self.state.allow_diagonals)
+    }
+
+    fn rebuild_pathfinder(&mut self, grid: Vec<Vec<bool>>) {
+        self.pathfinder = self.build_pathfinder(grid.clone(), self.state.goal);
+        self.known_grid = grid;
+        self.planned_goal = self.state.goal;
+        self.planned_algorithm = self.state.algorithm.clone();
+        self.planned_connectivity = self.connectivity();
+        self.planned_diagonal_cost = self.state.diagonal_cost;
+        self.planned_turn_penalty = self.state.turn_penalty;
+        self.planned_terrain_costs = self.state.terrain_costs.clone();
+        self.planned_thin_walls = self.state.thin_walls.clone();
+    }
+
+    fn build_pathfinder(
+        &self,
+        grid: Vec<Vec<bool>>,
+        goal: Coord,
+    ) -> Box<dyn Pathfinder<Coord = Coord>> {
+        let options = PlannerOptions {
+            connectivity: self.connectivity(),
+            tie_break: self.state.tie_break,
+            diagonal_cost: self.state.diagonal_cost,
+            turn_penalty: self.state.turn_penalty,
+            terrain_costs: self.state.terrain_costs.clone(),
+            thin_walls: self.state.thin_walls.clone(),
         };
+        let mut pathfinder =
+            build_planner(&self.state.algorithm, grid, self.state.pos, goal, &options);
+        pathfinder.set_cancel_flag(self.cancel.clone());
+        pathfinder
+    }
+
+    /// Flag that stops this rover's searches. Clones share it, so keep one and
+    /// call `cancel()` to abort; `reset()` it before planning again.
+    pub fn cancel_flag(&self) -> CancelFlag {
+        self.cancel.clone()
     }
 
-    pub fn compute_path_now(&mut self) -> Vec<Coord> {
+    /// Plan from the current position. The pathfinder is kept between calls:
+    /// changed cells are fed through `update_obstacles` as one batch so
+    /// incremental planners (D*-Lite) can reuse their search, and it is only
+    /// rebuilt when the goal, algorithm, connectivity, diagonal cost, turn
+    /// penalty, terrain or thin walls change (D*-Lite caches its neighbors and searches over
+    /// headings).
+    /// The result also reports cost, work and solver time.
+    pub fn compute_path_detailed(&mut self) -> PathResult {
         let grid = self.build_grid();
-        self.pathfinder = match self.state.algorithm.as_str() {
-            "A*" => Box::new(AStar::new(grid, self.state.pos, self.state.goal)),
-            "D*-Lite" => Box::new(DStarLite::new(grid, self.state.pos, self.state.goal)),
-            "Field D*" => Box::new(FieldDStar::new(grid, self.state.pos, self.state.goal)),
-            _ => Box::new(DStarLite::new(grid, self.state.pos, self.state.goal)),
-        };
+        if self.planned_goal != self.state.goal
+            || self.planned_algorithm != self.state.algorithm
+            || self.planned_connectivity != self.connectivity()
+            || self.planned_diagonal_cost != self.state.diagonal_cost
+            || self.planned_turn_penalty != self.state.turn_penalty
+            || self.planned_terrain_costs != self.state.terrain_costs
+            || self.planned_thin_walls != self.state.thin_walls
+        {
+            self.rebuild_pathfinder(grid);
+        } else {
+            let mut changes = Vec::new();
+            for (x, (column, known)) in grid.iter().zip(&self.known_grid).enumerate() {
+                for (y, (&blocked, &was_blocked)) in column.iter().zip(known).enumerate() {
+                    if blocked != was_blocked {
+                        changes.push(((x, y), blocked));
+                    }
+                }
+            }
+            self.pathfinder.update_obstacles(&changes);
+            self.known_grid = grid;
+        }
 
+        let started = now_ms();
+        // Any-angle planners return sparse waypoints; the rover needs unit steps
         let path = self
             .pathfinder
             .compute_path(self.state.pos, self.state.goal)
+            .map(|waypoints| densify(&waypoints))
             .unwrap_or_default();
+        let elapsed_ms = now_ms() - started;
 
         self.state.path = path.clone();
-        path
+        // Weights left from an earlier algorithm don't price this one's route
+        let flat = HashMap::new();
+        let terrain_costs = if uses_terrain(&self.state.algorithm) {
+            &self.state.terrain_costs
+        } else {
+            &flat
+        };
+        PathResult {
+            cost: PathResult::path_cost(&path, terrain_costs, self.state.diagonal_cost),
+            nodes_expanded: self.pathfinder.nodes_expanded(),
+            elapsed_ms,
+            budget_exceeded: self.pathfinder.budget_exceeded(),
+            cancelled: self.pathfinder.cancelled(),
+            path,
+        }
+    }
+
+    /// Cells the last plan expanded, in order, e.g. to animate the search or
+    /// check that Dijkstra expands in order of distance.
+    pub fn last_search_trace(&self) -> Vec<Coord> {
+        self.pathfinder.search_trace()
+    }
+
+    /// Per-cell costs from the last plan, when the active algorithm keeps them.
+    pub fn cost_field(&self) -> Option<HashMap<Coord, f64>> {
+        self.pathfinder.cost_field()
     }
 
     pub fn build_grid(&self) -> Vec<Vec<bool>> {
@@ -133,11 +423,13 @@ impl Rover {
         self.state.goal = new_goal;
     }
 
+    #[allow(dead_code)]
     pub fn set_speed(&mut self, s: u32) {
         self.state.speed = s;
     }
 }
 
+#[allow(dead_code)]
 impl RoverState {
     pub fn grid_width(&self) -> usize {
         self.width
@@ -146,3 +438,68 @@ impl RoverState {
         self.height
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_weighted_patch_reroutes_the_path() {
+        let mut rover = Rover::new(10, 10);
+        rover.set_algorithm("A*");
+        rover.set_position((0, 5));
+        rover.set_goal((9, 5));
+        assert_eq!(rover.compute_path_detailed().cost, 9.0);
+
+        let patch: Vec<(Coord, f64)> = (3..=6)
+            .flat_map(|x| (3..=7).map(move |y| ((x, y), 10.0)))
+            .collect();
+        rover.set_terrain_costs(patch).unwrap();
+        let result = rover.compute_path_detailed();
+        assert!(result
+            .path
+            .iter()
+            .all(|c| !rover.state.terrain_costs.contains_key(c)));
+        // Around the patch: 9 across plus 3 rows out and back
+        assert_eq!(result.cost, 15.0);
+        assert_eq!(result.path.last(), Some(&(9, 5)));
+    }
+
+    #[test]
+    fn terrain_costs_below_one_are_rejected() {
+        let mut rover = Rover::new(10, 10);
+        rover.set_algorithm("A*");
+        rover.set_terrain_costs(vec![((2, 2), 3.0)]).unwrap();
+        for bad in [0.0, -1.0, 0.5, f64::NAN, f64::INFINITY] {
+            assert!(rover.set_terrain_costs(vec![((1, 1), bad)]).is_err());
+        }
+        assert_eq!(rover.state.terrain_costs, HashMap::from([((2, 2), 3.0)]));
+    }
+
+    #[test]
+    fn planners_that_ignore_terrain_refuse_it() {
+        let patch = vec![((4, 5), 10.0)];
+        for algorithm in ["D*-Lite", "Field D*", "Theta*"] {
+            let mut rover = Rover::new(10, 10);
+            rover.set_algorithm(algorithm);
+            assert!(
+                rover.set_terrain_costs(patch.clone()).is_err(),
+                "{}",
+                algorithm
+            );
+            assert!(rover.state.terrain_costs.is_empty());
+            rover.set_terrain_costs(Vec::new()).unwrap();
+        }
+
+        // Weights set for A* don't price the route of a planner that ignored them
+        let mut rover = Rover::new(10, 10);
+        rover.set_algorithm("A*");
+        rover.set_terrain_costs(patch).unwrap();
+        rover.set_algorithm("D*-Lite");
+        rover.set_position((0, 5));
+        rover.set_goal((9, 5));
+        let result = rover.compute_path_detailed();
+        assert!(result.path.contains(&(4, 5)));
+        assert_eq!(result.cost, 9.0);
+    }
+}
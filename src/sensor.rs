@@ -0,0 +1,389 @@
+// src/sensor.rs
+//
+// How far the rover "sees". The same sensor decides which amber DOBs get
+// converted and what shape the canvas draws as the detection ring. It is
+// separate from movement connectivity: a rover can sense in a circle and
+// still step on a 4-grid.
+
+use std::ops::RangeInclusive;
+
+use crate::pathfinding::Coord;
+
+/// Default sensor range: DOBs within this many cells of the rover are detected.
+pub const DETECTION_RADIUS: f64 = 2.0;
+
+/// Sensor ranges the controls offer, in cells.
+pub const SENSOR_RANGES: RangeInclusive<f64> = 1.0..=5.0;
+
+/// Half the opening of the forward cone: 60° either side of the heading.
+pub const CONE_HALF_ANGLE: f64 = std::f64::consts::FRAC_PI_3;
+
+/// Which way the rover faces, taken from its last move.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Direction {
+    North,
+    NorthEast,
+    #[default]
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// Direction of the step `from` -> `to`; `None` when they are the same cell.
+    pub fn between(from: Coord, to: Coord) -> Option<Self> {
+        use std::cmp::Ordering::*;
+        Some(match (to.0.cmp(&from.0), to.1.cmp(&from.1)) {
+            (Equal, Less) => Direction::North,
+            (Greater, Less) => Direction::NorthEast,
+            (Greater, Equal) => Direction::East,
+            (Greater, Greater) => Direction::SouthEast,
+            (Equal, Greater) => Direction::South,
+            (Less, Greater) => Direction::SouthWest,
+            (Less, Equal) => Direction::West,
+            (Less, Less) => Direction::NorthWest,
+            (Equal, Equal) => return None,
+        })
+    }
+
+    /// Direction of the last move along `trail`, if it has one.
+    pub fn of_last_step(trail: &[Coord]) -> Option<Self> {
+        match trail {
+            [.., from, to] => Self::between(*from, *to),
+            _ => None,
+        }
+    }
+
+    /// Angle in radians on screen, where y grows downward: East is 0, South π/2.
+    pub fn angle(self) -> f64 {
+        let eighth = std::f64::consts::FRAC_PI_4;
+        eighth
+            * match self {
+                Direction::East => 0.0,
+                Direction::SouthEast => 1.0,
+                Direction::South => 2.0,
+                Direction::SouthWest => 3.0,
+                Direction::West => 4.0,
+                Direction::NorthWest => -3.0,
+                Direction::North => -2.0,
+                Direction::NorthEast => -1.0,
+            }
+    }
+}
+
+/// How much of the detection area around the rover it can see.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FieldOfView {
+    /// Everything in range, all the way round.
+    #[default]
+    Full,
+    /// Only the part of the area within `CONE_HALF_ANGLE` of the heading.
+    ForwardCone,
+}
+
+impl FieldOfView {
+    pub const ALL: [FieldOfView; 2] = [FieldOfView::Full, FieldOfView::ForwardCone];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FieldOfView::Full => "Full circle",
+            FieldOfView::ForwardCone => "Forward cone",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.label() == label)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum DistanceMetric {
+    /// Straight-line distance; detection area is a circle.
+    #[default]
+    Euclidean,
+    /// max(dx, dy); detection area is a square.
+    Chebyshev,
+    /// dx + dy; detection area is a diamond.
+    Manhattan,
+}
+
+impl DistanceMetric {
+    pub const ALL: [DistanceMetric; 3] = [
+        DistanceMetric::Euclidean,
+        DistanceMetric::Chebyshev,
+        DistanceMetric::Manhattan,
+    ];
+
+    pub fn distance(self, a: Coord, b: Coord) -> f64 {
+        let dx = a.0.abs_diff(b.0) as f64;
+        let dy = a.1.abs_diff(b.1) as f64;
+        match self {
+            DistanceMetric::Euclidean => (dx * dx + dy * dy).sqrt(),
+            DistanceMetric::Chebyshev => dx.max(dy),
+            DistanceMetric::Manhattan => dx + dy,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DistanceMetric::Euclidean => "Euclidean",
+            DistanceMetric::Chebyshev => "Chebyshev",
+            DistanceMetric::Manhattan => "Manhattan",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.label() == label)
+    }
+}
+
+/// What happens to an amber DOB once the sensor detects it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ConversionPolicy {
+    /// Every detected DOB becomes a permanent static obstacle.
+    #[default]
+    PermanentOnDetect,
+    /// Only detected DOBs on or next to the planned path are blocked; the rest
+    /// stay amber until a later plan passes beside them.
+    AdjacentBlockOnly,
+}
+
+impl ConversionPolicy {
+    pub const ALL: [ConversionPolicy; 2] = [
+        ConversionPolicy::PermanentOnDetect,
+        ConversionPolicy::AdjacentBlockOnly,
+    ];
+
+    /// Whether a detected DOB at `dob` converts while the rover follows `planned_path`.
+    pub fn converts(self, dob: Coord, planned_path: &[Coord]) -> bool {
+        match self {
+            ConversionPolicy::PermanentOnDetect => true,
+            ConversionPolicy::AdjacentBlockOnly => planned_path
+                .iter()
+                .any(|&c| c.0.abs_diff(dob.0) <= 1 && c.1.abs_diff(dob.1) <= 1),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ConversionPolicy::PermanentOnDetect => "Convert on detect",
+            ConversionPolicy::AdjacentBlockOnly => "Block only near path",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.label() == label)
+    }
+}
+
+/// Shape and reach of the rover's detection area.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sensor {
+    pub metric: DistanceMetric,
+    pub range: f64, // Cells
+    pub field_of_view: FieldOfView,
+}
+
+impl Default for Sensor {
+    fn default() -> Self {
+        Self {
+            metric: DistanceMetric::default(),
+            range: DETECTION_RADIUS,
+            field_of_view: FieldOfView::default(),
+        }
+    }
+}
+
+impl Sensor {
+    /// Whether `cell` falls inside the detection area centred on `rover`.
+    pub fn detects(self, rover: Coord, cell: Coord) -> bool {
+        self.metric.distance(rover, cell) <= self.range
+    }
+
+    /// `detects`, narrowed to the forward cone when the rover facing `heading`
+    /// only looks ahead. The rover's own cell is always seen.
+    pub fn detects_facing(self, rover: Coord, heading: Direction, cell: Coord) -> bool {
+        if !self.detects(rover, cell) {
+            return false;
+        }
+        match self.field_of_view {
+            FieldOfView::Full => true,
+            FieldOfView::ForwardCone => {
+                if cell == rover {
+                    return true;
+                }
+                let dx = cell.0 as f64 - rover.0 as f64;
+                let dy = cell.1 as f64 - rover.1 as f64;
+                let offset = dy.atan2(dx) - heading.angle();
+                // Wrap to (-π, π] so a West heading sees both sides of the seam
+                let offset = offset.sin().atan2(offset.cos());
+                offset.abs() <= CONE_HALF_ANGLE + 1e-9
+            }
+        }
+    }
+}
+
+/// Occupancy threshold a fresh map starts with: a cell more likely occupied
+/// than not is planned around.
+pub const DEFAULT_OCCUPANCY_THRESHOLD: f64 = 0.5;
+
+/// Log-odds one reading adds to a cell's belief when it sees the cell
+/// occupied, or takes away when it sees it free: ln(0.7 / 0.3), so a 0.5
+/// cell goes to 0.7 or 0.3 in one look.
+pub const OCCUPANCY_LOG_ODDS_STEP: f64 = 0.847_297_860_387_204;
+
+/// Beliefs stay inside this range, so no reading is final and the next one
+/// can still move it.
+pub const OCCUPANCY_BOUNDS: RangeInclusive<f64> = 0.02..=0.98;
+
+/// Bayesian update of the occupancy belief `p` after the sensor sees the
+/// cell `occupied` or free, in log-odds form.
+pub fn update_occupancy(p: f64, occupied: bool) -> f64 {
+    let (low, high) = (*OCCUPANCY_BOUNDS.start(), *OCCUPANCY_BOUNDS.end());
+    let p = p.clamp(low, high);
+    let step = if occupied {
+        OCCUPANCY_LOG_ODDS_STEP
+    } else {
+        -OCCUPANCY_LOG_ODDS_STEP
+    };
+    let log_odds = (p / (1.0 - p)).ln() + step;
+    (1.0 / (1.0 + (-log_odds).exp())).clamp(low, high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_corner_just_outside_the_circle_is_not_detected() {
+        // (2, 2) is √8 ≈ 2.83 away: inside the square of radius 2, outside the circle
+        let euclidean = Sensor::default();
+        assert_eq!(euclidean.metric, DistanceMetric::Euclidean);
+        assert!(euclidean.detects((5, 5), (7, 5)));
+        assert!(!euclidean.detects((5, 5), (7, 7)));
+
+        let chebyshev = Sensor {
+            metric: DistanceMetric::Chebyshev,
+            ..Sensor::default()
+        };
+        assert!(chebyshev.detects((5, 5), (7, 7)));
+        assert!(!chebyshev.detects((5, 5), (8, 5)));
+    }
+
+    #[test]
+    fn manhattan_detection_is_a_diamond() {
+        let sensor = Sensor {
+            metric: DistanceMetric::Manhattan,
+            ..Sensor::default()
+        };
+        assert!(sensor.detects((5, 5), (6, 6)));
+        assert!(sensor.detects((5, 5), (5, 3)));
+        assert!(!sensor.detects((5, 5), (6, 7)));
+    }
+
+    #[test]
+    fn metric_labels_round_trip() {
+        for metric in DistanceMetric::ALL {
+            assert_eq!(DistanceMetric::from_label(metric.label()), Some(metric));
+        }
+        assert_eq!(DistanceMetric::from_label("Taxicab"), None);
+    }
+
+    #[test]
+    fn repeated_readings_move_the_belief_both_ways_within_bounds() {
+        assert!((update_occupancy(0.5, true) - 0.7).abs() < 1e-9);
+        assert!((update_occupancy(0.5, false) - 0.3).abs() < 1e-9);
+
+        let mut p = 0.5;
+        for _ in 0..20 {
+            let next = update_occupancy(p, true);
+            assert!(next >= p && OCCUPANCY_BOUNDS.contains(&next));
+            p = next;
+        }
+        assert_eq!(p, *OCCUPANCY_BOUNDS.end());
+
+        // A belief pinned at the top still drops on the next free reading
+        let next = update_occupancy(p, false);
+        assert!(next < p);
+        for _ in 0..20 {
+            p = update_occupancy(p, false);
+            assert!(OCCUPANCY_BOUNDS.contains(&p));
+        }
+        assert_eq!(p, *OCCUPANCY_BOUNDS.start());
+        assert!(OCCUPANCY_BOUNDS.contains(&update_occupancy(1.0, true)));
+        assert!(OCCUPANCY_BOUNDS.contains(&update_occupancy(0.0, false)));
+    }
+
+    #[test]
+    fn the_forward_cone_sees_ahead_and_its_flanks_but_not_behind() {
+        // Headings clockwise on screen, each with its unit step
+        let compass = [
+            (Direction::North, (0, -1)),
+            (Direction::NorthEast, (1, -1)),
+            (Direction::East, (1, 0)),
+            (Direction::SouthEast, (1, 1)),
+            (Direction::South, (0, 1)),
+            (Direction::SouthWest, (-1, 1)),
+            (Direction::West, (-1, 0)),
+            (Direction::NorthWest, (-1, -1)),
+        ];
+        let sensor = Sensor {
+            range: 4.0,
+            field_of_view: FieldOfView::ForwardCone,
+            ..Sensor::default()
+        };
+        let rover: Coord = (5, 5);
+        let at = |(dx, dy): (isize, isize), k: isize| {
+            (
+                rover.0.checked_add_signed(dx * k).unwrap(),
+                rover.1.checked_add_signed(dy * k).unwrap(),
+            )
+        };
+
+        for (i, &(heading, ahead)) in compass.iter().enumerate() {
+            let turned = |eighths: usize| compass[(i + eighths) % 8].1;
+            assert!(
+                sensor.detects_facing(rover, heading, rover),
+                "{:?}",
+                heading
+            );
+            assert!(
+                sensor.detects_facing(rover, heading, at(ahead, 2)),
+                "{:?}",
+                heading
+            );
+            assert!(
+                !sensor.detects_facing(rover, heading, at(turned(4), 2)),
+                "{:?}",
+                heading
+            );
+            // 45° either side is inside the 60° half-angle, 90° is outside. West's
+            // flanks sit either side of the ±π seam, so its offsets have to wrap.
+            for eighths in [1, 7] {
+                assert!(
+                    sensor.detects_facing(rover, heading, at(turned(eighths), 1)),
+                    "{:?}",
+                    heading
+                );
+            }
+            for eighths in [2, 6] {
+                assert!(
+                    !sensor.detects_facing(rover, heading, at(turned(eighths), 2)),
+                    "{:?}",
+                    heading
+                );
+            }
+        }
+
+        // Just either side of the cone's edge: atan(3/2) ≈ 56° is seen, atan(2) ≈ 63° is not
+        for (heading, sign) in [(Direction::East, 1), (Direction::West, -1)] {
+            for side in [-1, 1] {
+                assert!(sensor.detects_facing(rover, heading, at((2 * sign, 3 * side), 1)));
+                assert!(!sensor.detects_facing(rover, heading, at((sign, 2 * side), 1)));
+            }
+        }
+    }
+}
@@ -0,0 +1,310 @@
+// src/serialize.rs
+//
+// Compact obstacle maps: `"<width>x<height>:<bits>"`, where `<bits>` is the
+// grid packed one bit per cell (row-major, least significant bit first) and
+// written as unpadded URL-safe base64. A dense 50x30 map is 250 characters
+// instead of several kilobytes of `[x, y]` pairs.
+//
+// Also reads hand-written ASCII maps (`#` wall, `.` free, `S`/`G` endpoints),
+// the quickest way to turn a pasted bug report into a scenario.
+
+use std::fmt;
+
+use crate::pathfinding::Coord;
+use crate::url_params::Scenario;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Largest grid a string may describe; keeps a hostile header from allocating gigabytes.
+const MAX_CELLS: usize = 1 << 20;
+
+/// Encode `grid[x][y]` (true = blocked). Columns must all have the same length.
+pub fn encode_grid(grid: &[Vec<bool>]) -> String {
+    let width = grid.len();
+    let height = grid.first().map_or(0, Vec::len);
+
+    let mut bytes = vec![0u8; (width * height).div_ceil(8)];
+    for (x, column) in grid.iter().enumerate() {
+        for (y, _) in column.iter().enumerate().filter(|(_, &blocked)| blocked) {
+            let i = y * width + x;
+            bytes[i / 8] |= 1 << (i % 8);
+        }
+    }
+
+    let mut out = format!("{}x{}:", width, height);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        // 1, 2 or 3 bytes become 2, 3 or 4 characters
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+/// Decode a string from `encode_grid` back into `grid[x][y]`. Malformed
+/// headers, unknown characters and bit strings that are too short or too
+/// long for the stated size are errors, never panics.
+pub fn decode_grid(encoded: &str) -> Result<Vec<Vec<bool>>, String> {
+    let (size, bits) = encoded
+        .trim()
+        .split_once(':')
+        .ok_or("expected <width>x<height>:<bits>")?;
+    let (width, height) = size
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<usize>().ok()?, h.parse::<usize>().ok()?)))
+        .ok_or_else(|| format!("bad grid size {:?}", size))?;
+    let cells = width
+        .checked_mul(height)
+        .filter(|&cells| cells <= MAX_CELLS)
+        .ok_or_else(|| format!("grid {}x{} is too large", width, height))?;
+
+    let byte_len = cells.div_ceil(8);
+    let expected_chars = byte_len / 3 * 4 + [0, 2, 3][byte_len % 3];
+    if bits.len() != expected_chars {
+        return Err(format!(
+            "a {}x{} grid needs {} characters, got {}",
+            width,
+            height,
+            expected_chars,
+            bits.len()
+        ));
+    }
+
+    let mut bytes = Vec::with_capacity(byte_len);
+    for chunk in bits.as_bytes().chunks(4) {
+        let mut n = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .ok_or_else(|| format!("invalid character {:?}", c as char))?;
+            n |= (value as u32) << (18 - 6 * i);
+        }
+        bytes.extend((0..chunk.len() - 1).map(|i| (n >> (16 - 8 * i)) as u8));
+    }
+
+    let mut grid = vec![vec![false; height]; width];
+    for (i, cell) in (0..cells).map(|i| (i, bytes[i / 8] >> (i % 8) & 1 == 1)) {
+        grid[i % width][i / width] = cell;
+    }
+    Ok(grid)
+}
+
+/// Why an ASCII map was rejected. Lines and columns count from 1.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    Empty,
+    RaggedRow {
+        line: usize,
+        len: usize,
+        expected: usize,
+    },
+    UnknownChar {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    DuplicateMarker {
+        marker: char,
+        line: usize,
+        column: usize,
+    },
+    MissingMarker(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "the map is empty"),
+            ParseError::RaggedRow {
+                line,
+                len,
+                expected,
+            } => write!(
+                f,
+                "line {} is {} cells wide, but the first row is {}",
+                line, len, expected
+            ),
+            ParseError::UnknownChar {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "unknown character {:?} at line {}, column {} (use # . S G)",
+                found, line, column
+            ),
+            ParseError::DuplicateMarker {
+                marker,
+                line,
+                column,
+            } => write!(
+                f,
+                "second '{}' at line {}, column {}; a map has exactly one",
+                marker, line, column
+            ),
+            ParseError::MissingMarker(marker) => write!(f, "no '{}' cell in the map", marker),
+        }
+    }
+}
+
+/// Read an ASCII map, one row per line: `#` wall, `.` free, `S` start and
+/// `G` goal (exactly one each). The size comes from the text; blank lines
+/// around the map and trailing whitespace are ignored.
+pub fn parse_ascii_map(s: &str) -> Result<Scenario, ParseError> {
+    let rows: Vec<&str> = s.lines().map(str::trim_end).collect();
+    let first = rows.iter().position(|row| !row.is_empty());
+    let last = rows.iter().rposition(|row| !row.is_empty());
+    let (Some(first), Some(last)) = (first, last) else {
+        return Err(ParseError::Empty);
+    };
+
+    let width = rows[first].chars().count();
+    let height = last - first + 1;
+    let mut scenario = Scenario::default_for(width, height);
+    let (mut start, mut goal) = (None::<Coord>, None::<Coord>);
+
+    for (y, row) in rows[first..=last].iter().enumerate() {
+        let line = first + y + 1;
+        let len = row.chars().count();
+        if len != width {
+            return Err(ParseError::RaggedRow {
+                line,
+                len,
+                expected: width,
+            });
+        }
+        for (x, c) in row.chars().enumerate() {
+            let marker = match c {
+                '#' => {
+                    scenario.obstacles.push((x, y));
+                    continue;
+                }
+                '.' => continue,
+                'S' => &mut start,
+                'G' => &mut goal,
+                found => {
+                    return Err(ParseError::UnknownChar {
+                        line,
+                        column: x + 1,
+                        found,
+                    })
+                }
+            };
+            if marker.replace((x, y)).is_some() {
+                return Err(ParseError::DuplicateMarker {
+                    marker: c,
+                    line,
+                    column: x + 1,
+                });
+            }
+        }
+    }
+
+    scenario.start = start.ok_or(ParseError::MissingMarker('S'))?;
+    scenario.goal = goal.ok_or(ParseError::MissingMarker('G'))?;
+    Ok(scenario)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_grid(width: usize, height: usize) -> Vec<Vec<bool>> {
+        (0..width)
+            .map(|x| (0..height).map(|y| (x * 7 + y * 3) % 5 == 0).collect())
+            .collect()
+    }
+
+    #[test]
+    fn grids_round_trip_through_the_compact_form() {
+        // Sizes chosen so the bit count hits every base64 remainder
+        for (width, height) in [(1, 1), (3, 3), (4, 4), (5, 5), (50, 30), (17, 1)] {
+            let grid = sample_grid(width, height);
+            let encoded = encode_grid(&grid);
+            assert!(encoded.starts_with(&format!("{}x{}:", width, height)));
+            assert_eq!(decode_grid(&encoded), Ok(grid));
+        }
+    }
+
+    #[test]
+    fn oversized_grids_are_refused() {
+        assert!(decode_grid("4096x4096:").is_err());
+        assert!(decode_grid(&format!("{}x2:", usize::MAX)).is_err());
+    }
+
+    #[test]
+    fn truncated_and_padded_strings_are_refused() {
+        let encoded = encode_grid(&sample_grid(10, 10));
+        assert!(decode_grid(&encoded[..encoded.len() - 1]).is_err());
+        assert!(decode_grid(&format!("{}A", encoded)).is_err());
+        assert!(decode_grid("10x10").is_err());
+        assert!(decode_grid("10y10:AAAA").is_err());
+    }
+
+    #[test]
+    fn characters_outside_the_alphabet_are_refused() {
+        assert!(decode_grid("2x2:A=").is_err());
+        // Multi-byte characters can match the expected byte length
+        assert!(decode_grid("2x2:é").is_err());
+    }
+
+    #[test]
+    fn an_ascii_map_gives_its_size_walls_and_endpoints() {
+        let map = "\n#S..\n.##.   \n...G\n\n";
+        let scenario = parse_ascii_map(map).unwrap();
+        assert_eq!((scenario.width, scenario.height), (4, 3));
+        assert_eq!(scenario.start, (1, 0));
+        assert_eq!(scenario.goal, (3, 2));
+        assert_eq!(scenario.obstacles, vec![(0, 0), (1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn malformed_ascii_maps_say_what_is_wrong() {
+        assert_eq!(parse_ascii_map(""), Err(ParseError::Empty));
+        assert_eq!(parse_ascii_map("  \n\n"), Err(ParseError::Empty));
+        // Line numbers count the blank line above the map
+        assert_eq!(
+            parse_ascii_map("\nS..\n..\n..G"),
+            Err(ParseError::RaggedRow {
+                line: 3,
+                len: 2,
+                expected: 3
+            })
+        );
+        assert_eq!(
+            parse_ascii_map("S.x\n..G"),
+            Err(ParseError::UnknownChar {
+                line: 1,
+                column: 3,
+                found: 'x'
+            })
+        );
+        assert_eq!(
+            parse_ascii_map("S.G\n.S."),
+            Err(ParseError::DuplicateMarker {
+                marker: 'S',
+                line: 2,
+                column: 2
+            })
+        );
+        assert_eq!(
+            parse_ascii_map("S..\n..."),
+            Err(ParseError::MissingMarker('G'))
+        );
+        assert_eq!(parse_ascii_map("..G"), Err(ParseError::MissingMarker('S')));
+        assert_eq!(
+            ParseError::UnknownChar {
+                line: 1,
+                column: 3,
+                found: 'x'
+            }
+            .to_string(),
+            "unknown character 'x' at line 1, column 3 (use # . S G)"
+        );
+    }
+}
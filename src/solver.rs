@@ -0,0 +1,448 @@
+// src/solver.rs
+//
+// Self-contained solve requests, so a planner can run away from the UI
+// thread (see `worker.js` and the protocol notes in docs/ARCHITECTURE.md).
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pathfinding::hex::{HexAStar, HexCoord};
+use crate::pathfinding::movement::{DEFAULT_DIAGONAL_COST, MAX_TURN_PENALTY};
+use crate::pathfinding::{parse_algorithm, uses_terrain, Coord, Pathfinder};
+use crate::rover::{now_ms, validate_terrain_costs, Rover};
+use crate::serialize::decode_grid;
+
+/// Cell shape of a request's grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GridKind {
+    #[default]
+    Square,
+    /// Pointy-top hexes; cells are odd-r offset `[col, row]`, every step costs 1.0.
+    Hex,
+}
+
+impl GridKind {
+    /// "square" or "hex", as in the JSON protocol.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim() {
+            "square" => Some(GridKind::Square),
+            "hex" => Some(GridKind::Hex),
+            _ => None,
+        }
+    }
+}
+
+/// Everything a worker needs to plan: the map, endpoints and algorithm.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SolveRequest {
+    pub width: usize,
+    pub height: usize,
+    pub obstacles: Vec<Coord>,
+    pub start: Coord,
+    pub goal: Coord,
+    /// A name from `ALGORITHMS` or a preset such as "A* (8-dir)".
+    pub algorithm: String,
+    /// Connectivity for bare algorithm names; presets override it. Theta*
+    /// always moves diagonally.
+    #[serde(default)]
+    pub allow_diagonals: bool,
+    /// Cost of one diagonal step, at least 1.0; √2 when omitted. Theta*
+    /// ignores it.
+    #[serde(default = "default_diagonal_cost")]
+    pub diagonal_cost: f64,
+    /// Extra cost per change of direction, 0 to `MAX_TURN_PENALTY` (A*,
+    /// Dijkstra and D*-Lite only).
+    #[serde(default)]
+    pub turn_penalty: f64,
+    /// Weighted cells as `[[x, y], cost]`; costs must be >= 1.0. Only A*,
+    /// Dijkstra and Greedy Best-First take them; other algorithms reject them.
+    #[serde(default)]
+    pub terrain_costs: Vec<(Coord, f64)>,
+    /// Blocked borders between neighboring cells as `[[x, y], [x, y]]` pairs;
+    /// pairs that aren't orthogonal neighbors are ignored.
+    #[serde(default)]
+    pub thin_walls: Vec<(Coord, Coord)>,
+    /// More obstacles in the compact `serialize.rs` form, for dense maps.
+    #[serde(default)]
+    pub obstacle_mask: Option<String>,
+    /// Also return the cells the search expanded, in order (`trace`).
+    #[serde(default)]
+    pub record_trace: bool,
+    /// Square (default) or hex cells. Hex grids plan with A* or Dijkstra only
+    /// and ignore diagonals, turn penalty, terrain and thin walls.
+    #[serde(default)]
+    pub grid_kind: GridKind,
+}
+
+fn default_diagonal_cost() -> f64 {
+    DEFAULT_DIAGONAL_COST
+}
+
+/// The planned path plus the stats `PathResult` reports.
+#[derive(Clone, Debug, Serialize)]
+pub struct SolveResponse {
+    pub algorithm: String,
+    pub path: Vec<Coord>,
+    pub cost: f64,
+    pub nodes_expanded: usize,
+    pub elapsed_ms: f64,
+    pub budget_exceeded: bool,
+    /// Expansion order when `record_trace` was set; omitted otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<Coord>>,
+}
+
+impl SolveRequest {
+    /// Reject endpoints outside the grid, out-of-range costs and penalties,
+    /// and terrain costs below 1.0 or for a planner that ignores them,
+    /// before any solver sees them.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, (x, y)) in [("start", self.start), ("goal", self.goal)] {
+            if x >= self.width || y >= self.height {
+                return Err(format!(
+                    "{} ({}, {}) is outside the {}x{} grid",
+                    name, x, y, self.width, self.height
+                ));
+            }
+        }
+        self.mask_obstacles()?;
+        if self.diagonal_cost.is_nan() || self.diagonal_cost < 1.0 {
+            return Err(format!(
+                "diagonal_cost {} must be a number >= 1.0",
+                self.diagonal_cost
+            ));
+        }
+        if !(0.0..=MAX_TURN_PENALTY).contains(&self.turn_penalty) {
+            return Err(format!(
+                "turn_penalty {} must be a number from 0 to {}",
+                self.turn_penalty, MAX_TURN_PENALTY
+            ));
+        }
+        if self.grid_kind == GridKind::Hex
+            && !matches!(
+                parse_algorithm(&self.algorithm),
+                Some(("A*" | "Dijkstra", _))
+            )
+        {
+            return Err(format!(
+                "hex grids support A* and Dijkstra, not {:?}",
+                self.algorithm
+            ));
+        }
+        if self.grid_kind == GridKind::Square
+            && !self.terrain_costs.is_empty()
+            && !parse_algorithm(&self.algorithm).is_some_and(|(a, _)| uses_terrain(a))
+        {
+            return Err(format!(
+                "{} ignores terrain costs; use A*, Dijkstra or Greedy Best-First",
+                self.algorithm
+            ));
+        }
+        validate_terrain_costs(&self.terrain_costs)
+    }
+
+    /// Cells set in `obstacle_mask`; its size has to match the request's.
+    fn mask_obstacles(&self) -> Result<Vec<Coord>, String> {
+        let Some(mask) = &self.obstacle_mask else {
+            return Ok(Vec::new());
+        };
+        let grid = decode_grid(mask).map_err(|e| format!("obstacle_mask: {}", e))?;
+        let height = grid.first().map_or(0, Vec::len);
+        if (grid.len(), height) != (self.width, self.height) {
+            return Err(format!(
+                "obstacle_mask is {}x{}, the request is {}x{}",
+                grid.len(),
+                height,
+                self.width,
+                self.height
+            ));
+        }
+        Ok((0..self.width)
+            .flat_map(|x| (0..self.height).map(move |y| (x, y)))
+            .filter(|&(x, y)| grid[x][y])
+            .collect())
+    }
+}
+
+/// Plan with the same `Rover` plumbing the interactive app uses.
+pub fn solve(request: &SolveRequest) -> SolveResponse {
+    if request.grid_kind == GridKind::Hex {
+        return solve_hex(request);
+    }
+    let mut rover = Rover::new(request.width, request.height);
+    let mut obstacles = request.obstacles.clone();
+    // A bad mask adds nothing; `validate` reports it first
+    obstacles.extend(request.mask_obstacles().unwrap_or_default());
+    rover.set_obstacles(obstacles);
+    rover.set_position(request.start);
+    rover.set_goal(request.goal);
+    // A preset's connectivity wins over the flag, so set the flag first
+    rover.set_allow_diagonals(request.allow_diagonals);
+    rover.set_algorithm(&request.algorithm);
+    rover.set_diagonal_cost(request.diagonal_cost);
+    rover.set_turn_penalty(request.turn_penalty);
+    // Rejected costs leave the terrain flat; `validate` reports them first
+    let _ = rover.set_terrain_costs(request.terrain_costs.clone());
+    rover.set_thin_walls(request.thin_walls.iter().copied());
+    let result = rover.compute_path_detailed();
+    let trace = request.record_trace.then(|| rover.last_search_trace());
+
+    SolveResponse {
+        algorithm: request.algorithm.clone(),
+        path: result.path,
+        cost: result.cost,
+        nodes_expanded: result.nodes_expanded,
+        elapsed_ms: result.elapsed_ms,
+        budget_exceeded: result.budget_exceeded,
+        trace,
+    }
+}
+
+/// Hex grids skip `Rover` (its planners are square-only) and run `HexAStar`
+/// directly, converting to and from offset cells at the edges.
+fn solve_hex(request: &SolveRequest) -> SolveResponse {
+    let mut grid = vec![vec![false; request.height]; request.width];
+    // A bad mask adds nothing; `validate` reports it first
+    let mask = request.mask_obstacles().unwrap_or_default();
+    for &(x, y) in request.obstacles.iter().chain(&mask) {
+        if x < request.width && y < request.height {
+            grid[x][y] = true;
+        }
+    }
+    let use_heuristic = !matches!(parse_algorithm(&request.algorithm), Some(("Dijkstra", _)));
+    let mut planner = HexAStar::new(grid).with_heuristic(use_heuristic);
+
+    let started = now_ms();
+    let path: Vec<Coord> = planner
+        .compute_path(
+            HexCoord::from_offset(request.start),
+            HexCoord::from_offset(request.goal),
+        )
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(HexCoord::to_offset)
+        .collect();
+    let elapsed_ms = now_ms() - started;
+    let trace = request.record_trace.then(|| {
+        planner
+            .search_trace()
+            .into_iter()
+            .filter_map(HexCoord::to_offset)
+            .collect()
+    });
+
+    SolveResponse {
+        algorithm: request.algorithm.clone(),
+        cost: path.len().saturating_sub(1) as f64,
+        path,
+        nodes_expanded: planner.nodes_expanded(),
+        elapsed_ms,
+        budget_exceeded: planner.budget_exceeded(),
+        trace,
+    }
+}
+
+/// Costs closer than this count as equal when checking a fixture.
+const COST_TOLERANCE: f64 = 1e-6;
+
+/// A regression scenario (see `fixtures/scenarios/`): a map plus the path
+/// cost each algorithm is expected to find, `null` when the goal is unreachable.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ScenarioFixture {
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    pub obstacles: Vec<Coord>,
+    pub start: Coord,
+    pub goal: Coord,
+    #[serde(default)]
+    pub allow_diagonals: bool,
+    pub expected: BTreeMap<String, Option<f64>>,
+}
+
+/// One algorithm whose result differs from the fixture.
+#[derive(Clone, Debug, Serialize)]
+pub struct ScenarioMismatch {
+    pub scenario: String,
+    pub algorithm: String,
+    pub expected: Option<f64>,
+    pub actual: Option<f64>,
+}
+
+/// Solve `fixture` with every algorithm it lists and report the ones whose
+/// cost (or reachability) differs; an empty list means it passed.
+pub fn check_fixture(fixture: &ScenarioFixture) -> Result<Vec<ScenarioMismatch>, String> {
+    let mut mismatches = Vec::new();
+    for (algorithm, &expected) in &fixture.expected {
+        if parse_algorithm(algorithm).is_none() {
+            return Err(format!(
+                "{}: unknown algorithm {:?}",
+                fixture.name, algorithm
+            ));
+        }
+        let request = SolveRequest {
+            width: fixture.width,
+            height: fixture.height,
+            obstacles: fixture.obstacles.clone(),
+            start: fixture.start,
+            goal: fixture.goal,
+            algorithm: algorithm.clone(),
+            allow_diagonals: fixture.allow_diagonals,
+            diagonal_cost: DEFAULT_DIAGONAL_COST,
+            turn_penalty: 0.0,
+            terrain_costs: Vec::new(),
+            thin_walls: Vec::new(),
+            obstacle_mask: None,
+            record_trace: false,
+            grid_kind: GridKind::Square,
+        };
+        request
+            .validate()
+            .map_err(|e| format!("{}: {}", fixture.name, e))?;
+        let response = solve(&request);
+        let actual = (!response.path.is_empty()).then_some(response.cost);
+
+        let matches = match (expected, actual) {
+            (Some(e), Some(a)) => (e - a).abs() <= COST_TOLERANCE,
+            (None, None) => true,
+            _ => false,
+        };
+        if !matches {
+            mismatches.push(ScenarioMismatch {
+                scenario: fixture.name.clone(),
+                algorithm: algorithm.clone(),
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn every_fixture_scenario_finds_its_expected_costs() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/scenarios");
+        let mut checked = 0;
+        for entry in fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let fixture: ScenarioFixture =
+                serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+            let mismatches = check_fixture(&fixture).unwrap();
+            assert!(
+                mismatches.is_empty(),
+                "{}: {:?}",
+                path.display(),
+                mismatches
+            );
+            checked += 1;
+        }
+        assert!(
+            checked >= 4,
+            "only {} fixtures in {}",
+            checked,
+            dir.display()
+        );
+    }
+
+    #[test]
+    fn the_diagonal_cost_defaults_to_root_two_and_prices_the_path() {
+        let json = |extra: &str| {
+            format!(
+                r#"{{"width": 5, "height": 5, "obstacles": [], "start": [0, 0],
+                    "goal": [3, 3], "algorithm": "A* (8-dir)"{}}}"#,
+                extra
+            )
+        };
+        let request: SolveRequest = serde_json::from_str(&json("")).unwrap();
+        assert_eq!(request.diagonal_cost, DEFAULT_DIAGONAL_COST);
+        assert!((solve(&request).cost - 3.0 * DEFAULT_DIAGONAL_COST).abs() < 1e-9);
+
+        let request: SolveRequest =
+            serde_json::from_str(&json(r#", "diagonal_cost": 2.0"#)).unwrap();
+        assert_eq!(solve(&request).cost, 6.0);
+
+        // Far too dear to ever take, but still a valid cost
+        let request: SolveRequest =
+            serde_json::from_str(&json(r#", "diagonal_cost": 1e300"#)).unwrap();
+        request.validate().unwrap();
+        assert_eq!(solve(&request).cost, 6.0);
+
+        let request: SolveRequest =
+            serde_json::from_str(&json(r#", "diagonal_cost": 0.5"#)).unwrap();
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn a_hex_request_solves_natively() {
+        let request: SolveRequest = serde_json::from_str(
+            r#"{"width": 5, "height": 5, "obstacles": [[2, 0]], "start": [0, 0],
+                "goal": [4, 0], "algorithm": "A*", "grid_kind": "hex", "record_trace": true}"#,
+        )
+        .unwrap();
+        request.validate().unwrap();
+
+        let response = solve(&request);
+        assert_eq!(response.path.first(), Some(&(0, 0)));
+        assert_eq!(response.path.last(), Some(&(4, 0)));
+        assert!(!response.path.contains(&(2, 0)));
+        assert_eq!(response.cost, response.path.len() as f64 - 1.0);
+        assert!(response.elapsed_ms >= 0.0);
+        assert!(response.trace.is_some_and(|trace| !trace.is_empty()));
+    }
+
+    #[test]
+    fn turn_penalties_out_of_range_are_refused() {
+        let request = |penalty: f64| SolveRequest {
+            width: 5,
+            height: 5,
+            obstacles: Vec::new(),
+            start: (0, 0),
+            goal: (4, 4),
+            algorithm: "A*".to_string(),
+            allow_diagonals: false,
+            diagonal_cost: DEFAULT_DIAGONAL_COST,
+            turn_penalty: penalty,
+            terrain_costs: Vec::new(),
+            thin_walls: Vec::new(),
+            obstacle_mask: None,
+            record_trace: false,
+            grid_kind: GridKind::Square,
+        };
+        assert!(request(0.0).validate().is_ok());
+        assert!(request(MAX_TURN_PENALTY).validate().is_ok());
+        for penalty in [-1.0, f64::NAN, f64::INFINITY, 1e300] {
+            assert!(request(penalty).validate().is_err(), "{}", penalty);
+        }
+    }
+
+    #[test]
+    fn terrain_costs_need_a_planner_that_uses_them() {
+        let json = |algorithm: &str| {
+            format!(
+                r#"{{"width": 5, "height": 5, "obstacles": [], "start": [0, 0], "goal": [4, 0],
+                    "algorithm": "{}", "terrain_costs": [[[2, 0], 4.0]]}}"#,
+                algorithm
+            )
+        };
+        for algorithm in ["A*", "Dijkstra (8-dir)", "Greedy Best-First"] {
+            let request: SolveRequest = serde_json::from_str(&json(algorithm)).unwrap();
+            assert!(request.validate().is_ok(), "{}", algorithm);
+        }
+        for algorithm in ["D*-Lite", "Field D* (8-dir)", "Theta*"] {
+            let request: SolveRequest = serde_json::from_str(&json(algorithm)).unwrap();
+            assert!(request.validate().is_err(), "{}", algorithm);
+        }
+    }
+}
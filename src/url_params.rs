@@ -0,0 +1,161 @@
+// src/url_params.rs
+//
+// Scenario settings read from the page URL, so a link like
+// `?start=2,3&goal=40,20&algo=astar&w=60&h=40&seed=42` opens that exact setup.
+// `seed` carves the maze `maze.rs` generates from it; `map` carries any other
+// static obstacles as a `serialize.rs` mask.
+// Anything missing, malformed or out of range quietly falls back to the
+// default, so a bad link still loads the app.
+
+use web_sys::UrlSearchParams;
+
+use crate::pathfinding::Coord;
+use crate::serialize::{decode_grid, encode_grid};
+
+pub const DEFAULT_WIDTH: usize = 50;
+pub const DEFAULT_HEIGHT: usize = 30;
+
+/// Grid sizes a link may ask for; smaller is cramped, larger stops fitting on screen.
+pub const SIZE_RANGE: std::ops::RangeInclusive<usize> = 10..=200;
+
+/// Grid size, endpoints and algorithm to start the app with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scenario {
+    pub width: usize,
+    pub height: usize,
+    pub start: Coord,
+    pub goal: Coord,
+    pub algorithm: Option<&'static str>, // None = use the saved preference
+    pub maze_seed: Option<u64>,          // Some = start from the maze with this seed
+    pub obstacles: Vec<Coord>,           // Static walls to load (ignored with a seed)
+}
+
+impl Default for Scenario {
+    fn default() -> Self {
+        Self::default_for(DEFAULT_WIDTH, DEFAULT_HEIGHT)
+    }
+}
+
+impl Scenario {
+    /// Default endpoints near opposite corners; (5, 5) → (45, 25) on 50x30.
+    pub fn default_for(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            start: (width / 10, height / 6),
+            goal: (width - width / 10, height - height / 6),
+            algorithm: None,
+            maze_seed: None,
+            obstacles: Vec::new(),
+        }
+    }
+
+    /// Build a scenario from query values looked up with `get`.
+    pub fn from_params(get: impl Fn(&str) -> Option<String>) -> Self {
+        let size = |key: &str, default: usize| {
+            get(key)
+                .and_then(|v| v.trim().parse::<usize>().ok())
+                .filter(|v| SIZE_RANGE.contains(v))
+                .unwrap_or(default)
+        };
+        let mut scenario = Self::default_for(size("w", DEFAULT_WIDTH), size("h", DEFAULT_HEIGHT));
+
+        let in_bounds = |&(x, y): &Coord| x < scenario.width && y < scenario.height;
+        let start = get("start").and_then(|v| parse_coord(&v)).filter(in_bounds);
+        let goal = get("goal").and_then(|v| parse_coord(&v)).filter(in_bounds);
+        let start = start.unwrap_or(scenario.start);
+        let goal = goal.unwrap_or(scenario.goal);
+        // Start and goal on one cell isn't a journey; keep the defaults instead
+        if start != goal {
+            scenario.start = start;
+            scenario.goal = goal;
+        }
+
+        scenario.algorithm = get("algo").and_then(|v| algorithm_from_param(&v));
+        scenario.maze_seed = get("seed").and_then(|v| v.trim().parse().ok());
+        // A mask for another grid size would land walls in the wrong places
+        let (width, height) = (scenario.width, scenario.height);
+        if let Some(grid) = get("map")
+            .and_then(|v| decode_grid(&v).ok())
+            .filter(|grid| grid.len() == width && grid.first().map_or(0, Vec::len) == height)
+        {
+            scenario.obstacles = (0..width)
+                .flat_map(|x| (0..height).map(move |y| (x, y)))
+                .filter(|&(x, y)| grid[x][y] && (x, y) != scenario.start && (x, y) != scenario.goal)
+                .collect();
+        }
+        scenario
+    }
+
+    /// Query string (with the leading `?`) that `from_params` reads back as this scenario.
+    pub fn to_query(&self) -> String {
+        let mut query = format!(
+            "?start={},{}&goal={},{}&w={}&h={}",
+            self.start.0, self.start.1, self.goal.0, self.goal.1, self.width, self.height
+        );
+        if let Some(slug) = self.algorithm.and_then(algorithm_param) {
+            query.push_str(&format!("&algo={}", slug));
+        }
+        if let Some(seed) = self.maze_seed {
+            query.push_str(&format!("&seed={}", seed));
+        } else if !self.obstacles.is_empty() {
+            let mut grid = vec![vec![false; self.height]; self.width];
+            for &(x, y) in &self.obstacles {
+                if x < self.width && y < self.height {
+                    grid[x][y] = true;
+                }
+            }
+            query.push_str(&format!("&map={}", encode_grid(&grid)));
+        }
+        query
+    }
+
+    /// Scenario from `window.location.search`, or the default outside a browser.
+    pub fn from_location() -> Self {
+        let params = web_sys::window()
+            .and_then(|w| w.location().search().ok())
+            .and_then(|search| UrlSearchParams::new_with_str(&search).ok());
+        match params {
+            Some(params) => Self::from_params(|key| params.get(key)),
+            None => Self::default(),
+        }
+    }
+}
+
+/// `"x,y"` with non-negative integers.
+fn parse_coord(value: &str) -> Option<Coord> {
+    let (x, y) = value.split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+/// Algorithm name from a URL-friendly slug (`astar`, `dstar-lite`, ...) or its display name.
+fn algorithm_from_param(value: &str) -> Option<&'static str> {
+    let slug: String = value
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '*')
+        .collect::<String>()
+        .to_ascii_lowercase()
+        .replace('*', "star");
+    match slug.as_str() {
+        "astar" => Some("A*"),
+        "dstar" | "dstarlite" => Some("D*-Lite"),
+        "dijkstra" => Some("Dijkstra"),
+        "greedy" | "greedybestfirst" => Some("Greedy Best-First"),
+        "fielddstar" => Some("Field D*"),
+        "theta" | "thetastar" => Some("Theta*"),
+        _ => None,
+    }
+}
+
+/// The slug `algorithm_from_param` maps back to `name`.
+fn algorithm_param(name: &str) -> Option<&'static str> {
+    match name {
+        "A*" => Some("astar"),
+        "D*-Lite" => Some("dstar-lite"),
+        "Dijkstra" => Some("dijkstra"),
+        "Greedy Best-First" => Some("greedy"),
+        "Field D*" => Some("field-dstar"),
+        "Theta*" => Some("theta"),
+        _ => None,
+    }
+}
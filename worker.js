@@ -0,0 +1,18 @@
+// Module worker that runs one solve per message, off the UI thread.
+// Start it with `new Worker('./worker.js', { type: 'module' })`; the message
+// protocol is documented in docs/ARCHITECTURE.md.
+
+import init, { solve_json } from './pkg/scout.js';
+
+const ready = init('./pkg/scout_bg.wasm');
+
+self.onmessage = async (event) => {
+    const { id, request } = event.data;
+    try {
+        await ready;
+        const response = JSON.parse(solve_json(JSON.stringify(request)));
+        self.postMessage({ id, response });
+    } catch (err) {
+        self.postMessage({ id, error: String(err) });
+    }
+};